@@ -0,0 +1,468 @@
+use crate::flatten::{flatten, FlattenConfig};
+use crate::formatter::errors::FormatterError;
+use crate::formatter::FormatConfig;
+use crate::value::{to_pointer, Value};
+
+/// How `to_csv` writes values that are objects or arrays.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Nested {
+    /// One column per leaf, named by its path: `address.city`, `tags.0`.
+    Flatten,
+    /// One column holding the value as compact JSON.
+    Json,
+}
+
+pub struct CsvConfig {
+    delimiter: char,
+    nested: Nested,
+}
+
+impl CsvConfig {
+    pub fn new() -> CsvConfig {
+        CsvConfig {
+            delimiter: ',',
+            nested: Nested::Flatten,
+        }
+    }
+
+    /// Tab-separated values, quoted the same way as CSV.
+    pub fn tsv() -> CsvConfig {
+        CsvConfig::new().with_delimiter('\t')
+    }
+
+    pub fn with_delimiter(mut self, delimiter: char) -> CsvConfig {
+        self.delimiter = delimiter;
+        self
+    }
+
+    pub fn with_nested(mut self, nested: Nested) -> CsvConfig {
+        self.nested = nested;
+        self
+    }
+}
+
+impl Default for CsvConfig {
+    fn default() -> Self {
+        CsvConfig::new()
+    }
+}
+
+/// Writes an array of objects as RFC 4180 CSV: a header row with every column any record has,
+/// in the order they first appear, then one row per record. `null` and missing members are
+/// empty fields, and fields are quoted when they hold the delimiter, a quote or a line break,
+/// or are strings `from_csv` would otherwise read as something else.
+pub fn to_csv(value: &Value, config: &CsvConfig) -> Result<String, FormatterError> {
+    let records = match value {
+        Value::Array(records) => records,
+        _ => {
+            return Err(not_representable(
+                &[],
+                "the document must be an array of objects",
+            ))
+        }
+    };
+
+    let mut columns: Vec<String> = vec![];
+    let mut rows = vec![];
+    for (index, record) in records.iter().enumerate() {
+        let pairs = match record {
+            Value::Object(pairs) => pairs,
+            _ => {
+                return Err(not_representable(
+                    &[index.to_string()],
+                    "every record must be an object",
+                ))
+            }
+        };
+        let row = match config.nested {
+            Nested::Flatten => flatten(record, &FlattenConfig::new())?
+                .as_object()
+                .cloned()
+                .unwrap_or_default(),
+            Nested::Json => pairs.clone(),
+        };
+        for (column, _) in &row {
+            if !columns.contains(column) {
+                columns.push(column.clone());
+            }
+        }
+        rows.push(row);
+    }
+
+    let mut csv = String::new();
+    let header = columns.iter().map(|column| (column.clone(), false));
+    write_row(header.collect(), config, &mut csv);
+    for row in &rows {
+        let fields = columns
+            .iter()
+            .map(|column| match row.iter().find(|(name, _)| name == column) {
+                Some((_, value)) => field(value),
+                None => (String::new(), false),
+            })
+            .collect();
+        write_row(fields, config, &mut csv);
+    }
+    Ok(csv)
+}
+
+/// Reads CSV with a header row into an array of objects, one per record. Unquoted fields that
+/// are JSON numbers or `true`/`false` become numbers and booleans and unquoted empty fields
+/// `null`; quoted fields are always strings, so `"007"` stays a string.
+///
+/// Fails with `InvalidCsv` and the line number for an unclosed quote, text after a closing
+/// quote or a record with more fields than the header.
+pub fn from_csv(input: &str, config: &CsvConfig) -> Result<Value, FormatterError> {
+    let mut records = Reader::new(input, config.delimiter).records()?.into_iter();
+    let header = match records.next() {
+        Some((_, header)) => header,
+        None => return Ok(Value::Array(vec![])),
+    };
+
+    let mut columns: Vec<String> = vec![];
+    for (name, _) in header {
+        if columns.contains(&name) {
+            return Err(FormatterError::DuplicateKeyEntry(name));
+        }
+        columns.push(name);
+    }
+
+    let mut objects = vec![];
+    for (line, fields) in records {
+        if fields.len() > columns.len() {
+            return Err(FormatterError::InvalidCsv(line));
+        }
+        let mut pairs = fields
+            .into_iter()
+            .zip(&columns)
+            .map(|((text, quoted), column)| (column.clone(), infer(text, quoted)))
+            .collect::<Vec<(String, Value)>>();
+        for column in &columns[pairs.len()..] {
+            pairs.push((column.clone(), Value::Null));
+        }
+        objects.push(Value::Object(pairs));
+    }
+    Ok(Value::Array(objects))
+}
+
+/// Reads CSV (or, with `CsvConfig::tsv()`, TSV) and prints it as JSON with `config`.
+pub fn csv_to_json(
+    input: &str,
+    csv: Option<CsvConfig>,
+    config: Option<FormatConfig>,
+) -> Result<String, FormatterError> {
+    Ok(from_csv(input, &csv.unwrap_or_default())?.format_like(input, config))
+}
+
+/// A field's text, and whether it must be quoted to be read back as a string: `""`, `"12"` and
+/// `"true"` would otherwise become `null`, a number and a boolean.
+fn field(value: &Value) -> (String, bool) {
+    match value {
+        Value::Null => (String::new(), false),
+        Value::String(string) => {
+            let retyped = !matches!(infer(string.clone(), false), Value::String(_));
+            (string.clone(), retyped)
+        }
+        value => (value.to_string(), false),
+    }
+}
+
+fn write_row(fields: Vec<(String, bool)>, config: &CsvConfig, csv: &mut String) {
+    for (index, (field, quote)) in fields.into_iter().enumerate() {
+        if index > 0 {
+            csv.push(config.delimiter);
+        }
+        if quote || field.contains([config.delimiter, '"', '\r', '\n']) {
+            csv.push('"');
+            csv.push_str(&field.replace('"', "\"\""));
+            csv.push('"');
+        } else {
+            csv.push_str(&field);
+        }
+    }
+    csv.push_str("\r\n");
+}
+
+fn infer(text: String, quoted: bool) -> Value {
+    if quoted {
+        return Value::String(text);
+    }
+    match text.as_str() {
+        "" => Value::Null,
+        "true" => Value::Bool(true),
+        "false" => Value::Bool(false),
+        _ if is_json_number(&text) => match text.parse::<f64>() {
+            // Out of range for a double, like `1e400`, or more digits than it holds, like
+            // `12345678901234567890`, so keep the text.
+            Ok(number) if number.is_finite() && is_exact(number, &text) => Value::Number(number),
+            _ => Value::String(text),
+        },
+        _ => Value::String(text),
+    }
+}
+
+/// Whether `number` has the same significant digits as the `text` it was read from, so
+/// `-1.5e2` and `0.10` are exact but `12345678901234567890` and `1e-400` are not.
+fn is_exact(number: f64, text: &str) -> bool {
+    fn significant(number: &str) -> &str {
+        let mantissa = number.split(['e', 'E']).next().unwrap_or_default();
+        mantissa
+            .trim_start_matches(['-', '0', '.'])
+            .trim_end_matches(['0', '.'])
+    }
+    let shortest = format!("{:e}", number);
+    significant(&shortest).replace('.', "") == significant(text).replace('.', "")
+}
+
+/// The JSON number grammar, so `007`, `+1`, `.5` and `1e` stay strings.
+fn is_json_number(text: &str) -> bool {
+    let unsigned = text.strip_prefix('-').unwrap_or(text);
+    let (mantissa, exponent) = match unsigned.find(['e', 'E']) {
+        Some(index) => (&unsigned[..index], Some(&unsigned[index + 1..])),
+        None => (unsigned, None),
+    };
+    let (whole, fraction) = match mantissa.split_once('.') {
+        Some((whole, fraction)) => (whole, Some(fraction)),
+        None => (mantissa, None),
+    };
+    let digits = |part: &str| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit());
+
+    digits(whole)
+        && (whole == "0" || !whole.starts_with('0'))
+        && fraction.is_none_or(digits)
+        && exponent
+            .is_none_or(|exponent| digits(exponent.strip_prefix(['+', '-']).unwrap_or(exponent)))
+}
+
+/// Splits RFC 4180 text into records of `(text, quoted)` fields, with the line each starts on.
+struct Reader<'a> {
+    characters: std::iter::Peekable<std::str::Chars<'a>>,
+    delimiter: char,
+    line: usize,
+}
+
+type Record = (usize, Vec<(String, bool)>);
+
+impl<'a> Reader<'a> {
+    fn new(input: &'a str, delimiter: char) -> Reader<'a> {
+        Reader {
+            characters: input.chars().peekable(),
+            delimiter,
+            line: 1,
+        }
+    }
+
+    fn records(&mut self) -> Result<Vec<Record>, FormatterError> {
+        let mut records = vec![];
+        while self.characters.peek().is_some() {
+            let line = self.line;
+            let record = self.record()?;
+            // Blank lines hold no record.
+            if record.len() > 1 || record[0] != (String::new(), false) {
+                records.push((line, record));
+            }
+        }
+        Ok(records)
+    }
+
+    fn record(&mut self) -> Result<Vec<(String, bool)>, FormatterError> {
+        let mut fields = vec![];
+        loop {
+            let field = self.field()?;
+            fields.push(field);
+            match self.characters.next() {
+                Some(character) if character == self.delimiter => continue,
+                Some('\r') => {
+                    self.characters.next_if_eq(&'\n');
+                    self.line += 1;
+                    return Ok(fields);
+                }
+                Some('\n') => {
+                    self.line += 1;
+                    return Ok(fields);
+                }
+                None => return Ok(fields),
+                Some(_) => return Err(FormatterError::InvalidCsv(self.line)),
+            }
+        }
+    }
+
+    /// Reads one field, leaving the delimiter or line break after it.
+    fn field(&mut self) -> Result<(String, bool), FormatterError> {
+        let mut text = String::new();
+        if self.characters.next_if_eq(&'"').is_none() {
+            let delimiter = self.delimiter;
+            while let Some(character) = self
+                .characters
+                .next_if(|c| *c != delimiter && *c != '\r' && *c != '\n')
+            {
+                text.push(character);
+            }
+            return Ok((text, false));
+        }
+
+        let start = self.line;
+        loop {
+            match self.characters.next() {
+                Some('"') if self.characters.next_if_eq(&'"').is_some() => text.push('"'),
+                Some('"') => return Ok((text, true)),
+                Some(character) => {
+                    if character == '\n' {
+                        self.line += 1;
+                    }
+                    text.push(character);
+                }
+                None => return Err(FormatterError::InvalidCsv(start)),
+            }
+        }
+    }
+}
+
+fn not_representable(pointer: &[String], reason: &'static str) -> FormatterError {
+    FormatterError::NotRepresentable {
+        format: "CSV",
+        pointer: to_pointer(pointer.iter()),
+        reason,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn csv(json: &str, config: CsvConfig) -> String {
+        to_csv(&crate::parse(json).unwrap(), &config).unwrap()
+    }
+
+    #[test]
+    fn columns_are_the_union_of_all_records() {
+        assert_eq!(
+            csv(
+                r#"[{"id": 1, "name": "a"}, {"id": 2, "email": "b@example.com", "name": null}]"#,
+                CsvConfig::new()
+            ),
+            "id,name,email\r\n1,a,\r\n2,,b@example.com\r\n"
+        );
+    }
+
+    #[test]
+    fn nested_values_are_flattened_or_encoded() {
+        let json = r#"[{"id": 1, "address": {"city": "Oslo", "zip": "0150"}, "tags": ["x", "y"], "extra": {}}]"#;
+        assert_eq!(
+            csv(json, CsvConfig::new()),
+            "id,address.city,address.zip,tags.0,tags.1,extra\r\n1,Oslo,0150,x,y,{}\r\n"
+        );
+        assert_eq!(
+            csv(json, CsvConfig::tsv().with_nested(Nested::Json)),
+            "id\taddress\ttags\textra\r\n1\t\"{\"\"city\"\":\"\"Oslo\"\",\"\"zip\"\":\"\"0150\"\"}\"\t\"[\"\"x\"\",\"\"y\"\"]\"\t{}\r\n"
+        );
+    }
+
+    #[test]
+    fn quotes_fields_that_need_it() {
+        assert_eq!(
+            csv(
+                r#"[{"text": "a, b", "quote": "say \"hi\"", "lines": "1\n2", "plain": "x y"}]"#,
+                CsvConfig::new()
+            ),
+            "text,quote,lines,plain\r\n\"a, b\",\"say \"\"hi\"\"\",\"1\n2\",x y\r\n"
+        );
+    }
+
+    #[test]
+    fn only_arrays_of_objects_can_be_written() {
+        let error = |pointer: &str, reason| FormatterError::NotRepresentable {
+            format: "CSV",
+            pointer: pointer.to_owned(),
+            reason,
+        };
+        let config = CsvConfig::new();
+        assert_eq!(
+            to_csv(&Value::Null, &config).unwrap_err(),
+            error("", "the document must be an array of objects")
+        );
+        assert_eq!(
+            to_csv(&crate::parse(r#"[{}, 1]"#).unwrap(), &config).unwrap_err(),
+            error("/1", "every record must be an object")
+        );
+        assert_eq!(
+            to_csv(
+                &crate::parse(r#"[{"a.b": 1, "a": {"b": 2}}]"#).unwrap(),
+                &config
+            )
+            .unwrap_err(),
+            FormatterError::ConflictingKeys(String::from("a.b"))
+        );
+    }
+
+    #[test]
+    fn reads_records_and_infers_types() {
+        let input = "id,name,active,zip,score,note\r\n1,\"Smith, J\",true,007,-1.5e2,\n2,\"say \"\"hi\"\"\",false,\"123\",,\"\"\n";
+        assert_eq!(
+            from_csv(input, &CsvConfig::new()).unwrap().to_string(),
+            r#"[{"id":1,"name":"Smith, J","active":true,"zip":"007","score":-150,"note":null},{"id":2,"name":"say \"hi\"","active":false,"zip":"123","score":null,"note":""}]"#
+        );
+    }
+
+    #[test]
+    fn keeps_numbers_out_of_range_as_strings() {
+        assert_eq!(
+            from_csv("a,b\n1e400,-1e400\n", &CsvConfig::new())
+                .unwrap()
+                .to_string(),
+            r#"[{"a":"1e400","b":"-1e400"}]"#
+        );
+    }
+
+    #[test]
+    fn keeps_numbers_a_double_can_not_hold_exactly_as_strings() {
+        assert_eq!(
+            from_csv(
+                "a,b,c,d
+12345678901234567890,1e-400,9007199254740993,9007199254740992
+",
+                &CsvConfig::new()
+            )
+            .unwrap()
+            .to_string(),
+            r#"[{"a":"12345678901234567890","b":"1e-400","c":"9007199254740993","d":9007199254740992}]"#
+        );
+    }
+
+    #[test]
+    fn reads_tsv_with_line_breaks_and_short_rows() {
+        let input = "a\tb\n\"1\n2\"\n\n3\t4\n";
+        assert_eq!(
+            from_csv(input, &CsvConfig::tsv()).unwrap().to_string(),
+            r#"[{"a":"1\n2","b":null},{"a":3,"b":4}]"#
+        );
+    }
+
+    #[test]
+    fn reports_malformed_csv() {
+        let config = CsvConfig::new();
+        assert_eq!(
+            from_csv("a\n\"open\n", &config).unwrap_err(),
+            FormatterError::InvalidCsv(2)
+        );
+        assert_eq!(
+            from_csv("a\n\"x\"y\n", &config).unwrap_err(),
+            FormatterError::InvalidCsv(2)
+        );
+        assert_eq!(
+            from_csv("a\n1\n2,3\n", &config).unwrap_err(),
+            FormatterError::InvalidCsv(3)
+        );
+        assert_eq!(
+            from_csv("a,a\n", &config).unwrap_err(),
+            FormatterError::DuplicateKeyEntry(String::from("a"))
+        );
+    }
+
+    #[test]
+    fn round_trips_through_json() {
+        let json = r#"[{"id":1,"name":"a, b","note":"","code":"12"},{"id":2,"name":"c","note":null,"code":"true"}]"#;
+        let config = CsvConfig::new();
+        let written = to_csv(&crate::parse(json).unwrap(), &config).unwrap();
+        assert_eq!(from_csv(&written, &config).unwrap().to_string(), json);
+    }
+}
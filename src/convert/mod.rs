@@ -0,0 +1,7 @@
+//! Converting documents to and from other formats.
+mod csv;
+mod toml;
+mod yaml;
+pub use self::toml::to_toml;
+pub use csv::{csv_to_json, from_csv, to_csv, CsvConfig, Nested};
+pub use yaml::to_yaml;
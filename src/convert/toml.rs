@@ -0,0 +1,279 @@
+use crate::datetime::{is_date, is_date_time, is_local_date_time, is_time};
+use crate::formatter::errors::FormatterError;
+use crate::value::{to_pointer, Value};
+
+/// Writes `value` as a TOML 1.0 document. Nested objects become `[tables]` and arrays of
+/// objects `[[arrays of tables]]`, while objects inside other arrays are written inline.
+/// Strings holding an RFC 3339 date, time or date-time are written as TOML dates.
+///
+/// Fails with `NotRepresentable` for `null`, which TOML has no value for, and for a document
+/// that isn't an object.
+pub fn to_toml(value: &Value) -> Result<String, FormatterError> {
+    let pairs = match value {
+        Value::Object(pairs) => pairs,
+        _ => return Err(not_representable(&[], "the document must be an object")),
+    };
+    let mut toml = String::new();
+    write_table(&mut vec![], &mut vec![], pairs, &mut toml)?;
+    Ok(toml)
+}
+
+/// Writes the members of the table at `path`: its plain values first, then its sub-tables.
+/// `pointer` is the same location in the document, which includes array indexes.
+fn write_table(
+    path: &mut Vec<String>,
+    pointer: &mut Vec<String>,
+    pairs: &[(String, Value)],
+    toml: &mut String,
+) -> Result<(), FormatterError> {
+    for (key, value) in pairs {
+        if !is_table(value) && !is_array_of_tables(value) {
+            pointer.push(key.clone());
+            let inline = inline(pointer, value)?;
+            pointer.pop();
+            toml.push_str(&format!("{} = {}\n", self::key(key), inline));
+        }
+    }
+
+    for (key, value) in pairs {
+        path.push(key.clone());
+        pointer.push(key.clone());
+        match value {
+            Value::Object(members) => {
+                if needs_header(members) {
+                    separate(toml);
+                    toml.push_str(&format!("[{}]\n", header(path)));
+                }
+                write_table(path, pointer, members, toml)?;
+            }
+            Value::Array(items) if is_array_of_tables(value) => {
+                for (index, item) in items.iter().enumerate() {
+                    separate(toml);
+                    toml.push_str(&format!("[[{}]]\n", header(path)));
+                    pointer.push(index.to_string());
+                    let members = item.as_object().map(Vec::as_slice).unwrap_or_default();
+                    write_table(path, pointer, members, toml)?;
+                    pointer.pop();
+                }
+            }
+            _ => {}
+        }
+        path.pop();
+        pointer.pop();
+    }
+    Ok(())
+}
+
+fn is_table(value: &Value) -> bool {
+    matches!(value, Value::Object(_))
+}
+
+fn is_array_of_tables(value: &Value) -> bool {
+    match value {
+        Value::Array(items) => !items.is_empty() && items.iter().all(is_table),
+        _ => false,
+    }
+}
+
+/// A table only made of sub-tables is created by their headers, so it doesn't need its own.
+fn needs_header(members: &[(String, Value)]) -> bool {
+    members.is_empty()
+        || members
+            .iter()
+            .any(|(_, value)| !is_table(value) && !is_array_of_tables(value))
+}
+
+/// A blank line before every header but the first line of the document.
+fn separate(toml: &mut String) {
+    if !toml.is_empty() {
+        toml.push('\n');
+    }
+}
+
+fn header(path: &[String]) -> String {
+    path.iter()
+        .map(|name| key(name))
+        .collect::<Vec<String>>()
+        .join(".")
+}
+
+fn inline(pointer: &mut Vec<String>, value: &Value) -> Result<String, FormatterError> {
+    match value {
+        Value::Null => Err(not_representable(pointer, "TOML has no null")),
+        Value::Bool(boolean) => Ok(boolean.to_string()),
+        Value::Number(number) => Ok(self::number(*number)),
+        Value::String(string) if is_datetime(string) => Ok(string.clone()),
+        Value::String(string) => Ok(self::string(string)),
+        Value::Array(items) => {
+            let mut inlined = vec![];
+            for (index, item) in items.iter().enumerate() {
+                pointer.push(index.to_string());
+                inlined.push(inline(pointer, item)?);
+                pointer.pop();
+            }
+            Ok(format!("[{}]", inlined.join(", ")))
+        }
+        Value::Object(pairs) if pairs.is_empty() => Ok(String::from("{}")),
+        Value::Object(pairs) => {
+            let mut members = vec![];
+            for (name, member) in pairs {
+                pointer.push(name.clone());
+                members.push(format!("{} = {}", key(name), inline(pointer, member)?));
+                pointer.pop();
+            }
+            Ok(format!("{{ {} }}", members.join(", ")))
+        }
+    }
+}
+
+/// Whole numbers that fit an `i64` are integers, anything else a float.
+fn number(number: f64) -> String {
+    if number.fract() != 0.0 {
+        number.to_string()
+    } else if number.abs() < 9.2e18 {
+        format!("{}", number as i64)
+    } else {
+        format!("{:e}", number)
+    }
+}
+
+fn is_datetime(string: &str) -> bool {
+    is_date_time(string) || is_local_date_time(string) || is_date(string) || is_time(string)
+}
+
+fn key(name: &str) -> String {
+    let bare = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    match bare {
+        true => name.to_owned(),
+        false => string(name),
+    }
+}
+
+/// A basic string, with everything TOML doesn't allow raw escaped.
+fn string(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('"');
+    for character in text.chars() {
+        match character {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\u{8}' => quoted.push_str("\\b"),
+            '\t' => quoted.push_str("\\t"),
+            '\n' => quoted.push_str("\\n"),
+            '\u{c}' => quoted.push_str("\\f"),
+            '\r' => quoted.push_str("\\r"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04X}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+fn not_representable(pointer: &[String], reason: &'static str) -> FormatterError {
+    FormatterError::NotRepresentable {
+        format: "TOML",
+        pointer: to_pointer(pointer.iter()),
+        reason,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn toml(json: &str) -> Result<String, FormatterError> {
+        to_toml(&crate::parse(json).unwrap())
+    }
+
+    #[test]
+    fn objects_become_tables() {
+        assert_eq!(
+            toml(
+                r#"{"package": {"name": "demo", "version": "0.1.0", "authors": ["a", "b"]},
+                    "dependencies": {"serde": {"version": "1", "features": ["derive"]}, "log": "0.4"},
+                    "target": {"cfg(unix)": {"dependencies": {"libc": "0.2"}}},
+                    "features": {}}"#
+            )
+            .unwrap(),
+            r#"[package]
+name = "demo"
+version = "0.1.0"
+authors = ["a", "b"]
+
+[dependencies]
+log = "0.4"
+
+[dependencies.serde]
+version = "1"
+features = ["derive"]
+
+[target."cfg(unix)".dependencies]
+libc = "0.2"
+
+[features]
+"#
+        );
+    }
+
+    #[test]
+    fn arrays_of_objects_become_arrays_of_tables() {
+        assert_eq!(
+            toml(r#"{"bin": [{"name": "a", "test": {"harness": false}}, {"name": "b"}], "mixed": [1, {"x": 2}]}"#)
+                .unwrap(),
+            r#"mixed = [1, { x = 2 }]
+
+[[bin]]
+name = "a"
+
+[bin.test]
+harness = false
+
+[[bin]]
+name = "b"
+"#
+        );
+    }
+
+    #[test]
+    fn scalars() {
+        assert_eq!(
+            toml(r#"{"int": 3, "float": 2.5, "big": 1e300, "on": true, "text": "a\"b\n", "odd key": 1}"#)
+                .unwrap(),
+            "int = 3\nfloat = 2.5\nbig = 1e300\non = true\ntext = \"a\\\"b\\n\"\n\"odd key\" = 1\n"
+        );
+    }
+
+    #[test]
+    fn detects_dates() {
+        assert_eq!(
+            toml(r#"{"at": "1979-05-27T07:32:00Z", "local": "1979-05-27T07:32:00", "day": "1979-05-27", "time": "07:32:00", "not": "1979-13-27"}"#)
+                .unwrap(),
+            "at = 1979-05-27T07:32:00Z\nlocal = 1979-05-27T07:32:00\nday = 1979-05-27\ntime = 07:32:00\nnot = \"1979-13-27\"\n"
+        );
+    }
+
+    #[test]
+    fn rejects_what_toml_cant_express() {
+        let error = |reason, pointer: &str| FormatterError::NotRepresentable {
+            format: "TOML",
+            pointer: pointer.to_owned(),
+            reason,
+        };
+        assert_eq!(
+            toml("[1]").unwrap_err(),
+            error("the document must be an object", "")
+        );
+        assert_eq!(
+            toml(r#"{"a": {"b": [1, null]}}"#).unwrap_err(),
+            error("TOML has no null", "/a/b/1")
+        );
+        assert_eq!(
+            toml(r#"{"items": [{"x": 1}, {"x": null}]}"#).unwrap_err(),
+            error("TOML has no null", "/items/1/x")
+        );
+    }
+}
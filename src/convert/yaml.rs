@@ -0,0 +1,311 @@
+use crate::value::Value;
+
+const INDENT: usize = 2;
+
+/// Words some YAML parsers read as something other than a string (YAML 1.1 booleans included,
+/// since plenty of tooling still follows it). Compared case-insensitively.
+const RESERVED: [&str; 11] = [
+    "null", "~", "true", "false", "yes", "no", "on", "off", "y", "n", "<<",
+];
+
+/// Writes `value` as a YAML 1.2 document in block style. Strings are only quoted when a parser
+/// would otherwise read them as something else, and multi-line strings become literal block
+/// scalars.
+pub fn to_yaml(value: &Value) -> String {
+    let mut yaml = String::new();
+    match value {
+        Value::Object(pairs) if !pairs.is_empty() => write_members(pairs, 0, &mut yaml),
+        Value::Array(items) if !items.is_empty() => write_items(items, 0, &mut yaml),
+        value => {
+            write_value(value, 0, &mut yaml);
+            yaml.remove(0);
+        }
+    }
+    yaml
+}
+
+/// Writes `value` after a `key:` or `- ` belonging to a node at `indent`.
+fn write_value(value: &Value, indent: usize, yaml: &mut String) {
+    match value {
+        Value::Object(pairs) if !pairs.is_empty() => {
+            yaml.push('\n');
+            write_members(pairs, indent + INDENT, yaml);
+        }
+        Value::Array(items) if !items.is_empty() => {
+            yaml.push('\n');
+            write_items(items, indent + INDENT, yaml);
+        }
+        Value::String(string) if is_block(string) => {
+            let chomping = match (string.ends_with('\n'), string.ends_with("\n\n")) {
+                (false, _) => "-",
+                (true, false) => "",
+                (true, true) => "+",
+            };
+            yaml.push_str(&format!(" |{}\n", chomping));
+            let content = string.strip_suffix('\n').unwrap_or(string);
+            for line in content.split('\n') {
+                if !line.is_empty() {
+                    yaml.push_str(&" ".repeat(indent + INDENT));
+                    yaml.push_str(line);
+                }
+                yaml.push('\n');
+            }
+        }
+        value => {
+            yaml.push(' ');
+            yaml.push_str(&scalar(value));
+            yaml.push('\n');
+        }
+    }
+}
+
+fn write_members(pairs: &[(String, Value)], indent: usize, yaml: &mut String) {
+    for (key, value) in pairs {
+        yaml.push_str(&" ".repeat(indent));
+        yaml.push_str(&string(key));
+        yaml.push(':');
+        write_value(value, indent, yaml);
+    }
+}
+
+fn write_items(items: &[Value], indent: usize, yaml: &mut String) {
+    for item in items {
+        yaml.push_str(&" ".repeat(indent));
+        yaml.push('-');
+        match item {
+            // A mapping or sequence inside a sequence starts on the dash's line.
+            Value::Object(_) | Value::Array(_) if !is_empty(item) => {
+                let mut nested = String::new();
+                write_value(item, indent, &mut nested);
+                yaml.push(' ');
+                yaml.push_str(&nested[1 + indent + INDENT..]);
+            }
+            item => write_value(item, indent, yaml),
+        }
+    }
+}
+
+fn is_empty(value: &Value) -> bool {
+    match value {
+        Value::Object(pairs) => pairs.is_empty(),
+        Value::Array(items) => items.is_empty(),
+        _ => false,
+    }
+}
+
+fn scalar(value: &Value) -> String {
+    match value {
+        Value::Null => String::from("null"),
+        Value::Bool(boolean) => boolean.to_string(),
+        // `1e400` parses to infinity, and a built `Value` can hold NaN too.
+        Value::Number(number) if number.is_nan() => String::from(".nan"),
+        Value::Number(number) if number.is_infinite() => match *number > 0.0 {
+            true => String::from(".inf"),
+            false => String::from("-.inf"),
+        },
+        Value::Number(number) => number.to_string(),
+        Value::String(text) => string(text),
+        Value::Object(_) => String::from("{}"),
+        Value::Array(_) => String::from("[]"),
+    }
+}
+
+fn string(text: &str) -> String {
+    match needs_quotes(text) {
+        true => double_quoted(text),
+        false => text.to_owned(),
+    }
+}
+
+/// Multi-line text without anything a literal block can't hold. Its indentation is detected
+/// from the first non-empty line, so that line can't start with whitespace.
+fn is_block(text: &str) -> bool {
+    let first = text.split('\n').find(|line| !line.is_empty());
+    text.contains('\n')
+        && first.is_some_and(|line| !line.starts_with([' ', '\t']))
+        && !text
+            .chars()
+            .any(|c| c != '\n' && c != '\t' && is_special(c))
+}
+
+fn needs_quotes(text: &str) -> bool {
+    let first = match text.chars().next() {
+        Some(first) => first,
+        None => return true,
+    };
+    let second = text.chars().nth(1);
+
+    RESERVED.iter().any(|word| word.eq_ignore_ascii_case(text))
+        || looks_like_number(text)
+        || looks_like_date(text)
+        || text.starts_with([' ', '\t'])
+        || text.ends_with([' ', '\t', ':'])
+        || "[]{},#&*!|>'\"%@`".contains(first)
+        || (matches!(first, '-' | '?' | ':') && second.is_none_or(|c| c == ' '))
+        || text.starts_with("---")
+        || text.starts_with("...")
+        || text.contains(": ")
+        || text.contains(" #")
+        || text
+            .chars()
+            .any(|c| c == '\t' || c == '\n' || is_special(c))
+}
+
+/// Anything YAML 1.1 or 1.2 might read as a number, like `012`, `1_000`, `0x1F` or `.inf`.
+fn looks_like_number(text: &str) -> bool {
+    let unsigned = text.strip_prefix(['+', '-']).unwrap_or(text);
+    let lower = unsigned.to_ascii_lowercase();
+
+    text.parse::<f64>().is_ok()
+        || matches!(lower.as_str(), ".inf" | ".nan")
+        || ["0x", "0o", "0b"]
+            .iter()
+            .any(|prefix| lower.starts_with(prefix))
+        || (unsigned.chars().any(|c| c.is_ascii_digit())
+            && unsigned
+                .chars()
+                .all(|c| c.is_ascii_digit() || "_.:eE+-".contains(c)))
+}
+
+/// YAML 1.1 timestamps such as `2024-05-01`, which some parsers turn into dates.
+fn looks_like_date(text: &str) -> bool {
+    let bytes = text.as_bytes();
+    bytes.len() >= 8
+        && bytes[..4].iter().all(u8::is_ascii_digit)
+        && bytes[4] == b'-'
+        && text[5..].starts_with(|c: char| c.is_ascii_digit())
+}
+
+/// Characters that can only be written escaped, inside double quotes.
+fn is_special(character: char) -> bool {
+    character.is_control() || matches!(character, '\u{2028}' | '\u{2029}' | '\u{feff}')
+}
+
+fn double_quoted(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('"');
+    for character in text.chars() {
+        match character {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            '\r' => quoted.push_str("\\r"),
+            '\u{85}' => quoted.push_str("\\N"),
+            '\u{2028}' => quoted.push_str("\\L"),
+            '\u{2029}' => quoted.push_str("\\P"),
+            c if (c as u32) < 0x100 && is_special(c) => {
+                quoted.push_str(&format!("\\x{:02X}", c as u32))
+            }
+            c if is_special(c) => quoted.push_str(&format!("\\u{:04X}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn yaml(json: &str) -> String {
+        to_yaml(&crate::parse(json).unwrap())
+    }
+
+    #[test]
+    fn nests_in_block_style() {
+        assert_eq!(
+            yaml(
+                r#"{"apiVersion": "v1", "metadata": {"name": "web", "labels": {}},
+                    "spec": {"ports": [{"port": 80, "protocol": "TCP"}], "args": [["a", "b"], []]}}"#
+            ),
+            "apiVersion: v1
+metadata:
+  name: web
+  labels: {}
+spec:
+  ports:
+    - port: 80
+      protocol: TCP
+  args:
+    - - a
+      - b
+    - []
+"
+        );
+    }
+
+    #[test]
+    fn scalars_at_the_top_level() {
+        assert_eq!(yaml("null"), "null\n");
+        assert_eq!(yaml("1.5"), "1.5\n");
+        assert_eq!(yaml(r#""plain""#), "plain\n");
+        assert_eq!(yaml("[]"), "[]\n");
+    }
+
+    #[test]
+    fn writes_non_finite_numbers_as_yaml_floats() {
+        assert_eq!(yaml("[1e400, -1e400]"), "- .inf\n- -.inf\n");
+        assert_eq!(to_yaml(&Value::Number(f64::NAN)), ".nan\n");
+    }
+
+    #[test]
+    fn quotes_strings_that_would_change_type() {
+        for (string, quoted) in [
+            ("yes", r#""yes""#),
+            ("Off", r#""Off""#),
+            ("null", r#""null""#),
+            ("", r#""""#),
+            ("012", r#""012""#),
+            ("1e3", r#""1e3""#),
+            ("0x1F", r#""0x1F""#),
+            (".inf", r#"".inf""#),
+            ("2024-05-01", r#""2024-05-01""#),
+            (" padded", r#"" padded""#),
+            ("a: b", r#""a: b""#),
+            ("key:", r#""key:""#),
+            ("- item", r#""- item""#),
+            ("*ref", r#""*ref""#),
+            ("a #comment", r#""a #comment""#),
+            ("tab\there", r#""tab\there""#),
+            ("bell\u{7}", r#""bell\x07""#),
+        ] {
+            assert_eq!(string_of(string), quoted, "{:?}", string);
+        }
+        for plain in [
+            "hello world",
+            "http://example.com/a",
+            "-flag",
+            "a#b",
+            "1.2.3a",
+            "ünïcödé",
+        ] {
+            assert_eq!(string_of(plain), plain);
+        }
+    }
+
+    fn string_of(text: &str) -> String {
+        to_yaml(&Value::String(text.to_owned()))
+            .trim_end_matches('\n')
+            .to_owned()
+    }
+
+    #[test]
+    fn multi_line_strings_are_literal_blocks() {
+        assert_eq!(
+            yaml(r#"{"script": "set -e\n\n  make\n", "note": "a\nb", "kept": "x\n\n"}"#),
+            "script: |\n  set -e\n\n    make\nnote: |-\n  a\n  b\nkept: |+\n  x\n\n"
+        );
+        assert_eq!(yaml(r#"["a\nb"]"#), "- |-\n  a\n  b\n");
+        assert_eq!(yaml(r#"{"indented": "  a\nb"}"#), "indented: \"  a\\nb\"\n");
+    }
+
+    #[test]
+    fn keys_are_quoted_too() {
+        assert_eq!(
+            yaml(r#"{"on": 1, "a b": 2, "": 3}"#),
+            "\"on\": 1\na b: 2\n\"\": 3\n"
+        );
+    }
+}
@@ -0,0 +1,247 @@
+use crate::formatter::errors::FormatterError;
+use crate::tokenizer::{get_end_index, get_start_index, tokenize, Token};
+
+use super::{SyntaxElement, SyntaxKind, SyntaxNode, SyntaxToken};
+
+pub fn build(input: &str) -> Result<SyntaxNode, FormatterError> {
+    let mut builder = Builder {
+        tokens: lex(input)?,
+        position: 0,
+    };
+
+    let mut children = vec![];
+    builder.trivia(&mut children);
+    children.push(builder.value()?);
+    builder.trivia(&mut children);
+
+    if let Some(token) = builder.peek() {
+        return Err(FormatterError::UnexpectedToken(token.offset));
+    }
+
+    Ok(node(SyntaxKind::Document, 0, children))
+}
+
+/// Turns the tokenizer output into syntax tokens carrying their source text,
+/// joining runs of whitespace into a single token.
+fn lex(input: &str) -> Result<Vec<SyntaxToken>, FormatterError> {
+    let chars = input.chars().collect::<Vec<char>>();
+    let mut syntax_tokens: Vec<SyntaxToken> = vec![];
+    let mut offset = 0;
+
+    for token in tokenize(input)? {
+        let text = chars[get_start_index(&token)..=get_end_index(&token)]
+            .iter()
+            .collect::<String>();
+        let kind = kind_of(&token);
+        let len = text.len();
+        // The tokenizer lets through some numbers that aren't, like a lone `-`.
+        if kind == SyntaxKind::Number && text.parse::<f64>().is_err() {
+            return Err(FormatterError::InvalidNumber(offset));
+        }
+
+        match syntax_tokens.last_mut() {
+            Some(last) if kind == SyntaxKind::WhiteSpace && last.kind == SyntaxKind::WhiteSpace => {
+                last.text.push_str(&text)
+            }
+            _ => syntax_tokens.push(SyntaxToken { kind, text, offset }),
+        }
+        offset += len;
+    }
+
+    Ok(syntax_tokens)
+}
+
+fn kind_of(token: &Token) -> SyntaxKind {
+    match token {
+        Token::CloseBrace(_) => SyntaxKind::CloseBrace,
+        Token::CloseSquareBraket(_) => SyntaxKind::CloseSquareBraket,
+        Token::Colon(_) => SyntaxKind::Colon,
+        Token::Comma(_) => SyntaxKind::Comma,
+        Token::OpenBrace(_) => SyntaxKind::OpenBrace,
+        Token::OpenSquareBraket(_) => SyntaxKind::OpenSquareBraket,
+        Token::WhiteSpace(_, _) => SyntaxKind::WhiteSpace,
+        Token::Null(_, _) => SyntaxKind::Null,
+        Token::True(_, _) => SyntaxKind::True,
+        Token::False(_, _) => SyntaxKind::False,
+        Token::Number(_, _) => SyntaxKind::Number,
+        Token::StringLiteral(_, _) => SyntaxKind::StringLiteral,
+    }
+}
+
+fn node(kind: SyntaxKind, offset: usize, children: Vec<SyntaxElement>) -> SyntaxNode {
+    SyntaxNode {
+        kind,
+        offset,
+        children,
+    }
+}
+
+struct Builder {
+    tokens: Vec<SyntaxToken>,
+    position: usize,
+}
+
+impl Builder {
+    fn peek(&self) -> Option<&SyntaxToken> {
+        self.tokens.get(self.position)
+    }
+
+    fn peek_kind(&self) -> Option<SyntaxKind> {
+        self.peek().map(|token| token.kind)
+    }
+
+    fn bump(&mut self) -> SyntaxElement {
+        let token = self.tokens[self.position].clone();
+        self.position += 1;
+        SyntaxElement::Token(token)
+    }
+
+    fn trivia(&mut self, children: &mut Vec<SyntaxElement>) {
+        while self.peek_kind() == Some(SyntaxKind::WhiteSpace) {
+            children.push(self.bump());
+        }
+    }
+
+    fn unexpected(&self) -> FormatterError {
+        match self.peek() {
+            Some(token) => FormatterError::UnexpectedToken(token.offset),
+            None => FormatterError::ExpectedMoreTokens(),
+        }
+    }
+
+    fn value(&mut self) -> Result<SyntaxElement, FormatterError> {
+        match self.peek_kind() {
+            Some(SyntaxKind::OpenBrace) => self.object(),
+            Some(SyntaxKind::OpenSquareBraket) => self.array(),
+            Some(SyntaxKind::Null)
+            | Some(SyntaxKind::True)
+            | Some(SyntaxKind::False)
+            | Some(SyntaxKind::Number)
+            | Some(SyntaxKind::StringLiteral) => Ok(self.bump()),
+            _ => Err(self.unexpected()),
+        }
+    }
+
+    fn object(&mut self) -> Result<SyntaxElement, FormatterError> {
+        let offset = self.tokens[self.position].offset;
+        let mut children = vec![self.bump()];
+
+        self.trivia(&mut children);
+        if self.peek_kind() != Some(SyntaxKind::CloseBrace) {
+            loop {
+                children.push(self.member()?);
+                self.trivia(&mut children);
+                match self.peek_kind() {
+                    Some(SyntaxKind::Comma) => {
+                        children.push(self.bump());
+                        self.trivia(&mut children);
+                    }
+                    Some(SyntaxKind::CloseBrace) => break,
+                    _ => return Err(self.unexpected()),
+                }
+            }
+        }
+        children.push(self.bump());
+
+        Ok(SyntaxElement::Node(node(
+            SyntaxKind::Object,
+            offset,
+            children,
+        )))
+    }
+
+    fn member(&mut self) -> Result<SyntaxElement, FormatterError> {
+        let offset = match self.peek() {
+            Some(token) if token.kind == SyntaxKind::StringLiteral => token.offset,
+            Some(token) => return Err(FormatterError::ExpectedStringLiteral(token.offset)),
+            None => return Err(FormatterError::ExpectedMoreTokens()),
+        };
+        let mut children = vec![self.bump()];
+
+        self.trivia(&mut children);
+        if self.peek_kind() != Some(SyntaxKind::Colon) {
+            let position = match self.peek() {
+                Some(token) => token.offset,
+                None => self.tokens.last().map_or(0, SyntaxToken::end),
+            };
+            return Err(FormatterError::ExpectedColonInKeyValuePair(position));
+        }
+        children.push(self.bump());
+        self.trivia(&mut children);
+        children.push(self.value()?);
+
+        Ok(SyntaxElement::Node(node(
+            SyntaxKind::Member,
+            offset,
+            children,
+        )))
+    }
+
+    fn array(&mut self) -> Result<SyntaxElement, FormatterError> {
+        let offset = self.tokens[self.position].offset;
+        let mut children = vec![self.bump()];
+
+        self.trivia(&mut children);
+        if self.peek_kind() != Some(SyntaxKind::CloseSquareBraket) {
+            loop {
+                children.push(self.value()?);
+                self.trivia(&mut children);
+                match self.peek_kind() {
+                    Some(SyntaxKind::Comma) => {
+                        children.push(self.bump());
+                        self.trivia(&mut children);
+                    }
+                    Some(SyntaxKind::CloseSquareBraket) => break,
+                    _ => return Err(self.unexpected()),
+                }
+            }
+        }
+        children.push(self.bump());
+
+        Ok(SyntaxElement::Node(node(
+            SyntaxKind::Array,
+            offset,
+            children,
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lex_joins_whitespace() {
+        let tokens = lex(" \n\t1").unwrap();
+        assert_eq!(tokens.len(), 2);
+        assert_eq!(tokens[0].text(), " \n\t");
+        assert_eq!(tokens[1].offset(), 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "Unexpected token at position (4).")]
+    fn build_rejects_trailing_commas() {
+        match build("[1, ]") {
+            Ok(_) => (),
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "The number at position (4) is not valid.")]
+    fn build_rejects_numbers_that_are_not() {
+        match build("[1, -]") {
+            Ok(_) => (),
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Unexpected token at position (5).")]
+    fn build_rejects_content_after_the_document() {
+        match build("true false") {
+            Ok(_) => (),
+            Err(e) => panic!("{}", e),
+        }
+    }
+}
@@ -0,0 +1,249 @@
+use std::fmt;
+
+use crate::formatter::errors::FormatterError;
+use crate::value::{parse_pointer, unescape};
+
+mod builder;
+
+/// The kind of a node or token in the concrete syntax tree.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum SyntaxKind {
+    // Nodes
+    Document,
+    Object,
+    Array,
+    Member,
+
+    // Tokens
+    CloseBrace,
+    CloseSquareBraket,
+    Colon,
+    Comma,
+    OpenBrace,
+    OpenSquareBraket,
+    WhiteSpace,
+    Null,
+    True,
+    False,
+    Number,
+    StringLiteral,
+}
+
+/// A single token with its exact source text and byte offset.
+#[derive(Debug, PartialEq, Clone)]
+pub struct SyntaxToken {
+    kind: SyntaxKind,
+    text: String,
+    offset: usize,
+}
+
+impl SyntaxToken {
+    pub fn kind(&self) -> SyntaxKind {
+        self.kind
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    pub fn end(&self) -> usize {
+        self.offset + self.text.len()
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum SyntaxElement {
+    Node(SyntaxNode),
+    Token(SyntaxToken),
+}
+
+impl SyntaxElement {
+    pub fn kind(&self) -> SyntaxKind {
+        match self {
+            SyntaxElement::Node(node) => node.kind(),
+            SyntaxElement::Token(token) => token.kind(),
+        }
+    }
+
+    pub fn offset(&self) -> usize {
+        match self {
+            SyntaxElement::Node(node) => node.offset(),
+            SyntaxElement::Token(token) => token.offset(),
+        }
+    }
+
+    pub fn end(&self) -> usize {
+        match self {
+            SyntaxElement::Node(node) => node.end(),
+            SyntaxElement::Token(token) => token.end(),
+        }
+    }
+
+    /// Whether this is a JSON value: an object, an array or a scalar token.
+    pub fn is_value(&self) -> bool {
+        matches!(
+            self.kind(),
+            SyntaxKind::Object
+                | SyntaxKind::Array
+                | SyntaxKind::StringLiteral
+                | SyntaxKind::Number
+                | SyntaxKind::True
+                | SyntaxKind::False
+                | SyntaxKind::Null
+        )
+    }
+}
+
+/// A node of the lossless syntax tree. Every character of the input, whitespace included,
+/// belongs to exactly one token somewhere below the `Document` node.
+#[derive(Debug, PartialEq, Clone)]
+pub struct SyntaxNode {
+    kind: SyntaxKind,
+    offset: usize,
+    children: Vec<SyntaxElement>,
+}
+
+impl SyntaxNode {
+    pub fn kind(&self) -> SyntaxKind {
+        self.kind
+    }
+
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    pub fn end(&self) -> usize {
+        self.children
+            .last()
+            .map(|child| child.end())
+            .unwrap_or(self.offset)
+    }
+
+    pub fn children(&self) -> &[SyntaxElement] {
+        &self.children
+    }
+
+    pub fn child_nodes(&self) -> impl Iterator<Item = &SyntaxNode> {
+        self.children.iter().filter_map(|child| match child {
+            SyntaxElement::Node(node) => Some(node),
+            SyntaxElement::Token(_) => None,
+        })
+    }
+
+    /// The unescaped key of a `Member`.
+    pub fn key(&self) -> Option<String> {
+        match (self.kind, self.children.first()) {
+            (SyntaxKind::Member, Some(SyntaxElement::Token(key))) => {
+                let text = key.text();
+                Some(unescape(&text[1..text.len() - 1]))
+            }
+            _ => None,
+        }
+    }
+
+    /// The value of a `Member`, or the root value of a `Document`.
+    pub fn value(&self) -> Option<&SyntaxElement> {
+        match self.kind {
+            SyntaxKind::Member | SyntaxKind::Document => {
+                self.children.iter().rev().find(|child| child.is_value())
+            }
+            _ => None,
+        }
+    }
+
+    /// The value a JSON Pointer refers to, looked up from a `Document`.
+    pub fn pointer(&self, pointer: &str) -> Option<&SyntaxElement> {
+        let mut element = self.value()?;
+        for token in parse_pointer(pointer).ok()? {
+            let node = match element {
+                SyntaxElement::Node(node) => node,
+                SyntaxElement::Token(_) => return None,
+            };
+            element = match node.kind {
+                SyntaxKind::Object => node
+                    .child_nodes()
+                    .find(|member| member.key().as_ref() == Some(&token))?
+                    .value()?,
+                SyntaxKind::Array => node
+                    .children
+                    .iter()
+                    .filter(|child| child.is_value())
+                    .nth(token.parse().ok()?)?,
+                _ => return None,
+            };
+        }
+        Some(element)
+    }
+
+    /// All tokens below this node, in source order.
+    pub fn tokens(&self) -> Vec<&SyntaxToken> {
+        let mut tokens = vec![];
+        for child in &self.children {
+            match child {
+                SyntaxElement::Node(node) => tokens.extend(node.tokens()),
+                SyntaxElement::Token(token) => tokens.push(token),
+            }
+        }
+        tokens
+    }
+}
+
+impl fmt::Display for SyntaxNode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for token in self.tokens() {
+            write!(f, "{}", token.text())?;
+        }
+        Ok(())
+    }
+}
+
+/// Builds the lossless syntax tree of `input`, so `parse(input)?.to_string() == input`.
+pub fn parse(input: &str) -> Result<SyntaxNode, FormatterError> {
+    builder::build(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_the_input_exactly() {
+        let json = "\n  { \"a\" :[1,\n\n 2 ] ,\t\"b\\\"\": {}}  \n";
+        match parse(json) {
+            Ok(document) => assert_eq!(document.to_string(), json),
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    #[test]
+    fn records_byte_offsets() {
+        let json = r#"{"é": [true]}"#;
+        let document = parse(json).unwrap();
+        let object = document.child_nodes().next().unwrap();
+        let member = object.child_nodes().next().unwrap();
+
+        assert_eq!(member.kind(), SyntaxKind::Member);
+        assert_eq!(member.offset(), 1);
+        assert_eq!(&json[member.offset()..member.end()], r#""é": [true]"#);
+    }
+
+    #[test]
+    fn pointers_find_elements() {
+        let json = r#"{"a/b": [1, {"c": null}]}"#;
+        let document = parse(json).unwrap();
+        let span = |pointer: &str| {
+            document
+                .pointer(pointer)
+                .map(|element| &json[element.offset()..element.end()])
+        };
+
+        assert_eq!(span(""), Some(json));
+        assert_eq!(span("/a~1b/1/c"), Some("null"));
+        assert_eq!(span("/a~1b/2"), None);
+        assert_eq!(span("/missing"), None);
+    }
+}
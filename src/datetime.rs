@@ -0,0 +1,109 @@
+//! Recognising RFC 3339 dates and times in strings.
+
+/// Whether `text` is made of the given runs of ASCII digits, separated by `separator`.
+fn digits(text: &str, separator: char, lengths: &[usize]) -> bool {
+    let parts = text.split(separator).collect::<Vec<&str>>();
+    parts.len() == lengths.len()
+        && parts
+            .iter()
+            .zip(lengths)
+            .all(|(part, length)| part.len() == *length && part.bytes().all(|b| b.is_ascii_digit()))
+}
+
+/// The numbers of a string `digits` accepted.
+fn numbers(text: &str, separator: char) -> Vec<u32> {
+    text.split(separator)
+        .map(|part| part.parse().unwrap_or(0))
+        .collect()
+}
+
+/// A `full-date` such as `2024-05-01`, with a day that exists in that month.
+pub fn is_date(text: &str) -> bool {
+    if !digits(text, '-', &[4, 2, 2]) {
+        return false;
+    }
+    let (year, month, day) = match numbers(text, '-')[..] {
+        [year, month, day] => (year, month, day),
+        _ => return false,
+    };
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let days = match month {
+        2 if leap => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        1..=12 => 31,
+        _ => return false,
+    };
+    (1..=days).contains(&day)
+}
+
+/// A `partial-time` such as `12:30:00` or `23:59:60.25`.
+pub fn is_time(text: &str) -> bool {
+    let time = match text.split_once('.') {
+        Some((whole, fraction))
+            if !fraction.is_empty() && fraction.bytes().all(|b| b.is_ascii_digit()) =>
+        {
+            whole
+        }
+        Some(_) => return false,
+        None => text,
+    };
+    digits(time, ':', &[2, 2, 2])
+        && matches!(numbers(time, ':')[..], [hour, minute, second] if hour < 24 && minute < 60 && second <= 60)
+}
+
+/// A `date-time` such as `2024-05-01T12:30:00.5Z` or `2024-05-01t12:30:00+02:00`.
+pub fn is_date_time(text: &str) -> bool {
+    match text.find(['T', 't']) {
+        Some(index) => {
+            let time = &text[index + 1..];
+            match time.find(['Z', 'z', '+', '-']) {
+                Some(offset) => {
+                    is_date(&text[..index])
+                        && is_time(&time[..offset])
+                        && is_offset(&time[offset..])
+                }
+                None => false,
+            }
+        }
+        None => false,
+    }
+}
+
+/// A date and time without an offset, such as `2024-05-01T12:30:00`.
+pub fn is_local_date_time(text: &str) -> bool {
+    match text.find(['T', 't']) {
+        Some(index) => is_date(&text[..index]) && is_time(&text[index + 1..]),
+        None => false,
+    }
+}
+
+fn is_offset(offset: &str) -> bool {
+    matches!(offset, "Z" | "z")
+        || (offset.len() == 6
+            && digits(&offset[1..], ':', &[2, 2])
+            && matches!(numbers(&offset[1..], ':')[..], [hour, minute] if hour < 24 && minute < 60))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dates_must_exist() {
+        assert!(is_date("2024-02-29"));
+        assert!(!is_date("2023-02-29"));
+        assert!(!is_date("2024-13-01"));
+        assert!(!is_date("2024-5-01"));
+    }
+
+    #[test]
+    fn times_and_offsets_are_in_range() {
+        assert!(is_date_time("2024-05-01T23:59:60.5+14:00"));
+        assert!(!is_date_time("2024-05-01T24:00:00Z"));
+        assert!(!is_date_time("2024-05-01T12:00:00"));
+        assert!(is_local_date_time("2024-05-01t12:00:00"));
+        assert!(is_time("00:00:00.000"));
+        assert!(!is_time("12:60:00"));
+    }
+}
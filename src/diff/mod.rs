@@ -0,0 +1,261 @@
+//! Structural differences between two documents, reported by JSON Pointer.
+use crate::formatter::errors::FormatterError;
+use crate::value::{to_pointer, Value};
+
+pub(crate) mod walk;
+
+use walk::{walk, Differences};
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Change {
+    Added {
+        path: String,
+        value: Value,
+    },
+    Removed {
+        path: String,
+        value: Value,
+    },
+    Changed {
+        path: String,
+        from: Value,
+        to: Value,
+    },
+    /// The object at `path` has the same members in a different order.
+    Reordered {
+        path: String,
+    },
+}
+
+impl Change {
+    pub fn path(&self) -> &str {
+        match self {
+            Change::Added { path, .. }
+            | Change::Removed { path, .. }
+            | Change::Changed { path, .. }
+            | Change::Reordered { path } => path,
+        }
+    }
+
+    /// The change as an object for a machine readable report.
+    pub fn to_value(&self) -> Value {
+        let entry = |name: &str, value: Value| (String::from(name), value);
+        let kind = |kind: &str| entry("change", Value::String(String::from(kind)));
+        let path = entry("path", Value::String(self.path().to_owned()));
+
+        Value::Object(match self {
+            Change::Added { value, .. } => vec![kind("added"), path, entry("value", value.clone())],
+            Change::Removed { value, .. } => {
+                vec![kind("removed"), path, entry("value", value.clone())]
+            }
+            Change::Changed { from, to, .. } => vec![
+                kind("changed"),
+                path,
+                entry("from", from.clone()),
+                entry("to", to.clone()),
+            ],
+            Change::Reordered { .. } => vec![kind("reordered"), path],
+        })
+    }
+}
+
+pub struct DiffConfig {
+    compare_key_order: bool,
+}
+
+impl DiffConfig {
+    pub fn new() -> DiffConfig {
+        DiffConfig {
+            compare_key_order: false,
+        }
+    }
+
+    /// Reports objects whose members are the same but in a different order.
+    pub fn with_key_order(mut self, compare_key_order: bool) -> DiffConfig {
+        self.compare_key_order = compare_key_order;
+        self
+    }
+}
+
+impl Default for DiffConfig {
+    fn default() -> Self {
+        DiffConfig::new()
+    }
+}
+
+/// Every difference between `from` and `to`, in document order.
+///
+/// Arrays are compared after trimming the items they share at either end, so an insertion or
+/// removal is reported once rather than as a change to every item after it.
+pub fn diff(from: &Value, to: &Value, config: &DiffConfig) -> Vec<Change> {
+    let mut changes = vec![];
+    walk(from, to, config.compare_key_order, &mut changes);
+    changes
+}
+
+/// Parses both documents and compares them; formatting never counts as a difference.
+pub fn diff_documents(
+    from: &str,
+    to: &str,
+    config: Option<DiffConfig>,
+) -> Result<Vec<Change>, FormatterError> {
+    let config = config.unwrap_or_default();
+    Ok(diff(&crate::parse(from)?, &crate::parse(to)?, &config))
+}
+
+/// All changes as a JSON array, ready to be formatted.
+pub fn report(changes: &[Change]) -> Value {
+    Value::Array(changes.iter().map(Change::to_value).collect())
+}
+
+/// One line per change, e.g. `~ /port: 8080 -> 9090`, optionally coloured with ANSI escapes.
+pub fn render(changes: &[Change], color: bool) -> String {
+    changes
+        .iter()
+        .map(|change| {
+            let path = match change.path() {
+                "" => "(root)",
+                path => path,
+            };
+            let (colour, line) = match change {
+                Change::Added { value, .. } => ("32", format!("+ {}: {}", path, value)),
+                Change::Removed { value, .. } => ("31", format!("- {}: {}", path, value)),
+                Change::Changed { from, to, .. } => {
+                    ("33", format!("~ {}: {} -> {}", path, from, to))
+                }
+                Change::Reordered { .. } => ("36", format!("~ {}: members reordered", path)),
+            };
+            match color {
+                true => format!("\x1b[{}m{}\x1b[0m\n", colour, line),
+                false => format!("{}\n", line),
+            }
+        })
+        .collect()
+}
+
+impl Differences for Vec<Change> {
+    fn added(&mut self, path: &[String], value: &Value) {
+        self.push(Change::Added {
+            path: to_pointer(path.iter()),
+            value: value.clone(),
+        });
+    }
+
+    fn removed(&mut self, path: &[String], value: &Value) {
+        self.push(Change::Removed {
+            path: to_pointer(path.iter()),
+            value: value.clone(),
+        });
+    }
+
+    fn changed(&mut self, path: &[String], from: &Value, to: &Value) {
+        self.push(Change::Changed {
+            path: to_pointer(path.iter()),
+            from: from.clone(),
+            to: to.clone(),
+        });
+    }
+
+    fn reordered(&mut self, path: &[String]) {
+        self.push(Change::Reordered {
+            path: to_pointer(path.iter()),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn changes(from: &str, to: &str, config: DiffConfig) -> Vec<Change> {
+        diff_documents(from, to, Some(config)).unwrap()
+    }
+
+    #[test]
+    fn formatting_and_key_order_are_ignored() {
+        assert_eq!(
+            changes(
+                r#"{"a": 1, "b": [1, 2]}"#,
+                "{\n  \"b\": [1,2],\n  \"a\": 1.0\n}",
+                DiffConfig::new()
+            ),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn key_order_can_be_compared() {
+        assert_eq!(
+            changes(
+                r#"{"x": {"a": 1, "b": 2}}"#,
+                r#"{"x": {"b": 2, "a": 1}}"#,
+                DiffConfig::new().with_key_order(true)
+            ),
+            vec![Change::Reordered {
+                path: String::from("/x")
+            }]
+        );
+    }
+
+    #[test]
+    fn changes_are_reported_by_pointer() {
+        assert_eq!(
+            changes(
+                r#"{"a": 1, "b": {"c": "x"}, "d/e": []}"#,
+                r#"{"b": {"c": "y"}, "d/e": [null], "f": true}"#,
+                DiffConfig::new()
+            ),
+            vec![
+                Change::Removed {
+                    path: String::from("/a"),
+                    value: Value::Number(1.0)
+                },
+                Change::Changed {
+                    path: String::from("/b/c"),
+                    from: Value::String(String::from("x")),
+                    to: Value::String(String::from("y"))
+                },
+                Change::Added {
+                    path: String::from("/d~1e/0"),
+                    value: Value::Null
+                },
+                Change::Added {
+                    path: String::from("/f"),
+                    value: Value::Bool(true)
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn array_insertions_are_reported_once() {
+        assert_eq!(
+            changes("[1, 2, 3]", "[0, 1, 2, 3]", DiffConfig::new()),
+            vec![Change::Added {
+                path: String::from("/0"),
+                value: Value::Number(0.0)
+            }]
+        );
+    }
+
+    #[test]
+    fn changes_render_as_lines_and_reports() {
+        let found = changes(
+            r#"{"a": 1, "b": 2}"#,
+            r#"{"a": 2, "c": 3}"#,
+            DiffConfig::new(),
+        );
+        assert_eq!(render(&found, false), "~ /a: 1 -> 2\n- /b: 2\n+ /c: 3\n");
+        assert_eq!(render(&found[..1], true), "\x1b[33m~ /a: 1 -> 2\x1b[0m\n");
+        assert_eq!(
+            report(&found).to_string(),
+            r#"[{"change":"changed","path":"/a","from":1,"to":2},{"change":"removed","path":"/b","value":2},{"change":"added","path":"/c","value":3}]"#
+        );
+    }
+
+    #[test]
+    fn root_changes_are_labelled() {
+        let found = changes("1", "2", DiffConfig::new());
+        assert_eq!(render(&found, false), "~ (root): 1 -> 2\n");
+    }
+}
@@ -0,0 +1,158 @@
+//! The walk over two values that both `diff` and `patch::diff_to_patch` report from.
+use crate::value::{values_equal, Value};
+
+/// Where a walk reports what it finds, so each caller chooses the shape of its output.
+///
+/// Paths are the member names and array indexes leading to the difference.
+pub(crate) trait Differences {
+    fn added(&mut self, path: &[String], value: &Value);
+
+    fn removed(&mut self, path: &[String], value: &Value);
+
+    fn changed(&mut self, path: &[String], from: &Value, to: &Value);
+
+    /// The object at `path` has its shared members in a different order. Only reported when
+    /// the walk compares key order.
+    fn reordered(&mut self, _path: &[String]) {}
+
+    /// The items removed from the array at `path`, the first of them at index `start`.
+    fn removed_items(&mut self, path: &mut Vec<String>, start: usize, items: &[Value]) {
+        for (offset, item) in items.iter().enumerate() {
+            path.push((start + offset).to_string());
+            self.removed(path, item);
+            path.pop();
+        }
+    }
+
+    /// The items added to the array at `path` from index `start`. `at_end` says nothing
+    /// follows them.
+    fn added_items(
+        &mut self,
+        path: &mut Vec<String>,
+        start: usize,
+        items: &[Value],
+        _at_end: bool,
+    ) {
+        for (offset, item) in items.iter().enumerate() {
+            path.push((start + offset).to_string());
+            self.added(path, item);
+            path.pop();
+        }
+    }
+}
+
+/// Reports every difference between `from` and `to` to `differences`, in document order.
+///
+/// Objects are compared member by member and arrays item by item after trimming the items
+/// they share at either end, so a single insertion or removal is reported once. Anything else
+/// that differs is changed whole. Unless `compare_key_order` is set, values whose members are
+/// only in a different order are equal.
+pub(crate) fn walk(
+    from: &Value,
+    to: &Value,
+    compare_key_order: bool,
+    differences: &mut impl Differences,
+) {
+    let mut walk = Walk {
+        compare_key_order,
+        differences,
+    };
+    walk.values(&mut vec![], from, to);
+}
+
+struct Walk<'a, D> {
+    compare_key_order: bool,
+    differences: &'a mut D,
+}
+
+impl<D: Differences> Walk<'_, D> {
+    fn same(&self, from: &Value, to: &Value) -> bool {
+        match self.compare_key_order {
+            true => from == to,
+            false => values_equal(from, to),
+        }
+    }
+
+    fn values(&mut self, path: &mut Vec<String>, from: &Value, to: &Value) {
+        if self.same(from, to) {
+            return;
+        }
+        match (from, to) {
+            (Value::Object(from), Value::Object(to)) => self.objects(path, from, to),
+            (Value::Array(from), Value::Array(to)) => self.arrays(path, from, to),
+            (from, to) => self.differences.changed(path, from, to),
+        }
+    }
+
+    fn objects(
+        &mut self,
+        path: &mut Vec<String>,
+        from: &[(String, Value)],
+        to: &[(String, Value)],
+    ) {
+        let find = |pairs: &'_ [(String, Value)], key: &str| {
+            pairs
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value.clone())
+        };
+
+        if self.compare_key_order {
+            let shared = |pairs: &'_ [(String, Value)], other: &'_ [(String, Value)]| {
+                pairs
+                    .iter()
+                    .filter(|(key, _)| other.iter().any(|(name, _)| name == key))
+                    .map(|(key, _)| key.clone())
+                    .collect::<Vec<String>>()
+            };
+            if shared(from, to) != shared(to, from) {
+                self.differences.reordered(path);
+            }
+        }
+
+        for (key, value) in from {
+            path.push(key.clone());
+            match find(to, key) {
+                Some(other) => self.values(path, value, &other),
+                None => self.differences.removed(path, value),
+            }
+            path.pop();
+        }
+
+        for (key, value) in to {
+            if find(from, key).is_none() {
+                path.push(key.clone());
+                self.differences.added(path, value);
+                path.pop();
+            }
+        }
+    }
+
+    fn arrays(&mut self, path: &mut Vec<String>, from: &[Value], to: &[Value]) {
+        let prefix = from
+            .iter()
+            .zip(to.iter())
+            .take_while(|(from, to)| self.same(from, to))
+            .count();
+        let suffix = from[prefix..]
+            .iter()
+            .rev()
+            .zip(to[prefix..].iter().rev())
+            .take_while(|(from, to)| self.same(from, to))
+            .count();
+        let from_middle = &from[prefix..from.len() - suffix];
+        let to_middle = &to[prefix..to.len() - suffix];
+        let shared = from_middle.len().min(to_middle.len());
+
+        for offset in 0..shared {
+            path.push((prefix + offset).to_string());
+            self.values(path, &from_middle[offset], &to_middle[offset]);
+            path.pop();
+        }
+
+        self.differences
+            .removed_items(path, prefix + shared, &from_middle[shared..]);
+        self.differences
+            .added_items(path, prefix + shared, &to_middle[shared..], suffix == 0);
+    }
+}
@@ -0,0 +1,67 @@
+use crate::value::Value;
+
+/// A parsed filter. Every expression takes one input value and produces any number of outputs.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Expression {
+    /// `.`
+    Identity,
+    /// `..`, the input followed by every value inside it.
+    Recurse,
+    Literal(Value),
+    /// A string literal containing `\(...)`.
+    Interpolation(Vec<Part>),
+    /// `.name`, `."name"` and `.[index]`. The index is evaluated against the same input as the
+    /// target, so `.items[.selected]` works.
+    Index(Box<Expression>, Box<Expression>),
+    /// `.[start:end]`
+    Slice {
+        target: Box<Expression>,
+        start: Option<Box<Expression>>,
+        end: Option<Box<Expression>>,
+    },
+    /// `.[]`
+    Iterate(Box<Expression>),
+    /// `expression?`, which drops the error instead of failing.
+    Optional(Box<Expression>),
+    Pipe(Box<Expression>, Box<Expression>),
+    Comma(Box<Expression>, Box<Expression>),
+    /// `[expression]`, or `[]` when empty.
+    Array(Option<Box<Expression>>),
+    /// `{key: value, ...}`, with `{name}` already expanded to `{"name": .name}`.
+    Object(Vec<(Expression, Expression)>),
+    Negate(Box<Expression>),
+    Binary(Operator, Box<Expression>, Box<Expression>),
+    And(Box<Expression>, Box<Expression>),
+    Or(Box<Expression>, Box<Expression>),
+    /// `left // right`
+    Alternative(Box<Expression>, Box<Expression>),
+    /// `if ... then ... elif ... else ... end`, with `elif` nested into `otherwise`.
+    If {
+        condition: Box<Expression>,
+        then: Box<Expression>,
+        otherwise: Option<Box<Expression>>,
+    },
+    /// A builtin such as `length` or `map(f)`.
+    Call(String, Vec<Expression>),
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Part {
+    Text(String),
+    Expression(Expression),
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Modulo,
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
@@ -0,0 +1,966 @@
+use std::cmp::Ordering;
+
+use crate::formatter::errors::FormatterError;
+use crate::value::{values_equal, Value};
+
+use super::ast::{Expression, Operator, Part};
+
+type Outputs = Result<Vec<Value>, FormatterError>;
+
+/// Every output of `expression` for `input`, in order.
+pub fn evaluate(expression: &Expression, input: &Value) -> Outputs {
+    match expression {
+        Expression::Identity => Ok(vec![input.clone()]),
+        Expression::Recurse => {
+            let mut outputs = vec![];
+            recurse(input, &mut outputs);
+            Ok(outputs)
+        }
+        Expression::Literal(value) => Ok(vec![value.clone()]),
+        Expression::Interpolation(parts) => {
+            let mut strings = vec![String::new()];
+            for part in parts {
+                strings = match part {
+                    Part::Text(text) => strings.into_iter().map(|s| s + text).collect(),
+                    Part::Expression(expression) => {
+                        let values = evaluate(expression, input)?;
+                        strings
+                            .iter()
+                            .flat_map(|s| values.iter().map(move |v| s.clone() + &to_text(v)))
+                            .collect()
+                    }
+                };
+            }
+            Ok(strings.into_iter().map(Value::String).collect())
+        }
+        Expression::Index(target, index) => {
+            let mut outputs = vec![];
+            for target in evaluate(target, input)? {
+                for index in evaluate(index, input)? {
+                    outputs.push(index_value(&target, &index)?);
+                }
+            }
+            Ok(outputs)
+        }
+        Expression::Slice { target, start, end } => {
+            let bound = |bound: &Option<Box<Expression>>| match bound {
+                Some(bound) => evaluate(bound, input),
+                None => Ok(vec![Value::Null]),
+            };
+            let (starts, ends) = (bound(start)?, bound(end)?);
+            let mut outputs = vec![];
+            for target in evaluate(target, input)? {
+                for start in &starts {
+                    for end in &ends {
+                        outputs.push(slice(&target, start, end)?);
+                    }
+                }
+            }
+            Ok(outputs)
+        }
+        Expression::Iterate(target) => {
+            let mut outputs = vec![];
+            for target in evaluate(target, input)? {
+                outputs.extend(iterate(target)?);
+            }
+            Ok(outputs)
+        }
+        Expression::Optional(expression) => Ok(evaluate(expression, input).unwrap_or_default()),
+        Expression::Pipe(left, right) => {
+            let mut outputs = vec![];
+            for value in evaluate(left, input)? {
+                outputs.extend(evaluate(right, &value)?);
+            }
+            Ok(outputs)
+        }
+        Expression::Comma(left, right) => {
+            let mut outputs = evaluate(left, input)?;
+            outputs.extend(evaluate(right, input)?);
+            Ok(outputs)
+        }
+        Expression::Array(items) => Ok(vec![Value::Array(match items {
+            Some(items) => evaluate(items, input)?,
+            None => vec![],
+        })]),
+        Expression::Object(entries) => {
+            let mut objects = vec![vec![]];
+            for (key, value) in entries {
+                let keys = evaluate(key, input)?;
+                let values = evaluate(value, input)?;
+                let mut extended = vec![];
+                for object in &objects {
+                    for key in &keys {
+                        let key = match key {
+                            Value::String(key) => key,
+                            other => {
+                                return Err(failed(format!(
+                                    "object keys must be strings, found {}",
+                                    type_name(other)
+                                )))
+                            }
+                        };
+                        for value in &values {
+                            let mut object: Vec<(String, Value)> = object.clone();
+                            set_member(&mut object, key, value.clone());
+                            extended.push(object);
+                        }
+                    }
+                }
+                objects = extended;
+            }
+            Ok(objects.into_iter().map(Value::Object).collect())
+        }
+        Expression::Negate(operand) => evaluate(operand, input)?
+            .into_iter()
+            .map(|value| match value {
+                Value::Number(number) => Ok(Value::Number(-number)),
+                other => Err(failed(format!("{} cannot be negated", describe(&other)))),
+            })
+            .collect(),
+        Expression::Binary(operator, left, right) => {
+            let lefts = evaluate(left, input)?;
+            let mut outputs = vec![];
+            for right in evaluate(right, input)? {
+                for left in &lefts {
+                    outputs.push(binary(*operator, left, &right)?);
+                }
+            }
+            Ok(outputs)
+        }
+        Expression::And(left, right) => {
+            let mut outputs = vec![];
+            for left in evaluate(left, input)? {
+                if !is_truthy(&left) {
+                    outputs.push(Value::Bool(false));
+                    continue;
+                }
+                for right in evaluate(right, input)? {
+                    outputs.push(Value::Bool(is_truthy(&right)));
+                }
+            }
+            Ok(outputs)
+        }
+        Expression::Or(left, right) => {
+            let mut outputs = vec![];
+            for left in evaluate(left, input)? {
+                if is_truthy(&left) {
+                    outputs.push(Value::Bool(true));
+                    continue;
+                }
+                for right in evaluate(right, input)? {
+                    outputs.push(Value::Bool(is_truthy(&right)));
+                }
+            }
+            Ok(outputs)
+        }
+        Expression::Alternative(left, right) => {
+            let truthy = evaluate(left, input)
+                .unwrap_or_default()
+                .into_iter()
+                .filter(is_truthy)
+                .collect::<Vec<Value>>();
+            match truthy.is_empty() {
+                true => evaluate(right, input),
+                false => Ok(truthy),
+            }
+        }
+        Expression::If {
+            condition,
+            then,
+            otherwise,
+        } => {
+            let mut outputs = vec![];
+            for condition in evaluate(condition, input)? {
+                match (is_truthy(&condition), otherwise) {
+                    (true, _) => outputs.extend(evaluate(then, input)?),
+                    (false, Some(otherwise)) => outputs.extend(evaluate(otherwise, input)?),
+                    (false, None) => outputs.push(input.clone()),
+                }
+            }
+            Ok(outputs)
+        }
+        Expression::Call(name, arguments) => call(name, arguments, input),
+    }
+}
+
+fn call(name: &str, arguments: &[Expression], input: &Value) -> Outputs {
+    match (name, arguments) {
+        ("empty", []) => Ok(vec![]),
+        ("not", []) => Ok(vec![Value::Bool(!is_truthy(input))]),
+        ("length", []) => length(input).map(|length| vec![length]),
+        ("keys", []) => keys(input, true).map(|keys| vec![keys]),
+        ("keys_unsorted", []) => keys(input, false).map(|keys| vec![keys]),
+        ("type", []) => Ok(vec![Value::String(type_name(input).to_owned())]),
+        ("add", []) => {
+            let mut sum = Value::Null;
+            for item in iterate(input.clone())? {
+                sum = binary(Operator::Add, &sum, &item)?;
+            }
+            Ok(vec![sum])
+        }
+        ("any", []) => Ok(vec![Value::Bool(
+            iterate(input.clone())?.iter().any(is_truthy),
+        )]),
+        ("all", []) => Ok(vec![Value::Bool(
+            iterate(input.clone())?.iter().all(is_truthy),
+        )]),
+        ("sort", []) => {
+            let mut items = array(input)?.clone();
+            items.sort_by(compare);
+            Ok(vec![Value::Array(items)])
+        }
+        ("unique", []) => {
+            let mut items = array(input)?.clone();
+            items.sort_by(compare);
+            items.dedup_by(|a, b| compare(a, b) == Ordering::Equal);
+            Ok(vec![Value::Array(items)])
+        }
+        ("reverse", []) => match input {
+            Value::String(string) => Ok(vec![Value::String(string.chars().rev().collect())]),
+            Value::Null => Ok(vec![Value::Array(vec![])]),
+            _ => Ok(vec![Value::Array(
+                array(input)?.iter().rev().cloned().collect(),
+            )]),
+        },
+        ("min", []) => Ok(vec![array(input)?
+            .iter()
+            .min_by(|a, b| compare(a, b))
+            .cloned()
+            .unwrap_or(Value::Null)]),
+        ("max", []) => Ok(vec![array(input)?
+            .iter()
+            .max_by(|a, b| compare(a, b))
+            .cloned()
+            .unwrap_or(Value::Null)]),
+        ("first", []) => index_value(input, &Value::Number(0.0)).map(|first| vec![first]),
+        ("last", []) => index_value(input, &Value::Number(-1.0)).map(|last| vec![last]),
+        ("to_entries", []) => Ok(vec![to_entries(input)?]),
+        ("from_entries", []) => Ok(vec![from_entries(input)?]),
+        ("tostring", []) => Ok(vec![Value::String(to_text(input))]),
+        ("tojson", []) => Ok(vec![Value::String(input.to_string())]),
+        ("tonumber", []) => match input {
+            Value::Number(_) => Ok(vec![input.clone()]),
+            Value::String(string) => string
+                .trim()
+                .parse()
+                .map(|number| vec![Value::Number(number)])
+                .map_err(|_| failed(format!("cannot parse {} as a number", input))),
+            other => Err(failed(format!(
+                "{} cannot be parsed as a number",
+                describe(other)
+            ))),
+        },
+        ("ascii_downcase", []) => Ok(vec![Value::String(string(input)?.to_ascii_lowercase())]),
+        ("ascii_upcase", []) => Ok(vec![Value::String(string(input)?.to_ascii_uppercase())]),
+        ("floor", []) => Ok(vec![Value::Number(number(input)?.floor())]),
+        ("map", [f]) => {
+            let mut outputs = vec![];
+            for item in iterate(input.clone())? {
+                outputs.extend(evaluate(f, &item)?);
+            }
+            Ok(vec![Value::Array(outputs)])
+        }
+        ("select", [f]) => Ok(evaluate(f, input)?
+            .iter()
+            .filter(|condition| is_truthy(condition))
+            .map(|_| input.clone())
+            .collect()),
+        ("map_values", [f]) => match input {
+            Value::Object(pairs) => {
+                let mut mapped = vec![];
+                for (key, value) in pairs {
+                    if let Some(value) = evaluate(f, value)?.into_iter().next() {
+                        mapped.push((key.clone(), value));
+                    }
+                }
+                Ok(vec![Value::Object(mapped)])
+            }
+            _ => {
+                let mut mapped = vec![];
+                for item in array(input)? {
+                    mapped.extend(evaluate(f, item)?.into_iter().next());
+                }
+                Ok(vec![Value::Array(mapped)])
+            }
+        },
+        ("with_entries", [f]) => {
+            let mut entries = vec![];
+            for entry in iterate(to_entries(input)?)? {
+                entries.extend(evaluate(f, &entry)?);
+            }
+            Ok(vec![from_entries(&Value::Array(entries))?])
+        }
+        ("sort_by", [f]) => {
+            let mut keyed = by_key(f, input)?;
+            keyed.sort_by(|(a, _), (b, _)| compare(a, b));
+            Ok(vec![Value::Array(
+                keyed.into_iter().map(|(_, item)| item).collect(),
+            )])
+        }
+        ("group_by", [f]) => {
+            let mut keyed = by_key(f, input)?;
+            keyed.sort_by(|(a, _), (b, _)| compare(a, b));
+            let mut groups: Vec<(Value, Vec<Value>)> = vec![];
+            for (key, item) in keyed {
+                match groups.last_mut() {
+                    Some((last, group)) if compare(last, &key) == Ordering::Equal => {
+                        group.push(item)
+                    }
+                    _ => groups.push((key, vec![item])),
+                }
+            }
+            Ok(vec![Value::Array(
+                groups
+                    .into_iter()
+                    .map(|(_, group)| Value::Array(group))
+                    .collect(),
+            )])
+        }
+        ("unique_by", [f]) => {
+            let mut keyed = by_key(f, input)?;
+            keyed.sort_by(|(a, _), (b, _)| compare(a, b));
+            keyed.dedup_by(|(a, _), (b, _)| compare(a, b) == Ordering::Equal);
+            Ok(vec![Value::Array(
+                keyed.into_iter().map(|(_, item)| item).collect(),
+            )])
+        }
+        ("any", [f]) => {
+            let mut found = false;
+            for item in iterate(input.clone())? {
+                found = found || evaluate(f, &item)?.iter().any(is_truthy);
+            }
+            Ok(vec![Value::Bool(found)])
+        }
+        ("all", [f]) => {
+            let mut holds = true;
+            for item in iterate(input.clone())? {
+                holds = holds && evaluate(f, &item)?.iter().all(is_truthy);
+            }
+            Ok(vec![Value::Bool(holds)])
+        }
+        ("first", [f]) => Ok(evaluate(f, input)?.into_iter().take(1).collect()),
+        ("last", [f]) => Ok(evaluate(f, input)?.into_iter().last().into_iter().collect()),
+        ("has", [key]) => evaluate(key, input)?
+            .iter()
+            .map(|key| match (input, key) {
+                (Value::Object(pairs), Value::String(key)) => {
+                    Ok(Value::Bool(pairs.iter().any(|(name, _)| name == key)))
+                }
+                (Value::Array(items), Value::Number(index)) => Ok(Value::Bool(
+                    *index >= 0.0 && (*index as usize) < items.len(),
+                )),
+                _ => Err(failed(format!(
+                    "cannot check whether {} has a {} key",
+                    type_name(input),
+                    type_name(key)
+                ))),
+            })
+            .collect(),
+        ("join", [separator]) => {
+            let mut outputs = vec![];
+            for separator in evaluate(separator, input)? {
+                let separator = string(&separator)?;
+                let mut parts = vec![];
+                for item in array(input)? {
+                    parts.push(match item {
+                        Value::Null => String::new(),
+                        Value::Array(_) | Value::Object(_) => {
+                            return Err(failed(format!("cannot join with {}", type_name(item))))
+                        }
+                        item => to_text(item),
+                    });
+                }
+                outputs.push(Value::String(parts.join(separator)));
+            }
+            Ok(outputs)
+        }
+        ("split", [separator]) => each_string(separator, input, |text, separator| {
+            let parts = match separator {
+                "" => text.chars().map(String::from).collect(),
+                _ => text.split(separator).map(String::from).collect::<Vec<_>>(),
+            };
+            Value::Array(parts.into_iter().map(Value::String).collect())
+        }),
+        ("startswith", [prefix]) => each_string(prefix, input, |text, prefix| {
+            Value::Bool(text.starts_with(prefix))
+        }),
+        ("endswith", [suffix]) => each_string(suffix, input, |text, suffix| {
+            Value::Bool(text.ends_with(suffix))
+        }),
+        ("ltrimstr", [prefix]) => each_string(prefix, input, |text, prefix| {
+            Value::String(text.strip_prefix(prefix).unwrap_or(text).to_owned())
+        }),
+        ("rtrimstr", [suffix]) => each_string(suffix, input, |text, suffix| {
+            Value::String(text.strip_suffix(suffix).unwrap_or(text).to_owned())
+        }),
+        ("range", [end]) => {
+            let mut outputs = vec![];
+            for end in evaluate(end, input)? {
+                outputs.extend(range(0.0, number(&end)?));
+            }
+            Ok(outputs)
+        }
+        ("range", [start, end]) => {
+            let mut outputs = vec![];
+            for start in evaluate(start, input)? {
+                for end in evaluate(end, input)? {
+                    outputs.extend(range(number(&start)?, number(&end)?));
+                }
+            }
+            Ok(outputs)
+        }
+        _ => Err(failed(format!(
+            "{}/{} is not defined",
+            name,
+            arguments.len()
+        ))),
+    }
+}
+
+/// The input followed by everything inside it, depth first.
+fn recurse(value: &Value, outputs: &mut Vec<Value>) {
+    outputs.push(value.clone());
+    match value {
+        Value::Array(items) => items.iter().for_each(|item| recurse(item, outputs)),
+        Value::Object(pairs) => pairs.iter().for_each(|(_, item)| recurse(item, outputs)),
+        _ => {}
+    }
+}
+
+fn index_value(target: &Value, index: &Value) -> Result<Value, FormatterError> {
+    match (target, index) {
+        (Value::Object(_), Value::String(key)) => {
+            Ok(target.get(key).cloned().unwrap_or(Value::Null))
+        }
+        (Value::Array(items), Value::Number(index)) => {
+            let index = index.floor();
+            let index = if index < 0.0 {
+                index + items.len() as f64
+            } else {
+                index
+            };
+            Ok(match index >= 0.0 {
+                true => items.get(index as usize).cloned().unwrap_or(Value::Null),
+                false => Value::Null,
+            })
+        }
+        (Value::Null, Value::String(_)) | (Value::Null, Value::Number(_)) => Ok(Value::Null),
+        (_, Value::String(key)) => Err(failed(format!(
+            "cannot index {} with \"{}\"",
+            type_name(target),
+            key
+        ))),
+        _ => Err(failed(format!(
+            "cannot index {} with {}",
+            type_name(target),
+            type_name(index)
+        ))),
+    }
+}
+
+fn slice(target: &Value, start: &Value, end: &Value) -> Result<Value, FormatterError> {
+    let bounds = |length: usize| -> Result<(usize, usize), FormatterError> {
+        let clamp = |bound: &Value, default: usize| match bound {
+            Value::Null => Ok(default),
+            Value::Number(number) => {
+                let number = number.floor();
+                let number = if number < 0.0 {
+                    number + length as f64
+                } else {
+                    number
+                };
+                Ok(number.clamp(0.0, length as f64) as usize)
+            }
+            other => Err(failed(format!(
+                "slice bounds must be numbers, found {}",
+                type_name(other)
+            ))),
+        };
+        let start = clamp(start, 0)?;
+        Ok((start, clamp(end, length)?.max(start)))
+    };
+
+    match target {
+        Value::Null => Ok(Value::Null),
+        Value::Array(items) => {
+            let (start, end) = bounds(items.len())?;
+            Ok(Value::Array(items[start..end].to_vec()))
+        }
+        Value::String(string) => {
+            let characters = string.chars().collect::<Vec<char>>();
+            let (start, end) = bounds(characters.len())?;
+            Ok(Value::String(characters[start..end].iter().collect()))
+        }
+        other => Err(failed(format!("cannot slice {}", type_name(other)))),
+    }
+}
+
+fn iterate(value: Value) -> Result<Vec<Value>, FormatterError> {
+    match value {
+        Value::Array(items) => Ok(items),
+        Value::Object(pairs) => Ok(pairs.into_iter().map(|(_, value)| value).collect()),
+        other => Err(failed(format!("cannot iterate over {}", describe(&other)))),
+    }
+}
+
+fn binary(operator: Operator, left: &Value, right: &Value) -> Result<Value, FormatterError> {
+    let ordering = || compare(left, right);
+    let result = match (operator, left, right) {
+        (Operator::Equal, _, _) => Value::Bool(values_equal(left, right)),
+        (Operator::NotEqual, _, _) => Value::Bool(!values_equal(left, right)),
+        (Operator::Less, _, _) => Value::Bool(ordering() == Ordering::Less),
+        (Operator::LessOrEqual, _, _) => Value::Bool(ordering() != Ordering::Greater),
+        (Operator::Greater, _, _) => Value::Bool(ordering() == Ordering::Greater),
+        (Operator::GreaterOrEqual, _, _) => Value::Bool(ordering() != Ordering::Less),
+
+        (Operator::Add, Value::Null, other) | (Operator::Add, other, Value::Null) => other.clone(),
+        (Operator::Add, Value::Number(a), Value::Number(b)) => Value::Number(a + b),
+        (Operator::Add, Value::String(a), Value::String(b)) => Value::String(a.clone() + b),
+        (Operator::Add, Value::Array(a), Value::Array(b)) => {
+            Value::Array(a.iter().chain(b).cloned().collect())
+        }
+        (Operator::Add, Value::Object(a), Value::Object(b)) => {
+            let mut merged = a.clone();
+            for (key, value) in b {
+                set_member(&mut merged, key, value.clone());
+            }
+            Value::Object(merged)
+        }
+
+        (Operator::Subtract, Value::Number(a), Value::Number(b)) => Value::Number(a - b),
+        (Operator::Subtract, Value::Array(a), Value::Array(b)) => Value::Array(
+            a.iter()
+                .filter(|item| !b.iter().any(|other| values_equal(item, other)))
+                .cloned()
+                .collect(),
+        ),
+
+        (Operator::Multiply, Value::Number(a), Value::Number(b)) => Value::Number(a * b),
+        (Operator::Multiply, Value::Object(_), Value::Object(_)) => deep_merge(left, right),
+
+        (Operator::Divide, Value::Number(_), Value::Number(b)) if *b == 0.0 => {
+            return Err(failed(format!(
+                "{} and {} cannot be divided because the divisor is zero",
+                describe(left),
+                describe(right)
+            )))
+        }
+        (Operator::Divide, Value::Number(a), Value::Number(b)) => Value::Number(a / b),
+        (Operator::Divide, Value::String(a), Value::String(b)) => Value::Array(
+            a.split(b.as_str())
+                .map(|part| Value::String(part.to_owned()))
+                .collect(),
+        ),
+
+        (Operator::Modulo, Value::Number(a), Value::Number(b)) if b.trunc() == 0.0 => {
+            return Err(failed(format!(
+                "{} and {} cannot be divided because the divisor is zero",
+                describe(left),
+                describe(right)
+            )))
+        }
+        (Operator::Modulo, Value::Number(a), Value::Number(b)) => {
+            Value::Number((a.trunc() % b.trunc()).trunc())
+        }
+
+        (operator, _, _) => {
+            return Err(failed(format!(
+                "{} and {} cannot be {}",
+                describe(left),
+                describe(right),
+                verb(operator)
+            )));
+        }
+    };
+    match result {
+        Value::Number(number) if !number.is_finite() => Err(failed(format!(
+            "{} and {} cannot be {} because the result is out of range",
+            describe(left),
+            describe(right),
+            verb(operator)
+        ))),
+        result => Ok(result),
+    }
+}
+
+fn verb(operator: Operator) -> &'static str {
+    match operator {
+        Operator::Add => "added",
+        Operator::Subtract => "subtracted",
+        Operator::Multiply => "multiplied",
+        _ => "divided",
+    }
+}
+
+/// Merges objects recursively, `right` winning where both have a non-object value.
+fn deep_merge(left: &Value, right: &Value) -> Value {
+    match (left, right) {
+        (Value::Object(left), Value::Object(right)) => {
+            let mut merged = left.clone();
+            for (key, value) in right {
+                let value = match merged.iter().find(|(name, _)| name == key) {
+                    Some((_, existing)) => deep_merge(existing, value),
+                    None => value.clone(),
+                };
+                set_member(&mut merged, key, value);
+            }
+            Value::Object(merged)
+        }
+        (_, right) => right.clone(),
+    }
+}
+
+/// Replaces the member called `key` in place, or appends it.
+fn set_member(pairs: &mut Vec<(String, Value)>, key: &str, value: Value) {
+    match pairs.iter_mut().find(|(name, _)| name == key) {
+        Some((_, existing)) => *existing = value,
+        None => pairs.push((key.to_owned(), value)),
+    }
+}
+
+/// The order jq sorts in: null, false, true, numbers, strings, arrays, then objects.
+/// Objects compare by their sorted keys first, then by their values in key order.
+fn compare(left: &Value, right: &Value) -> Ordering {
+    let rank = |value: &Value| match value {
+        Value::Null => 0,
+        Value::Bool(false) => 1,
+        Value::Bool(true) => 2,
+        Value::Number(_) => 3,
+        Value::String(_) => 4,
+        Value::Array(_) => 5,
+        Value::Object(_) => 6,
+    };
+
+    match (left, right) {
+        (Value::Number(a), Value::Number(b)) => a.partial_cmp(b).unwrap_or(Ordering::Equal),
+        (Value::String(a), Value::String(b)) => a.cmp(b),
+        (Value::Array(a), Value::Array(b)) => a
+            .iter()
+            .zip(b)
+            .map(|(a, b)| compare(a, b))
+            .find(|ordering| *ordering != Ordering::Equal)
+            .unwrap_or_else(|| a.len().cmp(&b.len())),
+        (Value::Object(a), Value::Object(b)) => {
+            let (a_keys, b_keys) = (sorted_keys(a), sorted_keys(b));
+            a_keys.cmp(&b_keys).then_with(|| {
+                a_keys
+                    .iter()
+                    .map(|key| compare(left.get(key).unwrap(), right.get(key).unwrap()))
+                    .find(|ordering| *ordering != Ordering::Equal)
+                    .unwrap_or(Ordering::Equal)
+            })
+        }
+        _ => rank(left).cmp(&rank(right)),
+    }
+}
+
+fn sorted_keys(pairs: &[(String, Value)]) -> Vec<&String> {
+    let mut keys = pairs.iter().map(|(key, _)| key).collect::<Vec<&String>>();
+    keys.sort();
+    keys
+}
+
+fn length(value: &Value) -> Result<Value, FormatterError> {
+    let length = match value {
+        Value::Null => 0.0,
+        Value::Bool(_) => return Err(failed(format!("{} has no length", describe(value)))),
+        Value::Number(number) => number.abs(),
+        Value::String(string) => string.chars().count() as f64,
+        Value::Array(items) => items.len() as f64,
+        Value::Object(pairs) => pairs.len() as f64,
+    };
+    Ok(Value::Number(length))
+}
+
+fn keys(value: &Value, sorted: bool) -> Result<Value, FormatterError> {
+    match value {
+        Value::Object(pairs) => {
+            let mut keys = pairs.iter().map(|(key, _)| key.clone()).collect::<Vec<_>>();
+            if sorted {
+                keys.sort();
+            }
+            Ok(Value::Array(keys.into_iter().map(Value::String).collect()))
+        }
+        Value::Array(items) => Ok(Value::Array(
+            (0..items.len())
+                .map(|index| Value::Number(index as f64))
+                .collect(),
+        )),
+        other => Err(failed(format!("{} has no keys", describe(other)))),
+    }
+}
+
+fn to_entries(value: &Value) -> Result<Value, FormatterError> {
+    match value {
+        Value::Object(pairs) => Ok(Value::Array(
+            pairs
+                .iter()
+                .map(|(key, value)| {
+                    Value::Object(vec![
+                        (String::from("key"), Value::String(key.clone())),
+                        (String::from("value"), value.clone()),
+                    ])
+                })
+                .collect(),
+        )),
+        other => Err(failed(format!("{} has no entries", describe(other)))),
+    }
+}
+
+/// Accepts `key`/`k`/`name` and `value`/`v` members, like jq.
+fn from_entries(value: &Value) -> Result<Value, FormatterError> {
+    let mut pairs = vec![];
+    for entry in array(value)? {
+        let key = ["key", "k", "name"]
+            .iter()
+            .find_map(|name| entry.get(name).filter(|key| is_truthy(key)));
+        let key = match key {
+            Some(Value::String(key)) => key.clone(),
+            Some(key @ Value::Number(_)) | Some(key @ Value::Bool(_)) => to_text(key),
+            other => {
+                return Err(failed(format!(
+                    "cannot use {} as an object key",
+                    describe(other.unwrap_or(&Value::Null))
+                )))
+            }
+        };
+        let value = ["value", "v"]
+            .iter()
+            .find_map(|name| entry.get(name))
+            .cloned()
+            .unwrap_or(Value::Null);
+        set_member(&mut pairs, &key, value);
+    }
+    Ok(Value::Object(pairs))
+}
+
+/// Pairs each array item with the array of `f`'s outputs for it, for the `*_by` builtins.
+fn by_key(f: &Expression, input: &Value) -> Result<Vec<(Value, Value)>, FormatterError> {
+    array(input)?
+        .iter()
+        .map(|item| Ok((Value::Array(evaluate(f, item)?), item.clone())))
+        .collect()
+}
+
+/// Applies `operation` to the input string and each string `argument` produces.
+fn each_string(
+    argument: &Expression,
+    input: &Value,
+    operation: impl Fn(&str, &str) -> Value,
+) -> Outputs {
+    let text = string(input)?;
+    evaluate(argument, input)?
+        .iter()
+        .map(|argument| Ok(operation(text, string(argument)?)))
+        .collect()
+}
+
+fn range(start: f64, end: f64) -> Vec<Value> {
+    let mut numbers = vec![];
+    let mut number = start;
+    while number < end {
+        numbers.push(Value::Number(number));
+        number += 1.0;
+    }
+    numbers
+}
+
+fn array(value: &Value) -> Result<&Vec<Value>, FormatterError> {
+    value
+        .as_array()
+        .ok_or_else(|| failed(format!("{} is not an array", describe(value))))
+}
+
+fn string(value: &Value) -> Result<&str, FormatterError> {
+    value
+        .as_str()
+        .ok_or_else(|| failed(format!("{} is not a string", describe(value))))
+}
+
+fn number(value: &Value) -> Result<f64, FormatterError> {
+    value
+        .as_f64()
+        .ok_or_else(|| failed(format!("{} is not a number", describe(value))))
+}
+
+fn is_truthy(value: &Value) -> bool {
+    !matches!(value, Value::Null | Value::Bool(false))
+}
+
+/// Strings as they are, anything else as compact JSON.
+fn to_text(value: &Value) -> String {
+    match value {
+        Value::String(string) => string.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+/// The type and (shortened) value, e.g. `string ("abc")`.
+fn describe(value: &Value) -> String {
+    let mut text = value.to_string();
+    if text.chars().count() > 20 {
+        text = text.chars().take(17).collect::<String>() + "...";
+    }
+    format!("{} ({})", type_name(value), text)
+}
+
+fn failed(message: String) -> FormatterError {
+    FormatterError::FilterFailed(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::parser::parse_filter;
+    use super::*;
+
+    fn run(filter: &str, input: &str) -> Vec<String> {
+        let expression = parse_filter(filter).unwrap();
+        evaluate(&expression, &crate::parse(input).unwrap())
+            .unwrap()
+            .iter()
+            .map(Value::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn paths_and_iteration() {
+        let input = r#"{"a": {"b": [1, 2, 3]}, "c": null}"#;
+        assert_eq!(run(".a.b[1]", input), vec!["2"]);
+        assert_eq!(run(".a.b[-1]", input), vec!["3"]);
+        assert_eq!(run(".a.b[]", input), vec!["1", "2", "3"]);
+        assert_eq!(run(".a.b[1:]", input), vec!["[2,3]"]);
+        assert_eq!(run(".c.d", input), vec!["null"]);
+        assert_eq!(run(".a.b.c?", input), Vec::<String>::new());
+        assert_eq!(run("[..] | length", input), vec!["7"]);
+    }
+
+    #[test]
+    fn map_select_and_construction() {
+        let input = r#"[{"name": "a", "price": 5}, {"name": "b", "price": 15}]"#;
+        assert_eq!(
+            run("map(select(.price > 10) | {name, cost: .price * 2})", input),
+            vec![r#"[{"name":"b","cost":30}]"#]
+        );
+        assert_eq!(run("[.[].name]", input), vec![r#"["a","b"]"#]);
+        assert_eq!(run("map(.price) | add", input), vec!["20"]);
+        assert_eq!(
+            run(".[] | {(.name): .price}", input),
+            vec![r#"{"a":5}"#, r#"{"b":15}"#]
+        );
+    }
+
+    #[test]
+    fn generators_multiply_out() {
+        assert_eq!(
+            run("(1, 2) + (10, 20)", "null"),
+            vec!["11", "12", "21", "22"]
+        );
+        assert_eq!(
+            run("{a: (1, 2), b: 3}", "null"),
+            vec![r#"{"a":1,"b":3}"#, r#"{"a":2,"b":3}"#]
+        );
+    }
+
+    #[test]
+    fn keys_length_and_interpolation() {
+        let input = r#"{"b": "xyz", "a": [1, 2]}"#;
+        assert_eq!(run("keys", input), vec![r#"["a","b"]"#]);
+        assert_eq!(run("keys_unsorted", input), vec![r#"["b","a"]"#]);
+        assert_eq!(run(".b | length", input), vec!["3"]);
+        assert_eq!(
+            run(r#""\(.b) has \(.a | length) \("items")""#, input),
+            vec![r#""xyz has 2 items""#]
+        );
+    }
+
+    #[test]
+    fn conditionals_and_alternatives() {
+        assert_eq!(
+            run(
+                r#".[] | if . > 1 then "big" elif . == 1 then "one" else "small" end"#,
+                "[0, 1, 2]"
+            ),
+            vec![r#""small""#, r#""one""#, r#""big""#]
+        );
+        assert_eq!(run(".missing // \"default\"", "{}"), vec![r#""default""#]);
+        assert_eq!(run(".[] | not", "[null, 0]"), vec!["true", "false"]);
+    }
+
+    #[test]
+    fn sorting_follows_jq_order() {
+        assert_eq!(
+            run("sort", r#"[{"a": 1}, [1], "s", 2, true, false, null]"#),
+            vec![r#"[null,false,true,2,"s",[1],{"a":1}]"#]
+        );
+        assert_eq!(
+            run(
+                "sort_by(.n) | map(.id)",
+                r#"[{"id": 1, "n": 3}, {"id": 2, "n": 1}]"#
+            ),
+            vec!["[2,1]"]
+        );
+        assert_eq!(
+            run("group_by(. % 2)", "[1, 2, 3, 4]"),
+            vec!["[[2,4],[1,3]]"]
+        );
+    }
+
+    #[test]
+    fn arithmetic_on_other_types() {
+        assert_eq!(run(r#""a" + "b""#, "null"), vec![r#""ab""#]);
+        assert_eq!(run("[1, 2, 1] - [1]", "null"), vec!["[2]"]);
+        assert_eq!(
+            run(r#"{"a": {"b": 1}} * {"a": {"c": 2}}"#, "null"),
+            vec![r#"{"a":{"b":1,"c":2}}"#]
+        );
+        assert_eq!(run("7 % 3, 7 / 2", "null"), vec!["1", "3.5"]);
+    }
+
+    #[test]
+    fn errors_describe_the_values() {
+        let expression = parse_filter(".a").unwrap();
+        assert_eq!(
+            evaluate(&expression, &Value::Number(1.0)).unwrap_err(),
+            FormatterError::FilterFailed(String::from("cannot index number with \"a\""))
+        );
+        let expression = parse_filter("1 / 0").unwrap();
+        assert_eq!(
+            evaluate(&expression, &Value::Null).unwrap_err(),
+            FormatterError::FilterFailed(String::from(
+                "number (1) and number (0) cannot be divided because the divisor is zero"
+            ))
+        );
+        let huge = crate::parse(r#"{"a": 1e308}"#).unwrap();
+        for (filter, verb) in [
+            (".a * 10", "multiplied"),
+            (".a + .a", "added"),
+            ("-.a - .a", "subtracted"),
+        ] {
+            let error = evaluate(&parse_filter(filter).unwrap(), &huge).unwrap_err();
+            assert!(
+                error.to_string().contains(&format!(
+                    "cannot be {} because the result is out of range",
+                    verb
+                )),
+                "{}",
+                filter
+            );
+        }
+        let expression = parse_filter("nope(1)").unwrap();
+        assert_eq!(
+            evaluate(&expression, &Value::Null).unwrap_err(),
+            FormatterError::FilterFailed(String::from("nope/1 is not defined"))
+        );
+    }
+}
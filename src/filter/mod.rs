@@ -0,0 +1,108 @@
+//! A jq-style filter language for transforming documents.
+use crate::formatter::errors::FormatterError;
+use crate::formatter::{stringify_with_config, FormatConfig};
+use crate::parser::{Node, AST};
+use crate::value::Value;
+
+mod ast;
+mod evaluate;
+mod parser;
+
+/// A parsed filter such as `.items[] | select(.price < 10) | {name, total: .price * .count}`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Filter {
+    expression: ast::Expression,
+}
+
+impl Filter {
+    /// Fails with `InvalidFilter` and the character position of the problem.
+    pub fn parse(source: &str) -> Result<Filter, FormatterError> {
+        Ok(Filter {
+            expression: parser::parse_filter(source)?,
+        })
+    }
+
+    /// Every output of the filter for `input`, in order. A filter can produce no output (like
+    /// `empty` or `select`) or several (like `.[]`). Fails with `FilterFailed` when it is applied
+    /// to values it can't handle, e.g. indexing a number.
+    pub fn apply(&self, input: &Value) -> Result<Vec<Value>, FormatterError> {
+        evaluate::evaluate(&self.expression, input)
+    }
+}
+
+/// Runs `filter` over the document in `input` and prints each output with `config`, one after
+/// the other.
+pub fn filter(
+    input: &str,
+    filter: &str,
+    config: Option<FormatConfig>,
+) -> Result<String, FormatterError> {
+    let filter = Filter::parse(filter)?;
+    let mut config = config.unwrap_or_default();
+    config.detect_line_ending(input);
+
+    let mut printed = String::new();
+    for output in filter.apply(&crate::parse(input)?)? {
+        let root = Node::from(output);
+        printed.push_str(&stringify_with_config(AST { root }, &config));
+        if !printed.ends_with('\n') {
+            printed.push_str(config.newline());
+        }
+    }
+    Ok(printed)
+}
+
+impl Value {
+    /// Runs the filter `filter` against this value.
+    pub fn filter(&self, filter: &str) -> Result<Vec<Value>, FormatterError> {
+        Filter::parse(filter)?.apply(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Delimiter, DelimiterCount};
+
+    #[test]
+    fn prints_each_output() {
+        let config = FormatConfig::new(Delimiter::Spaces(DelimiterCount::Two));
+        assert_eq!(
+            filter(r#"{"a": [1, {"b": 2}]}"#, ".a[]", Some(config)).unwrap(),
+            "1\n{\n  \"b\": 2\n}\n"
+        );
+        assert_eq!(filter("[1, 2]", ".[] | select(. > 5)", None).unwrap(), "");
+    }
+
+    #[test]
+    fn separates_outputs_with_the_documents_line_ending() {
+        assert_eq!(
+            filter("[1,\r\n[2]]", ".[]", None).unwrap(),
+            "1\r\n[\r\n    2\r\n]\r\n"
+        );
+    }
+
+    #[test]
+    fn value_filter_returns_outputs() {
+        let value = crate::parse(r#"{"users": [{"name": "ada"}, {"name": "bob"}]}"#).unwrap();
+        assert_eq!(
+            value.filter(r#"[.users[] | "@\(.name)"]"#).unwrap(),
+            vec![Value::Array(vec![
+                Value::String(String::from("@ada")),
+                Value::String(String::from("@bob")),
+            ])]
+        );
+    }
+
+    #[test]
+    fn reports_parse_and_runtime_errors() {
+        assert_eq!(
+            filter("{}", ".a[", None).unwrap_err(),
+            FormatterError::InvalidFilter(3)
+        );
+        assert_eq!(
+            filter("true", ".[]", None).unwrap_err(),
+            FormatterError::FilterFailed(String::from("cannot iterate over boolean (true)"))
+        );
+    }
+}
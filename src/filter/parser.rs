@@ -0,0 +1,629 @@
+use crate::formatter::errors::FormatterError;
+use crate::value::{unescape, Value};
+
+use super::ast::{Expression, Operator, Part};
+
+const KEYWORDS: [&str; 8] = ["if", "then", "elif", "else", "end", "and", "or", "as"];
+
+/// Parses a filter such as `.items[] | select(.price < 10) | {name, total: .price * .count}`.
+pub fn parse_filter(source: &str) -> Result<Expression, FormatterError> {
+    let mut parser = Parser {
+        characters: source.chars().collect(),
+        position: 0,
+    };
+
+    let expression = parser.pipe()?;
+    parser.skip_blank();
+    if parser.position != parser.characters.len() {
+        return Err(parser.error());
+    }
+    Ok(expression)
+}
+
+struct Parser {
+    characters: Vec<char>,
+    position: usize,
+}
+
+impl Parser {
+    fn error(&self) -> FormatterError {
+        FormatterError::InvalidFilter(self.position)
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.characters.get(self.position).copied()
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<char> {
+        self.characters.get(self.position + offset).copied()
+    }
+
+    fn eat(&mut self, character: char) -> bool {
+        if self.peek() == Some(character) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn eat_str(&mut self, literal: &str) -> bool {
+        let matches = literal
+            .chars()
+            .enumerate()
+            .all(|(offset, character)| self.peek_at(offset) == Some(character));
+        if matches {
+            self.position += literal.chars().count();
+        }
+        matches
+    }
+
+    /// Eats `word` only when it isn't the start of a longer identifier.
+    fn eat_keyword(&mut self, word: &str) -> bool {
+        let start = self.position;
+        if self.eat_str(word) && !self.peek().is_some_and(is_identifier) {
+            true
+        } else {
+            self.position = start;
+            false
+        }
+    }
+
+    fn expect(&mut self, character: char) -> Result<(), FormatterError> {
+        self.skip_blank();
+        if self.eat(character) {
+            Ok(())
+        } else {
+            Err(self.error())
+        }
+    }
+
+    fn expect_keyword(&mut self, word: &str) -> Result<(), FormatterError> {
+        self.skip_blank();
+        if self.eat_keyword(word) {
+            Ok(())
+        } else {
+            Err(self.error())
+        }
+    }
+
+    /// Skips whitespace and `#` comments.
+    fn skip_blank(&mut self) {
+        loop {
+            match self.peek() {
+                Some(' ') | Some('\t') | Some('\n') | Some('\r') => self.position += 1,
+                Some('#') => {
+                    while !matches!(self.peek(), None | Some('\n')) {
+                        self.position += 1;
+                    }
+                }
+                _ => return,
+            }
+        }
+    }
+
+    fn pipe(&mut self) -> Result<Expression, FormatterError> {
+        let left = self.comma()?;
+        self.skip_blank();
+        if self.eat('|') {
+            Ok(Expression::Pipe(Box::new(left), Box::new(self.pipe()?)))
+        } else {
+            Ok(left)
+        }
+    }
+
+    fn comma(&mut self) -> Result<Expression, FormatterError> {
+        let mut left = self.alternative()?;
+        loop {
+            self.skip_blank();
+            if !self.eat(',') {
+                return Ok(left);
+            }
+            left = Expression::Comma(Box::new(left), Box::new(self.alternative()?));
+        }
+    }
+
+    fn alternative(&mut self) -> Result<Expression, FormatterError> {
+        let left = self.or()?;
+        self.skip_blank();
+        if self.eat_str("//") {
+            Ok(Expression::Alternative(
+                Box::new(left),
+                Box::new(self.alternative()?),
+            ))
+        } else {
+            Ok(left)
+        }
+    }
+
+    fn or(&mut self) -> Result<Expression, FormatterError> {
+        let mut left = self.and()?;
+        loop {
+            self.skip_blank();
+            if !self.eat_keyword("or") {
+                return Ok(left);
+            }
+            left = Expression::Or(Box::new(left), Box::new(self.and()?));
+        }
+    }
+
+    fn and(&mut self) -> Result<Expression, FormatterError> {
+        let mut left = self.comparison()?;
+        loop {
+            self.skip_blank();
+            if !self.eat_keyword("and") {
+                return Ok(left);
+            }
+            left = Expression::And(Box::new(left), Box::new(self.comparison()?));
+        }
+    }
+
+    /// Comparisons don't chain, `1 < 2 < 3` is an error.
+    fn comparison(&mut self) -> Result<Expression, FormatterError> {
+        let left = self.additive()?;
+        self.skip_blank();
+        let operator = match (self.peek(), self.peek_at(1)) {
+            (Some('='), Some('=')) => Operator::Equal,
+            (Some('!'), Some('=')) => Operator::NotEqual,
+            (Some('<'), Some('=')) => Operator::LessOrEqual,
+            (Some('>'), Some('=')) => Operator::GreaterOrEqual,
+            (Some('<'), _) => Operator::Less,
+            (Some('>'), _) => Operator::Greater,
+            _ => return Ok(left),
+        };
+        self.position += match operator {
+            Operator::Less | Operator::Greater => 1,
+            _ => 2,
+        };
+        let right = self.additive()?;
+        Ok(Expression::Binary(
+            operator,
+            Box::new(left),
+            Box::new(right),
+        ))
+    }
+
+    fn additive(&mut self) -> Result<Expression, FormatterError> {
+        let mut left = self.multiplicative()?;
+        loop {
+            self.skip_blank();
+            let operator = match self.peek() {
+                Some('+') => Operator::Add,
+                Some('-') => Operator::Subtract,
+                _ => return Ok(left),
+            };
+            self.position += 1;
+            let right = self.multiplicative()?;
+            left = Expression::Binary(operator, Box::new(left), Box::new(right));
+        }
+    }
+
+    fn multiplicative(&mut self) -> Result<Expression, FormatterError> {
+        let mut left = self.unary()?;
+        loop {
+            self.skip_blank();
+            let operator = match (self.peek(), self.peek_at(1)) {
+                (Some('*'), _) => Operator::Multiply,
+                (Some('/'), Some('/')) => return Ok(left),
+                (Some('/'), _) => Operator::Divide,
+                (Some('%'), _) => Operator::Modulo,
+                _ => return Ok(left),
+            };
+            self.position += 1;
+            let right = self.unary()?;
+            left = Expression::Binary(operator, Box::new(left), Box::new(right));
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expression, FormatterError> {
+        self.skip_blank();
+        if self.eat('-') {
+            Ok(Expression::Negate(Box::new(self.unary()?)))
+        } else {
+            self.postfix()
+        }
+    }
+
+    /// A term followed by any number of `.name`, `[...]` and `?` suffixes.
+    fn postfix(&mut self) -> Result<Expression, FormatterError> {
+        let mut term = self.term()?;
+        loop {
+            match (self.peek(), self.peek_at(1)) {
+                (Some('.'), Some('[')) => {
+                    self.position += 2;
+                    term = self.bracket(term)?;
+                }
+                (Some('.'), Some(next)) if next == '"' || is_identifier_start(next) => {
+                    self.position += 1;
+                    term = self.field(term)?;
+                }
+                (Some('['), _) => {
+                    self.position += 1;
+                    term = self.bracket(term)?;
+                }
+                (Some('?'), _) => {
+                    self.position += 1;
+                    term = Expression::Optional(Box::new(term));
+                }
+                _ => return Ok(term),
+            }
+        }
+    }
+
+    fn term(&mut self) -> Result<Expression, FormatterError> {
+        self.skip_blank();
+        match self.peek() {
+            Some('.') => {
+                self.position += 1;
+                match self.peek() {
+                    Some('.') => {
+                        self.position += 1;
+                        Ok(Expression::Recurse)
+                    }
+                    Some('[') => {
+                        self.position += 1;
+                        self.bracket(Expression::Identity)
+                    }
+                    Some(next) if next == '"' || is_identifier_start(next) => {
+                        self.field(Expression::Identity)
+                    }
+                    _ => Ok(Expression::Identity),
+                }
+            }
+            Some('"') => self.string(),
+            Some(character) if character.is_ascii_digit() => self.number(),
+            Some('(') => {
+                self.position += 1;
+                let expression = self.pipe()?;
+                self.expect(')')?;
+                Ok(expression)
+            }
+            Some('[') => {
+                self.position += 1;
+                self.skip_blank();
+                if self.eat(']') {
+                    return Ok(Expression::Array(None));
+                }
+                let items = self.pipe()?;
+                self.expect(']')?;
+                Ok(Expression::Array(Some(Box::new(items))))
+            }
+            Some('{') => {
+                self.position += 1;
+                self.object()
+            }
+            Some(character) if is_identifier_start(character) => {
+                let start = self.position;
+                let name = self.identifier();
+                match name.as_str() {
+                    "true" => Ok(Expression::Literal(Value::Bool(true))),
+                    "false" => Ok(Expression::Literal(Value::Bool(false))),
+                    "null" => Ok(Expression::Literal(Value::Null)),
+                    "if" => self.conditional(),
+                    name if KEYWORDS.contains(&name) => {
+                        self.position = start;
+                        Err(self.error())
+                    }
+                    _ => self.call(&name),
+                }
+            }
+            _ => Err(self.error()),
+        }
+    }
+
+    fn identifier(&mut self) -> String {
+        let mut name = String::new();
+        while let Some(character) = self.peek().filter(|character| is_identifier(*character)) {
+            name.push(character);
+            self.position += 1;
+        }
+        name
+    }
+
+    /// `.name` or `."name"` after `target`, with the dot already eaten.
+    fn field(&mut self, target: Expression) -> Result<Expression, FormatterError> {
+        let key = match self.peek() {
+            Some('"') => self.string()?,
+            _ => Expression::Literal(Value::String(self.identifier())),
+        };
+        Ok(Expression::Index(Box::new(target), Box::new(key)))
+    }
+
+    /// `[]`, `[index]` or `[start:end]` after `target`, with the `[` already eaten.
+    fn bracket(&mut self, target: Expression) -> Result<Expression, FormatterError> {
+        let target = Box::new(target);
+        self.skip_blank();
+        if self.eat(']') {
+            return Ok(Expression::Iterate(target));
+        }
+
+        let start = match self.peek() {
+            Some(':') => None,
+            _ => Some(Box::new(self.pipe()?)),
+        };
+        self.skip_blank();
+        if !self.eat(':') {
+            self.expect(']')?;
+            return match start {
+                Some(index) => Ok(Expression::Index(target, index)),
+                None => Err(self.error()),
+            };
+        }
+
+        self.skip_blank();
+        let end = match self.peek() {
+            Some(']') => None,
+            _ => Some(Box::new(self.pipe()?)),
+        };
+        self.expect(']')?;
+        Ok(Expression::Slice { target, start, end })
+    }
+
+    fn object(&mut self) -> Result<Expression, FormatterError> {
+        let mut entries = vec![];
+        self.skip_blank();
+        if self.eat('}') {
+            return Ok(Expression::Object(entries));
+        }
+
+        loop {
+            self.skip_blank();
+            let key = match self.peek() {
+                Some('"') => self.string()?,
+                Some('(') => {
+                    self.position += 1;
+                    let key = self.pipe()?;
+                    self.expect(')')?;
+                    key
+                }
+                Some(character) if is_identifier_start(character) => {
+                    Expression::Literal(Value::String(self.identifier()))
+                }
+                _ => return Err(self.error()),
+            };
+
+            self.skip_blank();
+            let value = if self.eat(':') {
+                self.object_value()?
+            } else if let Expression::Literal(_) = key {
+                Expression::Index(Box::new(Expression::Identity), Box::new(key.clone()))
+            } else {
+                return Err(self.error());
+            };
+            entries.push((key, value));
+
+            self.skip_blank();
+            if self.eat('}') {
+                return Ok(Expression::Object(entries));
+            }
+            self.expect(',')?;
+        }
+    }
+
+    /// A member value stops at `,` so `{a: 1, b: 2}` has two members, but may use `|`.
+    fn object_value(&mut self) -> Result<Expression, FormatterError> {
+        let mut value = self.alternative()?;
+        loop {
+            self.skip_blank();
+            if !self.eat('|') {
+                return Ok(value);
+            }
+            value = Expression::Pipe(Box::new(value), Box::new(self.alternative()?));
+        }
+    }
+
+    /// `if` has been eaten.
+    fn conditional(&mut self) -> Result<Expression, FormatterError> {
+        let condition = Box::new(self.pipe()?);
+        self.expect_keyword("then")?;
+        let then = Box::new(self.pipe()?);
+
+        self.skip_blank();
+        let otherwise = if self.eat_keyword("elif") {
+            return Ok(Expression::If {
+                condition,
+                then,
+                otherwise: Some(Box::new(self.conditional()?)),
+            });
+        } else if self.eat_keyword("else") {
+            Some(Box::new(self.pipe()?))
+        } else {
+            None
+        };
+        self.expect_keyword("end")?;
+
+        Ok(Expression::If {
+            condition,
+            then,
+            otherwise,
+        })
+    }
+
+    /// A builtin, with its arguments separated by `;` as in `range(0; 10)`.
+    fn call(&mut self, name: &str) -> Result<Expression, FormatterError> {
+        let mut arguments = vec![];
+        if self.eat('(') {
+            loop {
+                arguments.push(self.pipe()?);
+                self.skip_blank();
+                if self.eat(')') {
+                    break;
+                }
+                self.expect(';')?;
+            }
+        }
+        Ok(Expression::Call(name.to_owned(), arguments))
+    }
+
+    fn number(&mut self) -> Result<Expression, FormatterError> {
+        let start = self.position;
+        let digits = |parser: &mut Parser| {
+            while parser.peek().is_some_and(|c| c.is_ascii_digit()) {
+                parser.position += 1;
+            }
+        };
+
+        digits(self);
+        if self.peek() == Some('.') && self.peek_at(1).is_some_and(|c| c.is_ascii_digit()) {
+            self.position += 1;
+            digits(self);
+        }
+        if matches!(self.peek(), Some('e') | Some('E')) {
+            self.position += 1;
+            if matches!(self.peek(), Some('+') | Some('-')) {
+                self.position += 1;
+            }
+            if !self.peek().is_some_and(|c| c.is_ascii_digit()) {
+                return Err(self.error());
+            }
+            digits(self);
+        }
+
+        let literal = self.characters[start..self.position]
+            .iter()
+            .collect::<String>();
+        literal
+            .parse()
+            .map(|number| Expression::Literal(Value::Number(number)))
+            .map_err(|_| FormatterError::InvalidFilter(start))
+    }
+
+    /// A JSON string literal, which may embed filters as `\(...)`.
+    fn string(&mut self) -> Result<Expression, FormatterError> {
+        self.position += 1;
+        let mut parts = vec![];
+        let mut raw = String::new();
+
+        loop {
+            match self.peek() {
+                None => return Err(self.error()),
+                Some('"') => {
+                    self.position += 1;
+                    break;
+                }
+                Some('\\') if self.peek_at(1) == Some('(') => {
+                    self.position += 2;
+                    if !raw.is_empty() {
+                        parts.push(Part::Text(unescape(&raw)));
+                        raw.clear();
+                    }
+                    parts.push(Part::Expression(self.pipe()?));
+                    self.expect(')')?;
+                }
+                Some('\\') => {
+                    let escaped = self.peek_at(1).ok_or_else(|| self.error())?;
+                    if !"\"\\/bfnrtu".contains(escaped) {
+                        return Err(self.error());
+                    }
+                    raw.push('\\');
+                    raw.push(escaped);
+                    self.position += 2;
+                }
+                Some(character) => {
+                    raw.push(character);
+                    self.position += 1;
+                }
+            }
+        }
+
+        let text = unescape(&raw);
+        match parts.is_empty() {
+            true => Ok(Expression::Literal(Value::String(text))),
+            false => {
+                if !text.is_empty() {
+                    parts.push(Part::Text(text));
+                }
+                Ok(Expression::Interpolation(parts))
+            }
+        }
+    }
+}
+
+fn is_identifier_start(character: char) -> bool {
+    character.is_ascii_alphabetic() || character == '_'
+}
+
+fn is_identifier(character: char) -> bool {
+    character.is_ascii_alphanumeric() || character == '_'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field(name: &str) -> Expression {
+        Expression::Index(
+            Box::new(Expression::Identity),
+            Box::new(Expression::Literal(Value::String(name.to_owned()))),
+        )
+    }
+
+    #[test]
+    fn parses_paths_and_pipes() {
+        assert_eq!(
+            parse_filter(".a | .b").unwrap(),
+            Expression::Pipe(Box::new(field("a")), Box::new(field("b")))
+        );
+        assert_eq!(
+            parse_filter(".a[]").unwrap(),
+            Expression::Iterate(Box::new(field("a")))
+        );
+        assert_eq!(parse_filter(r#"."a b""#).unwrap(), field("a b"));
+    }
+
+    #[test]
+    fn arithmetic_binds_tighter_than_comparison() {
+        let one = || Box::new(Expression::Literal(Value::Number(1.0)));
+        assert_eq!(
+            parse_filter("1 + 1 * 1 == 1").unwrap(),
+            Expression::Binary(
+                Operator::Equal,
+                Box::new(Expression::Binary(
+                    Operator::Add,
+                    one(),
+                    Box::new(Expression::Binary(Operator::Multiply, one(), one()))
+                )),
+                one()
+            )
+        );
+    }
+
+    #[test]
+    fn object_shorthand_reads_the_field() {
+        assert_eq!(
+            parse_filter("{name}").unwrap(),
+            Expression::Object(vec![(
+                Expression::Literal(Value::String(String::from("name"))),
+                field("name")
+            )])
+        );
+    }
+
+    #[test]
+    fn strings_interpolate() {
+        assert_eq!(
+            parse_filter(r#""id: \(.id)!""#).unwrap(),
+            Expression::Interpolation(vec![
+                Part::Text(String::from("id: ")),
+                Part::Expression(field("id")),
+                Part::Text(String::from("!")),
+            ])
+        );
+    }
+
+    #[test]
+    fn errors_report_positions() {
+        assert_eq!(
+            parse_filter(".a | ").unwrap_err(),
+            FormatterError::InvalidFilter(5)
+        );
+        assert_eq!(
+            parse_filter("{a: 1").unwrap_err(),
+            FormatterError::InvalidFilter(5)
+        );
+        assert_eq!(
+            parse_filter("if . then 1").unwrap_err(),
+            FormatterError::InvalidFilter(11)
+        );
+    }
+}
@@ -0,0 +1,299 @@
+//! Flattening nested documents into one object of leaf values, and back.
+use std::collections::HashSet;
+
+use crate::formatter::errors::FormatterError;
+use crate::formatter::FormatConfig;
+use crate::value::{parse_pointer, to_pointer, Value};
+
+/// How the path to each leaf is written as a key.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Keys {
+    /// Member names and array indexes joined by a separator, e.g. `a.b.0`.
+    Separator(String),
+    /// A JSON Pointer, e.g. `/a/b/0`, which stays unambiguous whatever the member names hold.
+    Pointer,
+}
+
+pub struct FlattenConfig {
+    keys: Keys,
+}
+
+impl FlattenConfig {
+    pub fn new() -> FlattenConfig {
+        FlattenConfig {
+            keys: Keys::Separator(String::from(".")),
+        }
+    }
+
+    pub fn with_separator(mut self, separator: &str) -> FlattenConfig {
+        self.keys = Keys::Separator(separator.to_owned());
+        self
+    }
+
+    pub fn with_pointer_keys(mut self) -> FlattenConfig {
+        self.keys = Keys::Pointer;
+        self
+    }
+}
+
+impl Default for FlattenConfig {
+    fn default() -> Self {
+        FlattenConfig::new()
+    }
+}
+
+/// Turns `{"a": {"b": [1, 2]}}` into `{"a.b.0": 1, "a.b.1": 2}`, with the leaves in document
+/// order. Empty objects and arrays are leaves too, so `unflatten` gives the same document back.
+///
+/// Fails with `ConflictingKeys` when two leaves get the same key, as `{"a.b": 1, "a": {"b": 2}}`
+/// does with the default separator.
+pub fn flatten(value: &Value, config: &FlattenConfig) -> Result<Value, FormatterError> {
+    let mut leaves = vec![];
+    collect(&mut vec![], value, config, &mut leaves, &mut HashSet::new())?;
+    Ok(Value::Object(leaves))
+}
+
+/// The reverse of `flatten`. Objects whose keys are exactly `0`, `1`, `2`… in order become
+/// arrays. Anything other than an object is returned as it is.
+///
+/// The flat keys don't say whether a level was an array or an object, so a round trip turns
+/// an object like `{"0": "a", "1": "b"}` into the array `["a", "b"]`.
+///
+/// Fails with `ConflictingKeys` when one key is a prefix of another, as in `a` and `a.b`.
+pub fn unflatten(value: &Value, config: &FlattenConfig) -> Result<Value, FormatterError> {
+    let pairs = match value {
+        Value::Object(pairs) => pairs,
+        value => return Ok(value.clone()),
+    };
+
+    let mut root = Tree::Branch(vec![]);
+    for (key, leaf) in pairs {
+        let path = match &config.keys {
+            Keys::Separator(separator) if !separator.is_empty() => {
+                key.split(separator.as_str()).map(String::from).collect()
+            }
+            Keys::Separator(_) => vec![key.clone()],
+            Keys::Pointer => parse_pointer(key)?,
+        };
+        if !root.insert(&path, leaf.clone()) {
+            return Err(FormatterError::ConflictingKeys(key.clone()));
+        }
+    }
+    Ok(root.into_value())
+}
+
+/// Parses `input`, flattens it and formats the result.
+pub fn flatten_document(
+    input: &str,
+    flatten: Option<FlattenConfig>,
+    config: Option<FormatConfig>,
+) -> Result<String, FormatterError> {
+    let document = crate::parse(input)?;
+    Ok(self::flatten(&document, &flatten.unwrap_or_default())?.format_like(input, config))
+}
+
+/// Parses `input`, unflattens it and formats the result.
+pub fn unflatten_document(
+    input: &str,
+    flatten: Option<FlattenConfig>,
+    config: Option<FormatConfig>,
+) -> Result<String, FormatterError> {
+    let document = crate::parse(input)?;
+    Ok(unflatten(&document, &flatten.unwrap_or_default())?.format_like(input, config))
+}
+
+fn collect(
+    path: &mut Vec<String>,
+    value: &Value,
+    config: &FlattenConfig,
+    leaves: &mut Vec<(String, Value)>,
+    keys: &mut HashSet<String>,
+) -> Result<(), FormatterError> {
+    match value {
+        Value::Object(pairs) if !pairs.is_empty() => {
+            for (key, member) in pairs {
+                path.push(key.clone());
+                collect(path, member, config, leaves, keys)?;
+                path.pop();
+            }
+        }
+        Value::Array(items) if !items.is_empty() => {
+            for (index, item) in items.iter().enumerate() {
+                path.push(index.to_string());
+                collect(path, item, config, leaves, keys)?;
+                path.pop();
+            }
+        }
+        leaf => {
+            let key = match &config.keys {
+                Keys::Separator(separator) => path.join(separator),
+                Keys::Pointer => to_pointer(path.iter()),
+            };
+            if !keys.insert(key.clone()) {
+                return Err(FormatterError::ConflictingKeys(key));
+            }
+            leaves.push((key, leaf.clone()));
+        }
+    }
+    Ok(())
+}
+
+/// The document being rebuilt. A branch only becomes an object or an array once every key is in.
+enum Tree {
+    Leaf(Value),
+    Branch(Vec<(String, Tree)>),
+}
+
+impl Tree {
+    /// Puts `value` at `path`, or returns false when something is already there or above it.
+    fn insert(&mut self, path: &[String], value: Value) -> bool {
+        match (self, path.split_first()) {
+            (tree @ Tree::Branch(_), None) if tree.is_empty() => {
+                *tree = Tree::Leaf(value);
+                true
+            }
+            (Tree::Branch(children), Some((name, rest))) => {
+                let index = match children.iter().position(|(key, _)| key == name) {
+                    Some(index) => index,
+                    None => {
+                        children.push((name.clone(), Tree::Branch(vec![])));
+                        children.len() - 1
+                    }
+                };
+                children[index].1.insert(rest, value)
+            }
+            _ => false,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        matches!(self, Tree::Branch(children) if children.is_empty())
+    }
+
+    fn into_value(self) -> Value {
+        match self {
+            Tree::Leaf(value) => value,
+            Tree::Branch(children) => {
+                let is_array = !children.is_empty()
+                    && children
+                        .iter()
+                        .enumerate()
+                        .all(|(index, (key, _))| *key == index.to_string());
+                let members = children
+                    .into_iter()
+                    .map(|(key, tree)| (key, tree.into_value()));
+                match is_array {
+                    true => Value::Array(members.map(|(_, value)| value).collect()),
+                    false => Value::Object(members.collect()),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flattened(json: &str, config: FlattenConfig) -> String {
+        flatten(&crate::parse(json).unwrap(), &config)
+            .unwrap()
+            .to_string()
+    }
+
+    fn unflattened(json: &str, config: FlattenConfig) -> Result<String, FormatterError> {
+        Ok(unflatten(&crate::parse(json).unwrap(), &config)?.to_string())
+    }
+
+    #[test]
+    fn flattens_to_leaves_in_document_order() {
+        assert_eq!(
+            flattened(r#"{"z": {"b": [1, 2]}, "a": true}"#, FlattenConfig::new()),
+            r#"{"z.b.0":1,"z.b.1":2,"a":true}"#
+        );
+        assert_eq!(
+            flattened(
+                r#"{"db": {"host": "x", "ports": []}, "tags": {}}"#,
+                FlattenConfig::new().with_separator("__")
+            ),
+            r#"{"db__host":"x","db__ports":[],"tags":{}}"#
+        );
+        assert_eq!(
+            flattened(
+                r#"{"a/b": {"c.d": [null]}}"#,
+                FlattenConfig::new().with_pointer_keys()
+            ),
+            r#"{"/a~1b/c.d/0":null}"#
+        );
+        assert_eq!(flattened("3", FlattenConfig::new()), r#"{"":3}"#);
+    }
+
+    #[test]
+    fn unflattens_objects_and_arrays() {
+        assert_eq!(
+            unflattened(
+                r#"{"z.b.0": 1, "z.b.1": 2, "a": true, "m.1": "x", "e": []}"#,
+                FlattenConfig::new()
+            )
+            .unwrap(),
+            r#"{"z":{"b":[1,2]},"a":true,"m":{"1":"x"},"e":[]}"#
+        );
+        assert_eq!(
+            unflattened(
+                r#"{"/a~1b/c.d/0": null}"#,
+                FlattenConfig::new().with_pointer_keys()
+            )
+            .unwrap(),
+            r#"{"a/b":{"c.d":[null]}}"#
+        );
+        // Flat keys can't tell an object keyed by indexes from an array.
+        let indexed = r#"{"0":"a","1":"b"}"#;
+        assert_eq!(
+            unflattened(
+                &flattened(indexed, FlattenConfig::new()),
+                FlattenConfig::new()
+            )
+            .unwrap(),
+            r#"["a","b"]"#
+        );
+        assert_eq!(
+            unflattened(r#"{"": 3}"#, FlattenConfig::new().with_pointer_keys()).unwrap(),
+            "3"
+        );
+    }
+
+    #[test]
+    fn round_trips() {
+        let json = r#"{"a":{"b":[1,{"c":[]}],"d":{}},"e":"f"}"#;
+        for config in [
+            FlattenConfig::new(),
+            FlattenConfig::new().with_separator("_"),
+            FlattenConfig::new().with_pointer_keys(),
+        ] {
+            let flat = flatten(&crate::parse(json).unwrap(), &config).unwrap();
+            assert_eq!(unflatten(&flat, &config).unwrap().to_string(), json);
+        }
+    }
+
+    #[test]
+    fn reports_conflicting_keys() {
+        assert_eq!(
+            unflattened(r#"{"a": 1, "a.b": 2}"#, FlattenConfig::new()).unwrap_err(),
+            FormatterError::ConflictingKeys(String::from("a.b"))
+        );
+        assert_eq!(
+            unflattened(r#"{"a.b": 1, "a": 2}"#, FlattenConfig::new()).unwrap_err(),
+            FormatterError::ConflictingKeys(String::from("a"))
+        );
+        let nested = crate::parse(r#"{"a.b": 1, "a": {"b": 2}}"#).unwrap();
+        assert_eq!(
+            flatten(&nested, &FlattenConfig::new()).unwrap_err(),
+            FormatterError::ConflictingKeys(String::from("a.b"))
+        );
+        assert!(flatten(&nested, &FlattenConfig::new().with_pointer_keys()).is_ok());
+        assert_eq!(
+            unflattened(r#"{"a": 1}"#, FlattenConfig::new().with_pointer_keys()).unwrap_err(),
+            FormatterError::InvalidPointer(String::from("a"))
+        );
+    }
+}
@@ -0,0 +1,122 @@
+use std::env;
+use std::io::{self, IsTerminal};
+
+/// Whether to colour output with ANSI escapes.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum ColorChoice {
+    /// Colour only when stdout is a terminal and `NO_COLOR` isn't set.
+    Auto,
+    Always,
+    #[default]
+    Never,
+}
+
+impl ColorChoice {
+    pub fn enabled(self) -> bool {
+        match self {
+            ColorChoice::Auto => {
+                io::stdout().is_terminal()
+                    && env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+            }
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+        }
+    }
+}
+
+/// The SGR parameters (the part between `ESC[` and `m`, e.g. `"1;34"`) used for each kind of
+/// token. An empty string leaves that kind uncoloured.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Theme {
+    pub key: String,
+    pub string: String,
+    pub number: String,
+    /// `true`, `false` and `null`.
+    pub literal: String,
+    /// Brackets, braces, commas and colons.
+    pub punctuation: String,
+}
+
+impl Theme {
+    /// Bright colours, for dark terminal backgrounds.
+    pub fn dark() -> Theme {
+        Theme {
+            key: String::from("1;94"),
+            string: String::from("92"),
+            number: String::from("96"),
+            literal: String::from("95"),
+            punctuation: String::from("90"),
+        }
+    }
+
+    /// The standard colours, which stay readable on light terminal backgrounds.
+    pub fn light() -> Theme {
+        Theme {
+            key: String::from("1;34"),
+            string: String::from("32"),
+            number: String::from("36"),
+            literal: String::from("35"),
+            punctuation: String::new(),
+        }
+    }
+
+    pub(crate) fn paint(&self, text: &str, role: Role) -> String {
+        let style = match role {
+            Role::Key => &self.key,
+            Role::String => &self.string,
+            Role::Number => &self.number,
+            Role::Literal => &self.literal,
+            Role::Punctuation => &self.punctuation,
+        };
+        match style.is_empty() {
+            true => text.to_owned(),
+            false => format!("\x1b[{}m{}\x1b[0m", style, text),
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Theme {
+        Theme::dark()
+    }
+}
+
+#[derive(Clone, Copy)]
+pub(crate) enum Role {
+    Key,
+    String,
+    Number,
+    Literal,
+    Punctuation,
+}
+
+impl Role {
+    /// The class HTML output gives tokens of this kind.
+    pub(crate) fn class(self) -> &'static str {
+        match self {
+            Role::Key => "key",
+            Role::String => "string",
+            Role::Number => "number",
+            Role::Literal => "literal",
+            Role::Punctuation => "punctuation",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_styles_leave_text_alone() {
+        let theme = Theme::light();
+        assert_eq!(theme.paint("1", Role::Number), "\x1b[36m1\x1b[0m");
+        assert_eq!(theme.paint(",", Role::Punctuation), ",");
+    }
+
+    #[test]
+    fn explicit_choices_ignore_the_terminal() {
+        assert!(ColorChoice::Always.enabled());
+        assert!(!ColorChoice::Never.enabled());
+    }
+}
@@ -1,12 +1,13 @@
 use std::fmt;
 use FormatterError::*;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub enum FormatterError {
     // Tokeniser
     ExpectedMoreCharacters(usize),
     InvalidTokenStartCharacter(usize, char),
     WrongCharacter {
+        position: usize,
         attempted_token_literal: &'static str,
         expected_character: char,
         wrong_character: char,
@@ -16,14 +17,45 @@ pub enum FormatterError {
     InvalidNumberCharacter(usize, char),
     ExtraDotInNumber(usize),
     ExtraEInNumber(usize),
-    NumberLiteralEndingInE(),
-    NumberCanNotHaveANegativeSignNotAtHead(),
+    NumberLiteralEndingInE(usize),
+    NumberCanNotHaveANegativeSignNotAtHead(usize),
+    InvalidNumber(usize),
 
     // Parser
     ExpectedMoreTokens(),
-    ExpectedColonInKeyValuePair(),
+    ExpectedColonInKeyValuePair(usize),
     ExpectedStringLiteral(usize),
     DuplicateKeyEntry(String),
+    UnexpectedToken(usize),
+
+    // JSON Pointer
+    InvalidPointer(String),
+    PointerNotFound(String),
+
+    // Query
+    InvalidQuery(usize),
+
+    // JSON Patch
+    InvalidPatchOperation(usize),
+    PatchTestFailed(String),
+
+    // JSON Schema
+    InvalidSchema(String),
+
+    // Filter
+    InvalidFilter(usize),
+    FilterFailed(String),
+
+    // Conversion
+    NotRepresentable {
+        format: &'static str,
+        pointer: String,
+        reason: &'static str,
+    },
+    InvalidCsv(usize),
+
+    // Flatten
+    ConflictingKeys(String),
 }
 
 impl fmt::Display for FormatterError {
@@ -38,13 +70,14 @@ impl fmt::Display for FormatterError {
                 character, position
             ),
             WrongCharacter {
+                position,
                 attempted_token_literal,
                 expected_character,
                 wrong_character,
             } => write!(
                 f,
-                "Wrong Character: found ({}) when expecting ({}) while trying to build token {}.",
-                wrong_character, expected_character, attempted_token_literal
+                "Wrong Character: found ({}) when expecting ({}) while trying to build token {} at position ({}).",
+                wrong_character, expected_character, attempted_token_literal, position
             ),
 
             // Number
@@ -63,23 +96,71 @@ impl fmt::Display for FormatterError {
                 "Found and extra e at postition ({}) which is not valid in a number.",
                 position
             ),
-            NumberLiteralEndingInE() => {
-                write!(f, "A number literal can not end with an 'e' character.")
-            },
-            NumberCanNotHaveANegativeSignNotAtHead() => write!(
+            NumberLiteralEndingInE(position) => write!(
                 f,
-                "Number can not have a - at a position other than the start of string"
+                "A number literal can not end with an 'e' character, found at position ({}).",
+                position
+            ),
+            NumberCanNotHaveANegativeSignNotAtHead(position) => write!(
+                f,
+                "Number can not have a - at a position other than the start of string, found at position ({})",
+                position
             ),
+            InvalidNumber(position) => {
+                write!(f, "The number at position ({}) is not valid.", position)
+            }
 
             // Parser
             ExpectedMoreTokens() => write!(f, "Ran out of tokens while parsing."),
-            ExpectedColonInKeyValuePair() => {
-                write!(f, "Key value pairs must be delimited by colons (:).")
-            }
+            ExpectedColonInKeyValuePair(position) => write!(
+                f,
+                "Key value pairs must be delimited by colons (:), expected one at position ({}).",
+                position
+            ),
             ExpectedStringLiteral(position) => {
                 write!(f, "Expected string literal at position ({}).", position)
             }
             DuplicateKeyEntry(literal) => write!(f, "Duplicate key ('{}') entry.", literal),
+            UnexpectedToken(position) => write!(f, "Unexpected token at position ({}).", position),
+
+            // JSON Pointer
+            InvalidPointer(pointer) => write!(f, "Invalid JSON pointer ('{}').", pointer),
+            PointerNotFound(pointer) => write!(f, "Nothing found at JSON pointer ('{}').", pointer),
+
+            // Query
+            InvalidQuery(position) => write!(f, "Invalid query at position ({}).", position),
+
+            // JSON Patch
+            InvalidPatchOperation(index) => {
+                write!(f, "Invalid JSON Patch operation at index ({}).", index)
+            }
+            PatchTestFailed(pointer) => write!(f, "JSON Patch test failed at ('{}').", pointer),
+
+            // JSON Schema
+            InvalidSchema(location) => write!(f, "Invalid JSON Schema at ('{}').", location),
+
+            // Filter
+            InvalidFilter(position) => write!(f, "Invalid filter at position ({}).", position),
+            FilterFailed(message) => write!(f, "Filter failed: {}.", message),
+
+            // Conversion
+            NotRepresentable {
+                format,
+                pointer,
+                reason,
+            } => write!(
+                f,
+                "The value at ('{}') can not be written as {}: {}.",
+                pointer, format, reason
+            ),
+            InvalidCsv(line) => write!(f, "Invalid CSV at line ({}).", line),
+
+            // Flatten
+            ConflictingKeys(key) => write!(
+                f,
+                "The key ('{}') conflicts with another flattened key.",
+                key
+            ),
         }
     }
 }
@@ -1,7 +1,20 @@
-use super::parser::Node;
+use std::ops::Range;
+
+use super::parser::DuplicateKeyPolicy;
 use super::parser::AST;
+use super::parser::{Item, Node};
+use crate::schema::{Schema, SchemaCursor};
+use crate::value::{escape, unescape};
 
+mod color;
 pub mod errors;
+mod preview;
+mod redact;
+use color::Role;
+pub use color::{ColorChoice, Theme};
+pub(crate) use preview::thousands;
+pub use preview::Preview;
+pub use redact::Redaction;
 
 pub enum DelimiterCount {
     Four,
@@ -11,27 +24,204 @@ pub enum Delimiter {
     Spaces(DelimiterCount),
     Tabs,
 }
+/// How lines end in the output.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum LineEnding {
+    Lf,
+    Crlf,
+    /// Whatever the input's first line ends with, LF for single-line input or when there is
+    /// no input, as in `Value::format`.
+    #[default]
+    Auto,
+}
+
+/// What is printed around each token, on top of the layout.
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum Markup {
+    Plain,
+    Ansi,
+    /// `<span>`s with a class per kind of token, containers optionally wrapped in `<details>`.
+    Html {
+        collapsible: bool,
+    },
+}
+
 pub struct FormatConfig {
     delimiter: Delimiter,
+    pub(crate) duplicate_keys: DuplicateKeyPolicy,
+    pub(crate) keep_blank_lines: bool,
+    schema: Option<Schema>,
+    schema_key: Option<String>,
+    pub(crate) markup: Markup,
+    theme: Theme,
+    redaction: Option<Redaction>,
+    pub(crate) preview: Option<Preview>,
+    line_ending: LineEnding,
 }
 
 impl FormatConfig {
     pub fn new(delimiter: Delimiter) -> FormatConfig {
-        FormatConfig { delimiter }
+        FormatConfig {
+            delimiter,
+            duplicate_keys: DuplicateKeyPolicy::default(),
+            keep_blank_lines: false,
+            schema: None,
+            schema_key: None,
+            markup: Markup::Plain,
+            theme: Theme::default(),
+            redaction: None,
+            preview: None,
+            line_ending: LineEnding::default(),
+        }
+    }
+
+    pub fn with_duplicate_keys(mut self, duplicate_keys: DuplicateKeyPolicy) -> FormatConfig {
+        self.duplicate_keys = duplicate_keys;
+        self
+    }
+
+    /// Keep a blank line wherever the input had one (or more) between two items.
+    pub fn with_blank_lines(mut self, keep_blank_lines: bool) -> FormatConfig {
+        self.keep_blank_lines = keep_blank_lines;
+        self
+    }
+
+    /// Print object members in the order `schema` declares them in `properties`. Members the
+    /// schema doesn't declare follow, in source order.
+    pub fn with_schema(mut self, schema: Schema) -> FormatConfig {
+        self.schema = Some(schema);
+        self
+    }
+
+    /// Start the top-level object with a `"$schema"` member pointing at `uri`, replacing any
+    /// `"$schema"` member already there.
+    pub fn with_schema_key(mut self, uri: &str) -> FormatConfig {
+        self.schema_key = Some(uri.to_owned());
+        self
+    }
+
+    /// Colour keys, strings, numbers, `true`/`false`/`null` and punctuation with ANSI escapes.
+    /// `ColorChoice::Auto` is settled here, when the config is built.
+    pub fn with_color(mut self, color: ColorChoice) -> FormatConfig {
+        self.markup = match color.enabled() {
+            true => Markup::Ansi,
+            false => Markup::Plain,
+        };
+        self
+    }
+
+    /// The colours `with_color` uses, `Theme::dark()` by default.
+    pub fn with_theme(mut self, theme: Theme) -> FormatConfig {
+        self.theme = theme;
+        self
+    }
+
+    /// Replace the values `redaction` selects before anything is printed.
+    pub fn with_redaction(mut self, redaction: Redaction) -> FormatConfig {
+        self.redaction = Some(redaction);
+        self
+    }
+
+    /// Only print a taste of the document: see `Preview`.
+    pub fn with_preview(mut self, preview: Preview) -> FormatConfig {
+        self.preview = Some(preview);
+        self
+    }
+
+    pub fn with_line_ending(mut self, line_ending: LineEnding) -> FormatConfig {
+        self.line_ending = line_ending;
+        self
+    }
+
+    /// Settles `LineEnding::Auto` from the text about to be formatted.
+    pub(crate) fn detect_line_ending(&mut self, input: &str) {
+        if self.line_ending == LineEnding::Auto {
+            self.line_ending = match input.find('\n') {
+                Some(index) if input[..index].ends_with('\r') => LineEnding::Crlf,
+                _ => LineEnding::Lf,
+            };
+        }
+    }
+
+    /// The line break the printer puts between lines.
+    pub(crate) fn newline(&self) -> &'static str {
+        match self.line_ending {
+            LineEnding::Crlf => "\r\n",
+            LineEnding::Lf | LineEnding::Auto => "\n",
+        }
+    }
+
+    fn paint(&self, text: &str, role: Role) -> String {
+        match self.markup {
+            Markup::Plain => text.to_owned(),
+            Markup::Ansi => self.theme.paint(text, role),
+            Markup::Html { .. } => format!(
+                "<span class=\"{}\">{}</span>",
+                role.class(),
+                escape_html(text)
+            ),
+        }
     }
 }
 
-pub(crate) fn stringify(ast: AST) -> String {
-    let AST { root } = ast;
-    let config = FormatConfig {
-        delimiter: Delimiter::Spaces(DelimiterCount::Four),
-    };
-    print_node(root, 0, &config)
+impl Default for FormatConfig {
+    fn default() -> FormatConfig {
+        FormatConfig::new(Delimiter::Spaces(DelimiterCount::Four))
+    }
 }
 
 pub(crate) fn stringify_with_config(ast: AST, config: &FormatConfig) -> String {
-    let AST { root } = ast;
-    print_node(root, 0, config)
+    let (printed, _) = stringify_with_spans(ast, config);
+    printed
+}
+
+/// Where the printer put each member or item it printed, as its byte range in the output and
+/// the span the parser recorded for it in the input.
+pub(crate) type Spans = Vec<(Range<usize>, Range<usize>)>;
+
+/// Like `stringify_with_config`, also returning the `Spans` of everything printed that came
+/// from the input.
+pub(crate) fn stringify_with_spans(ast: AST, config: &FormatConfig) -> (String, Spans) {
+    let AST { mut root } = ast;
+    if let Some(redaction) = &config.redaction {
+        redaction.apply(&mut root);
+    }
+    if let (Some(uri), Node::Object { pairs }) = (&config.schema_key, &mut root) {
+        pairs.retain(|pair| key_of(&pair.node).as_deref() != Some("$schema"));
+        pairs.insert(
+            0,
+            Item::from(Node::Pair {
+                key: Box::new(Node::Literal {
+                    literal: String::from("$schema"),
+                }),
+                value: Box::new(Node::Literal {
+                    literal: escape(uri),
+                }),
+            }),
+        );
+    }
+    let schema = config.schema.as_ref().map(SchemaCursor::new);
+    let mut spans = vec![];
+    let printed = print_node(root, 0, config, schema, &mut spans);
+    (printed, spans)
+}
+
+/// Prints a node as if it sat `depth` containers deep in a larger document.
+pub(crate) fn stringify_at_depth(node: Node, depth: usize, config: &FormatConfig) -> String {
+    print_node(node, depth, config, None, &mut vec![])
+}
+
+/// Prints the members or items of a container `depth` containers deep, as they would appear
+/// between its brackets, blank lines included.
+pub(crate) fn stringify_items(items: Vec<Item>, depth: usize, config: &FormatConfig) -> String {
+    let (indent, _) = derive(depth, config);
+    print_items(items, depth, &indent, config, None, None, &mut vec![])
+}
+
+/// The indentation of an item inside a container at `depth`.
+pub(crate) fn indentation(depth: usize, config: &FormatConfig) -> String {
+    let (_, dedent) = derive(depth, config);
+    dedent
 }
 
 fn derive(depth: usize, config: &FormatConfig) -> (String, String) {
@@ -47,49 +237,248 @@ fn derive(depth: usize, config: &FormatConfig) -> (String, String) {
     }
 }
 
-fn print_node(node: Node, depth: usize, config: &FormatConfig) -> String {
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for character in text.chars() {
+        match character {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            character => escaped.push(character),
+        }
+    }
+    escaped
+}
+
+/// The unescaped key of a `Node::Pair`.
+fn key_of(node: &Node) -> Option<String> {
     match node {
-        Node::Object { pairs } => {
-            let (indent, dedent) = derive(depth, config);
-            let end = format!("{}{}{}", dedent, "}", if depth == 0 { "\n" } else { "" });
-            let joiner = format!("{}{}", ",\n", indent);
+        Node::Pair { key, .. } => match key.as_ref() {
+            Node::Literal { literal } => Some(unescape(literal)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
 
-            format!(
-                "{}\n{}{}\n{}",
-                "{",
-                indent,
-                pairs
-                    .into_iter()
-                    .map(|ref item| print_node((**item).clone(), depth + 1, config))
-                    .collect::<Vec::<String>>()
-                    .join(&joiner),
-                &end
-            )
+/// Sorts object members into the schema's order, with an undeclared `$schema` member first as
+/// it says how to read the rest. Blank lines only survive if nothing moved.
+fn order_pairs(pairs: Vec<Item>, schema: SchemaCursor) -> Vec<Item> {
+    let order = schema.property_order();
+    let rank = |pair: &Node| match key_of(pair) {
+        Some(key) => match order.iter().position(|name| *name == key) {
+            Some(position) => position + 1,
+            None if key == "$schema" => 0,
+            None => order.len() + 1,
+        },
+        None => order.len() + 1,
+    };
+
+    let ranks = pairs
+        .iter()
+        .map(|pair| rank(&pair.node))
+        .collect::<Vec<usize>>();
+    if ranks.windows(2).all(|ranks| ranks[0] <= ranks[1]) {
+        return pairs;
+    }
+
+    let mut sorted = pairs
+        .into_iter()
+        .map(|pair| Item {
+            blank_line_before: false,
+            ..pair
+        })
+        .collect::<Vec<Item>>();
+    sorted.sort_by_key(|pair| rank(&pair.node));
+    sorted
+}
+
+fn print_items(
+    items: Vec<Item>,
+    depth: usize,
+    indent: &str,
+    config: &FormatConfig,
+    schema: Option<SchemaCursor>,
+    shown: Option<usize>,
+    spans: &mut Spans,
+) -> String {
+    let mut printed = String::new();
+    let count = items.len();
+
+    for (index, item) in items.into_iter().enumerate() {
+        if shown == Some(index) {
+            break;
         }
-        Node::Array { items } => {
-            let (indent, dedent) = derive(depth, config);
-            let joiner = format!("{}{}", ",\n", indent);
-            format!(
-                "[\n{}{}\n{}]",
-                indent,
-                items
-                    .into_iter()
-                    .map(|ref item| print_node((**item).clone(), depth + 1, config))
-                    .collect::<Vec::<String>>()
-                    .join(&joiner),
-                dedent
-            )
+        if index > 0 {
+            printed.push_str(&config.paint(",", Role::Punctuation));
+            printed.push_str(config.newline());
+            if item.blank_line_before {
+                printed.push_str(config.newline());
+            }
+            printed.push_str(indent);
         }
-        Node::Pair { key, value } => format!(
-            "{}: {}",
-            print_node(*key, depth, config),
-            print_node(*value, depth, config)
+        let item_schema = match item.node {
+            Node::Pair { .. } => schema,
+            _ => schema.and_then(|schema| schema.item(index)),
+        };
+        let (start, recorded) = (printed.len(), spans.len());
+        printed.push_str(&print_node(
+            item.node,
+            depth + 1,
+            config,
+            item_schema,
+            spans,
+        ));
+        shift(&mut spans[recorded..], start);
+        if let Some(span) = item.span {
+            spans.push((start..printed.len(), span));
+        }
+    }
+
+    if let (Some(preview), Some(shown)) = (&config.preview, shown) {
+        if !preview.comments && shown > 0 {
+            printed.push_str(&config.paint(",", Role::Punctuation));
+        }
+        if shown > 0 {
+            printed.push_str(config.newline());
+            printed.push_str(indent);
+        }
+        printed.push_str(&config.paint(&preview.more_items(count - shown), Role::Punctuation));
+    }
+    printed
+}
+
+/// Moves spans recorded in a piece of output to where the piece starts in the whole.
+fn shift(spans: &mut [(Range<usize>, Range<usize>)], by: usize) {
+    for (output, _) in spans {
+        *output = output.start + by..output.end + by;
+    }
+}
+
+/// Prints an object or array after `label`, the already printed `"key": ` of its member (if
+/// any). Collapsible HTML puts both in the `<summary>` of a `<details>` element.
+fn print_container(
+    node: Node,
+    depth: usize,
+    config: &FormatConfig,
+    schema: Option<SchemaCursor>,
+    label: String,
+    spans: &mut Spans,
+) -> String {
+    let (kind, open, close, items) = match node {
+        Node::Object { pairs } => (
+            "object",
+            "{",
+            "}",
+            match schema {
+                Some(schema) => order_pairs(pairs, schema),
+                None => pairs,
+            },
+        ),
+        Node::Array { items } => ("array", "[", "]", items),
+        _ => unreachable!("only objects and arrays are containers"),
+    };
+    // Only a top-level object ends in a newline.
+    let end = if depth == 0 && kind == "object" {
+        config.newline()
+    } else {
+        ""
+    };
+    let count = items.len();
+    let shown = match (&config.preview, kind) {
+        (Some(preview), _) if preview.collapses(depth) => {
+            let collapsed = preview.collapsed(kind == "object", count);
+            return format!(
+                "{}{}{}",
+                label,
+                config.paint(&collapsed, Role::Punctuation),
+                end
+            );
+        }
+        (Some(preview), "array") => preview.items_shown(count),
+        _ => None,
+    };
+    let (indent, dedent) = derive(depth, config);
+    let head = format!("{}{}", label, config.paint(open, Role::Punctuation));
+    let (head, tail) = match config.markup {
+        Markup::Html { collapsible: true } => (
+            format!(
+                "<details open class=\"{}\"><summary>{}</summary>",
+                kind, head
+            ),
+            "</details>",
         ),
-        Node::Literal { literal } => format!("\"{}\"", literal),
-        Node::Number { value } => format!("{}", value),
-        Node::True => String::from("true"),
-        Node::False => String::from("false"),
-        Node::Null => String::from("null"),
+        _ => (head, ""),
+    };
+    let recorded = spans.len();
+    let printed = print_items(items, depth, &indent, config, schema, shown, spans);
+    shift(
+        &mut spans[recorded..],
+        head.len() + config.newline().len() + indent.len(),
+    );
+
+    format!(
+        "{}{}{}{}{}{}{}{}{}",
+        head,
+        config.newline(),
+        indent,
+        printed,
+        config.newline(),
+        dedent,
+        config.paint(close, Role::Punctuation),
+        tail,
+        end
+    )
+}
+
+fn print_node(
+    node: Node,
+    depth: usize,
+    config: &FormatConfig,
+    schema: Option<SchemaCursor>,
+    spans: &mut Spans,
+) -> String {
+    match node {
+        Node::Object { .. } | Node::Array { .. } => {
+            print_container(node, depth, config, schema, String::new(), spans)
+        }
+        Node::Pair { key, value } => {
+            let value_schema = match (schema, key.as_ref()) {
+                (Some(schema), Node::Literal { literal }) => schema.property(&unescape(literal)),
+                _ => None,
+            };
+            let key = match *key {
+                Node::Literal { literal } => config.paint(&format!("\"{}\"", literal), Role::Key),
+                key => print_node(key, depth, config, None, spans),
+            };
+            let label = format!("{}{} ", key, config.paint(":", Role::Punctuation));
+            match *value {
+                container @ Node::Object { .. } | container @ Node::Array { .. } => {
+                    print_container(container, depth, config, value_schema, label, spans)
+                }
+                value => label + &print_node(value, depth, config, value_schema, spans),
+            }
+        }
+        Node::Literal { literal } => {
+            let truncated = config.preview.as_ref().and_then(|preview| {
+                let text = unescape(&literal);
+                let (kept, hidden) = preview.truncate(&text)?;
+                Some((escape(kept), preview.more_characters(hidden)))
+            });
+            match truncated {
+                Some((kept, marker)) => {
+                    config.paint(&format!("\"{}\"", kept), Role::String)
+                        + &config.paint(&marker, Role::Punctuation)
+                }
+                None => config.paint(&format!("\"{}\"", literal), Role::String),
+            }
+        }
+        Node::Number { value } => config.paint(&value.to_string(), Role::Number),
+        Node::True => config.paint("true", Role::Literal),
+        Node::False => config.paint("false", Role::Literal),
+        Node::Null => config.paint("null", Role::Literal),
     }
 }
 
@@ -101,44 +490,49 @@ mod tests {
     fn print_node_true() {
         let node = Node::True;
         let expected_string = "true";
-        let config = FormatConfig {
-            delimiter: Delimiter::Spaces(DelimiterCount::Four),
-        };
+        let config = FormatConfig::new(Delimiter::Spaces(DelimiterCount::Four));
 
-        assert_eq!(print_node(node, 0, &config), expected_string)
+        assert_eq!(
+            print_node(node, 0, &config, None, &mut vec![]),
+            expected_string
+        )
     }
 
     #[test]
     fn print_node_false() {
         let node = Node::False;
         let expected_string = "false";
-        let config = FormatConfig {
-            delimiter: Delimiter::Spaces(DelimiterCount::Four),
-        };
+        let config = FormatConfig::new(Delimiter::Spaces(DelimiterCount::Four));
 
-        assert_eq!(print_node(node, 0, &config), expected_string)
+        assert_eq!(
+            print_node(node, 0, &config, None, &mut vec![]),
+            expected_string
+        )
     }
 
     #[test]
     fn print_node_null() {
         let node = Node::Null;
         let expected_string = "null";
-        let config = FormatConfig {
-            delimiter: Delimiter::Spaces(DelimiterCount::Four),
-        };
+        let config = FormatConfig::new(Delimiter::Spaces(DelimiterCount::Four));
 
-        assert_eq!(print_node(node, 0, &config), expected_string)
+        assert_eq!(
+            print_node(node, 0, &config, None, &mut vec![]),
+            expected_string
+        )
     }
 
     #[test]
+    #[allow(clippy::approx_constant)]
     fn print_node_number() {
         let node = Node::Number { value: 3.141592 };
         let expected_string = "3.141592";
-        let config = FormatConfig {
-            delimiter: Delimiter::Spaces(DelimiterCount::Four),
-        };
+        let config = FormatConfig::new(Delimiter::Spaces(DelimiterCount::Four));
 
-        assert_eq!(print_node(node, 0, &config), expected_string)
+        assert_eq!(
+            print_node(node, 0, &config, None, &mut vec![]),
+            expected_string
+        )
     }
 
     #[test]
@@ -147,11 +541,12 @@ mod tests {
             literal: "key".to_owned(),
         };
         let expected_string = r#""key""#;
-        let config = FormatConfig {
-            delimiter: Delimiter::Spaces(DelimiterCount::Four),
-        };
+        let config = FormatConfig::new(Delimiter::Spaces(DelimiterCount::Four));
 
-        assert_eq!(print_node(node, 0, &config), expected_string)
+        assert_eq!(
+            print_node(node, 0, &config, None, &mut vec![]),
+            expected_string
+        )
     }
 
     #[test]
@@ -160,9 +555,7 @@ mod tests {
             literal: "key".to_owned(),
         };
         let r#true = Node::True;
-        let config = FormatConfig {
-            delimiter: Delimiter::Spaces(DelimiterCount::Four),
-        };
+        let config = FormatConfig::new(Delimiter::Spaces(DelimiterCount::Four));
 
         let pair = Node::Pair {
             key: Box::new(key),
@@ -171,19 +564,20 @@ mod tests {
 
         let expected_string = "\"key\": true";
 
-        assert_eq!(print_node(pair, 0, &config), expected_string)
+        assert_eq!(
+            print_node(pair, 0, &config, None, &mut vec![]),
+            expected_string
+        )
     }
 
     #[test]
     fn print_node_array() {
         let r#true = Node::True;
         let r#true2 = Node::True;
-        let config = FormatConfig {
-            delimiter: Delimiter::Spaces(DelimiterCount::Four),
-        };
+        let config = FormatConfig::new(Delimiter::Spaces(DelimiterCount::Four));
 
         let array = Node::Array {
-            items: vec![Box::new(r#true), Box::new(r#true2)],
+            items: vec![Item::from(r#true), Item::from(r#true2)],
         };
 
         let expected_string = "[
@@ -191,7 +585,10 @@ mod tests {
     true
 ]";
 
-        assert_eq!(print_node(array, 0, &config), expected_string)
+        assert_eq!(
+            print_node(array, 0, &config, None, &mut vec![]),
+            expected_string
+        )
     }
 
     #[test]
@@ -200,9 +597,7 @@ mod tests {
             literal: "key".to_owned(),
         };
         let r#true = Node::True;
-        let config = FormatConfig {
-            delimiter: Delimiter::Spaces(DelimiterCount::Four),
-        };
+        let config = FormatConfig::new(Delimiter::Spaces(DelimiterCount::Four));
 
         let pair = Node::Pair {
             key: Box::new(key),
@@ -210,7 +605,7 @@ mod tests {
         };
 
         let object = Node::Object {
-            pairs: vec![Box::new(pair)],
+            pairs: vec![Item::from(pair)],
         };
 
         let expected_string = r#"{
@@ -218,6 +613,170 @@ mod tests {
 }
 "#;
 
-        assert_eq!(print_node(object, 0, &config), expected_string)
+        assert_eq!(
+            print_node(object, 0, &config, None, &mut vec![]),
+            expected_string
+        )
+    }
+
+    #[test]
+    fn print_node_array_with_a_blank_line() {
+        let config = FormatConfig::new(Delimiter::Spaces(DelimiterCount::Two));
+
+        let array = Node::Array {
+            items: vec![
+                Item::from(Node::True),
+                Item {
+                    node: Node::False,
+                    blank_line_before: true,
+                    span: None,
+                },
+            ],
+        };
+
+        let expected_string = "[
+  true,
+
+  false
+]";
+
+        assert_eq!(
+            print_node(array, 0, &config, None, &mut vec![]),
+            expected_string
+        )
+    }
+
+    fn schema_config() -> FormatConfig {
+        let schema = Schema::parse(
+            r#"{
+                "properties": {
+                    "name": {},
+                    "version": {},
+                    "dependencies": {"items": {"properties": {"id": {}, "range": {}}}}
+                }
+            }"#,
+        )
+        .unwrap();
+        FormatConfig::new(Delimiter::Spaces(DelimiterCount::Two)).with_schema(schema)
+    }
+
+    #[test]
+    fn schema_orders_object_members() {
+        let input = r#"{"extra": 1, "dependencies": [{"range": "^1", "id": "a"}], "version": "1.0", "name": "x"}"#;
+        let expected = r#"{
+  "name": "x",
+  "version": "1.0",
+  "dependencies": [
+    {
+      "id": "a",
+      "range": "^1"
+    }
+  ],
+  "extra": 1
+}
+"#;
+
+        assert_eq!(
+            crate::format(input.to_owned(), Some(schema_config())).unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn schema_order_keeps_blank_lines_when_nothing_moves() {
+        let input = "{\"name\": \"x\",\n\n\"version\": \"1.0\"}";
+        let config = schema_config().with_blank_lines(true);
+
+        assert_eq!(
+            crate::format(input.to_owned(), Some(config)).unwrap(),
+            "{\n  \"name\": \"x\",\n\n  \"version\": \"1.0\"\n}\n"
+        );
+    }
+
+    #[test]
+    fn color_paints_each_kind_of_token() {
+        let config = FormatConfig::new(Delimiter::Spaces(DelimiterCount::Two))
+            .with_color(ColorChoice::Always)
+            .with_theme(Theme::light());
+        let input = r#"{"a": [1, "b", null]}"#;
+
+        assert_eq!(
+            crate::format(input.to_owned(), Some(config)).unwrap(),
+            "{\n  \x1b[1;34m\"a\"\x1b[0m: [\n    \x1b[36m1\x1b[0m,\n    \x1b[32m\"b\"\x1b[0m,\n    \x1b[35mnull\x1b[0m\n  ]\n}\n"
+        );
+    }
+
+    #[test]
+    fn color_is_off_unless_asked_for() {
+        let config = FormatConfig::default().with_color(ColorChoice::Never);
+        assert_eq!(
+            crate::format(String::from("[true]"), Some(config)).unwrap(),
+            "[\n    true\n]"
+        );
+    }
+
+    #[test]
+    fn schema_key_is_added_first() {
+        let input = r#"{"name": "x", "$schema": "old.json"}"#;
+        let config = FormatConfig::new(Delimiter::Spaces(DelimiterCount::Two))
+            .with_schema_key("https://example.com/manifest.json");
+
+        assert_eq!(
+            crate::format(input.to_owned(), Some(config)).unwrap(),
+            "{\n  \"$schema\": \"https://example.com/manifest.json\",\n  \"name\": \"x\"\n}\n"
+        );
+    }
+
+    #[test]
+    fn schema_key_stays_first_when_ordering_by_schema() {
+        let input = r#"{"version": "1.0", "name": "x"}"#;
+        let schema =
+            crate::schema::Schema::parse(r#"{"properties": {"name": {}, "version": {}}}"#).unwrap();
+        let config = FormatConfig::new(Delimiter::Spaces(DelimiterCount::Two))
+            .with_schema(schema)
+            .with_schema_key("https://example.com/manifest.json");
+
+        assert_eq!(
+            crate::format(input.to_owned(), Some(config)).unwrap(),
+            "{\n  \"$schema\": \"https://example.com/manifest.json\",\n  \"name\": \"x\",\n  \"version\": \"1.0\"\n}\n"
+        );
+    }
+
+    #[test]
+    fn line_endings_are_configurable() {
+        let lf = FormatConfig::default().with_line_ending(LineEnding::Lf);
+        let crlf = FormatConfig::default().with_line_ending(LineEnding::Crlf);
+
+        assert_eq!(
+            crate::format(String::from("{\r\n\"a\": [1]\r\n}"), Some(lf)).unwrap(),
+            "{\n    \"a\": [\n        1\n    ]\n}\n"
+        );
+        assert_eq!(
+            crate::format(String::from("{\"a\": [1, 2]}"), Some(crlf)).unwrap(),
+            "{\r\n    \"a\": [\r\n        1,\r\n        2\r\n    ]\r\n}\r\n"
+        );
+    }
+
+    #[test]
+    fn auto_line_ending_follows_the_first_line_break() {
+        let format = |input: &str| crate::format(input.to_owned(), None).unwrap();
+
+        assert_eq!(format("[\r\n1,\r\n2\r\n]\r\n"), "[\r\n    1,\r\n    2\r\n]");
+        assert_eq!(format("[\n1,\n2\n]\n"), "[\n    1,\n    2\n]");
+        assert_eq!(
+            format("[1,\r\n2,\n3]"),
+            "[\r\n    1,\r\n    2,\r\n    3\r\n]"
+        );
+        assert_eq!(format("[1,\n2]\r\n"), "[\n    1,\n    2\n]");
+        assert_eq!(format("[1, 2]"), "[\n    1,\n    2\n]");
+    }
+
+    #[test]
+    fn crlf_keeps_blank_lines_and_leaves_strings_alone() {
+        let config = FormatConfig::default().with_blank_lines(true);
+        assert_eq!(
+            crate::format(String::from("[1,\r\n\r\n\"a\nb\"]"), Some(config)).unwrap(),
+            "[\r\n    1,\r\n\r\n    \"a\nb\"\r\n]"
+        );
     }
 }
@@ -0,0 +1,190 @@
+/// Limits for printing a taste of a large document. What is left out is summarised with
+/// markers like `… 9,995 more items` and `{…12 keys}`, which are deliberately not JSON, or with
+/// JSONC comments when `with_comments` is set.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Preview {
+    items: Option<usize>,
+    string_length: Option<usize>,
+    depth: Option<usize>,
+    pub(crate) comments: bool,
+}
+
+impl Preview {
+    /// Ten items per array and 80 characters per string, at any depth.
+    pub fn new() -> Preview {
+        Preview {
+            items: Some(10),
+            string_length: Some(80),
+            depth: None,
+            comments: false,
+        }
+    }
+
+    /// Show at most `items` items of each array.
+    pub fn with_items(mut self, items: usize) -> Preview {
+        self.items = Some(items);
+        self
+    }
+
+    /// Cut strings after `string_length` characters.
+    pub fn with_string_length(mut self, string_length: usize) -> Preview {
+        self.string_length = Some(string_length);
+        self
+    }
+
+    /// Collapse objects and arrays nested `depth` or more containers deep, the top level being 0.
+    pub fn with_depth(mut self, depth: usize) -> Preview {
+        self.depth = Some(depth);
+        self
+    }
+
+    /// Write the markers as comments, so the preview is still valid JSONC.
+    pub fn with_comments(mut self, comments: bool) -> Preview {
+        self.comments = comments;
+        self
+    }
+
+    /// How many of `count` array items to show, if not all of them.
+    pub(crate) fn items_shown(&self, count: usize) -> Option<usize> {
+        self.items.filter(|items| *items < count)
+    }
+
+    pub(crate) fn collapses(&self, depth: usize) -> bool {
+        self.depth.is_some_and(|limit| depth >= limit)
+    }
+
+    /// The kept part of a string, and how many characters were cut, if any.
+    pub(crate) fn truncate<'a>(&self, text: &'a str) -> Option<(&'a str, usize)> {
+        let limit = self.string_length?;
+        let (end, _) = text.char_indices().nth(limit)?;
+        Some((&text[..end], text[end..].chars().count()))
+    }
+
+    /// The marker after the last item shown.
+    pub(crate) fn more_items(&self, hidden: usize) -> String {
+        let items = plural(hidden, "item");
+        match self.comments {
+            true => format!("// {} more {}", thousands(hidden), items),
+            false => format!("… {} more {}", thousands(hidden), items),
+        }
+    }
+
+    /// The marker following a truncated string.
+    pub(crate) fn more_characters(&self, hidden: usize) -> String {
+        let characters = plural(hidden, "character");
+        match self.comments {
+            true => format!(" /* {} more {} */", thousands(hidden), characters),
+            false => format!("… {} more {}", thousands(hidden), characters),
+        }
+    }
+
+    /// A whole container collapsed to one line, e.g. `{…12 keys}` or `[] /* 3 items */`.
+    pub(crate) fn collapsed(&self, object: bool, count: usize) -> String {
+        let (open, close, noun) = match object {
+            true => ("{", "}", plural(count, "key")),
+            false => ("[", "]", plural(count, "item")),
+        };
+        match self.comments {
+            _ if count == 0 => format!("{}{}", open, close),
+            true => format!("{}{} /* {} {} */", open, close, thousands(count), noun),
+            false => format!("{}…{} {}{}", open, thousands(count), noun, close),
+        }
+    }
+}
+
+impl Default for Preview {
+    fn default() -> Self {
+        Preview::new()
+    }
+}
+
+fn plural(count: usize, noun: &str) -> String {
+    match count {
+        1 => noun.to_owned(),
+        _ => format!("{}s", noun),
+    }
+}
+
+/// `9995` as `9,995`.
+pub(crate) fn thousands(number: usize) -> String {
+    let digits = number.to_string();
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+    for (index, digit) in digits.chars().enumerate() {
+        if index > 0 && (digits.len() - index).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FormatConfig;
+
+    fn preview(json: &str, preview: Preview) -> String {
+        let config = FormatConfig::default().with_preview(preview);
+        crate::format(json.to_owned(), Some(config)).unwrap()
+    }
+
+    #[test]
+    fn limits_array_items() {
+        let json = format!(
+            "[{}]",
+            (0..10_000)
+                .map(|n| n.to_string())
+                .collect::<Vec<_>>()
+                .join(",")
+        );
+        assert_eq!(
+            preview(&json, Preview::new().with_items(2)),
+            "[\n    0,\n    1,\n    … 9,998 more items\n]"
+        );
+        assert_eq!(
+            preview(&json, Preview::new().with_items(1).with_comments(true)),
+            "[\n    0\n    // 9,999 more items\n]"
+        );
+        assert_eq!(
+            preview("[1, 2]", Preview::new().with_items(2)),
+            "[\n    1,\n    2\n]"
+        );
+    }
+
+    #[test]
+    fn truncates_long_strings() {
+        let json = r#"{"short": "abc", "long": "abcdefghé\"jk", "list": ["abcdefghij"]}"#;
+        assert_eq!(
+            preview(json, Preview::new().with_string_length(9)),
+            "{\n    \"short\": \"abc\",\n    \"long\": \"abcdefghé\"… 3 more characters,\n    \"list\": [\n        \"abcdefghi\"… 1 more character\n    ]\n}\n"
+        );
+        assert_eq!(
+            preview(
+                json,
+                Preview::new().with_string_length(8).with_comments(true)
+            ),
+            "{\n    \"short\": \"abc\",\n    \"long\": \"abcdefgh\" /* 4 more characters */,\n    \"list\": [\n        \"abcdefgh\" /* 2 more characters */\n    ]\n}\n"
+        );
+    }
+
+    #[test]
+    fn collapses_deep_containers() {
+        let json = r#"{"a": {"b": {"c": 1, "d": 2}, "e": [1, 2, 3], "f": {}, "g": [1]}}"#;
+        assert_eq!(
+            preview(json, Preview::new().with_depth(2)),
+            "{\n    \"a\": {\n        \"b\": {…2 keys},\n        \"e\": […3 items],\n        \"f\": {},\n        \"g\": […1 item]\n    }\n}\n"
+        );
+        assert_eq!(
+            preview(json, Preview::new().with_depth(1).with_comments(true)),
+            "{\n    \"a\": {} /* 4 keys */\n}\n"
+        );
+    }
+
+    #[test]
+    fn thousands_separators() {
+        assert_eq!(thousands(0), "0");
+        assert_eq!(thousands(999), "999");
+        assert_eq!(thousands(1_000), "1,000");
+        assert_eq!(thousands(1_234_567), "1,234,567");
+    }
+}
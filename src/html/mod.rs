@@ -0,0 +1,130 @@
+//! Formatted documents as syntax-highlighted HTML.
+use crate::formatter::errors::FormatterError;
+use crate::formatter::{FormatConfig, Markup};
+
+/// A light theme for the classes `render` uses, scoped to `pre.jsonist`.
+pub const STYLESHEET: &str = "<style>
+pre.jsonist { background: #fafafa; color: #383a42; padding: 1em; tab-size: 4; }
+pre.jsonist .key { color: #4078f2; }
+pre.jsonist .string { color: #50a14f; }
+pre.jsonist .number { color: #986801; }
+pre.jsonist .literal { color: #a626a4; }
+pre.jsonist .punctuation { color: #696c77; }
+pre.jsonist details, pre.jsonist summary { display: inline; }
+pre.jsonist summary { cursor: pointer; list-style: none; }
+pre.jsonist summary::-webkit-details-marker { display: none; }
+pre.jsonist details.object:not([open]) > summary::after { content: \" … }\"; color: #696c77; }
+pre.jsonist details.array:not([open]) > summary::after { content: \" … ]\"; color: #696c77; }
+</style>
+";
+
+pub struct HtmlConfig {
+    collapsible: bool,
+    stylesheet: bool,
+}
+
+impl HtmlConfig {
+    pub fn new() -> HtmlConfig {
+        HtmlConfig {
+            collapsible: false,
+            stylesheet: false,
+        }
+    }
+
+    /// Wrap every object and array in an open `<details>` element, with its key and opening
+    /// bracket as the `<summary>`, so it can be folded in a browser.
+    pub fn with_collapsible(mut self, collapsible: bool) -> HtmlConfig {
+        self.collapsible = collapsible;
+        self
+    }
+
+    /// Start with `STYLESHEET`, so the output can be pasted into a page as it is.
+    pub fn with_stylesheet(mut self, stylesheet: bool) -> HtmlConfig {
+        self.stylesheet = stylesheet;
+        self
+    }
+}
+
+impl Default for HtmlConfig {
+    fn default() -> Self {
+        HtmlConfig::new()
+    }
+}
+
+/// Formats `input` with `config` inside a `<pre class="jsonist">`, wrapping each token in a
+/// `<span>` whose class is `key`, `string`, `number`, `literal` (`true`, `false` and `null`) or
+/// `punctuation`. Text is HTML-escaped; colours are left to the page's CSS (or `STYLESHEET`).
+pub fn render(
+    input: &str,
+    config: Option<FormatConfig>,
+    html: Option<HtmlConfig>,
+) -> Result<String, FormatterError> {
+    let html = html.unwrap_or_default();
+    let mut config = config.unwrap_or_default();
+    config.markup = Markup::Html {
+        collapsible: html.collapsible,
+    };
+    let formatted = crate::format(input.to_owned(), Some(config))?;
+
+    let mut page = String::new();
+    if html.stylesheet {
+        page.push_str(STYLESHEET);
+    }
+    page.push_str("<pre class=\"jsonist\">");
+    page.push_str(formatted.trim_end_matches(['\r', '\n']));
+    page.push_str("</pre>\n");
+    Ok(page)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Delimiter, DelimiterCount};
+
+    fn two_spaces() -> Option<FormatConfig> {
+        Some(FormatConfig::new(Delimiter::Spaces(DelimiterCount::Two)))
+    }
+
+    #[test]
+    fn wraps_tokens_in_classed_spans() {
+        assert_eq!(
+            render(r#"{"a": [1, null]}"#, two_spaces(), None).unwrap(),
+            "<pre class=\"jsonist\"><span class=\"punctuation\">{</span>
+  <span class=\"key\">&quot;a&quot;</span><span class=\"punctuation\">:</span> <span class=\"punctuation\">[</span>
+    <span class=\"number\">1</span><span class=\"punctuation\">,</span>
+    <span class=\"literal\">null</span>
+  <span class=\"punctuation\">]</span>
+<span class=\"punctuation\">}</span></pre>
+"
+        );
+    }
+
+    #[test]
+    fn escapes_markup_in_strings() {
+        let html = render(r#"["<b>&</b>"]"#, two_spaces(), None).unwrap();
+        assert!(html.contains("<span class=\"string\">&quot;&lt;b&gt;&amp;&lt;/b&gt;&quot;</span>"));
+        assert!(!html.contains("<b>"));
+    }
+
+    #[test]
+    fn containers_can_be_collapsible() {
+        let config = HtmlConfig::new().with_collapsible(true);
+        let html = render(r#"{"a": []}"#, two_spaces(), Some(config)).unwrap();
+
+        assert!(html.starts_with(
+            "<pre class=\"jsonist\"><details open class=\"object\"><summary><span class=\"punctuation\">{</span></summary>"
+        ));
+        assert!(html.contains(
+            "<details open class=\"array\"><summary><span class=\"key\">&quot;a&quot;</span><span class=\"punctuation\">:</span> <span class=\"punctuation\">[</span></summary>"
+        ));
+        assert!(html.ends_with("</details></pre>\n"));
+    }
+
+    #[test]
+    fn stylesheet_comes_first() {
+        let config = HtmlConfig::new().with_stylesheet(true);
+        let html = render("1", None, Some(config)).unwrap();
+        assert!(html.starts_with("<style>"));
+        assert!(html.ends_with("<pre class=\"jsonist\"><span class=\"number\">1</span></pre>\n"));
+    }
+}
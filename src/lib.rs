@@ -1,15 +1,70 @@
+pub mod convert;
+pub mod cst;
+pub mod diff;
+pub mod filter;
+pub mod flatten;
 pub mod formatter;
+pub mod html;
+pub mod lsp;
+pub mod navigation;
+pub mod patch;
+pub mod query;
+pub mod schema;
+pub mod source_map;
+pub mod stats;
 pub use formatter::errors::FormatterError;
-pub use formatter::{Delimiter, DelimiterCount, FormatConfig};
+pub use formatter::{
+    ColorChoice, Delimiter, DelimiterCount, FormatConfig, LineEnding, Preview, Redaction, Theme,
+};
+pub use parser::DuplicateKeyPolicy;
+pub use range::{format_range, line_range, TextEdit};
+pub use value::Value;
 
+mod datetime;
 mod parser;
+mod range;
+mod regex;
 mod tokenizer;
+mod value;
 
 pub fn format(input: String, config: Option<FormatConfig>) -> Result<String, FormatterError> {
-    let tokens = tokenizer::tokenize(input.as_str())?;
-    let ast = parser::parse(tokens)?;
-    match config {
-        None => Ok(formatter::stringify(ast)),
-        Some(config) => Ok(formatter::stringify_with_config(ast, &config)),
-    }
+    let (formatted, _) = format_with_warnings(input, config)?;
+    Ok(formatted)
+}
+
+/// Parses `input` into a `Value` using the default `FormatConfig` rules.
+pub fn parse(input: &str) -> Result<Value, FormatterError> {
+    let (ast, _) = parse_with_config(input, &FormatConfig::default())?;
+    Ok(Value::from(ast.root))
+}
+
+/// Parses newline-delimited JSON: one document per non-blank line.
+pub fn parse_ndjson(input: &str) -> Result<Vec<Value>, FormatterError> {
+    input
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(parse)
+        .collect()
+}
+
+/// Like `format`, but also returns the non-fatal problems found while parsing
+/// (e.g. duplicate keys kept under `DuplicateKeyPolicy::KeepAll`).
+pub fn format_with_warnings(
+    input: String,
+    config: Option<FormatConfig>,
+) -> Result<(String, Vec<FormatterError>), FormatterError> {
+    let mut config = config.unwrap_or_default();
+    config.detect_line_ending(&input);
+    let (ast, warnings) = parse_with_config(&input, &config)?;
+    Ok((formatter::stringify_with_config(ast, &config), warnings))
+}
+
+pub(crate) fn parse_with_config(
+    input: &str,
+    config: &FormatConfig,
+) -> Result<(parser::AST, Vec<FormatterError>), FormatterError> {
+    let document = cst::parse(input)?;
+    let mut context = parser::ParseContext::for_config(config);
+    let ast = parser::parse(&document, &mut context)?;
+    Ok((ast, context.warnings))
 }
@@ -0,0 +1,123 @@
+use std::collections::HashSet;
+use std::ops::Range;
+
+use crate::cst::{self, SyntaxKind, SyntaxNode};
+use crate::formatter::errors::FormatterError;
+use crate::parser::{self, DuplicateKeyPolicy, ParseContext};
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Diagnostic {
+    pub range: Range<usize>,
+    pub error: FormatterError,
+}
+
+/// Every problem that would stop `text` from being formatted, with the byte range it covers.
+pub fn diagnostics(text: &str) -> Vec<Diagnostic> {
+    // The same parse `format` does, keeping duplicate keys so they can be reported with their
+    // positions from the syntax tree.
+    let parsed = cst::parse(text).and_then(|document| {
+        parser::parse(
+            &document,
+            &mut ParseContext::new(DuplicateKeyPolicy::KeepAll),
+        )?;
+        Ok(document)
+    });
+
+    match parsed {
+        Ok(document) => {
+            let mut found = vec![];
+            duplicate_keys(&document, &mut found);
+            found
+        }
+        Err(error) => {
+            let offset = error_offset(text, &error);
+            vec![Diagnostic {
+                range: offset..text.len().min(offset + 1),
+                error,
+            }]
+        }
+    }
+}
+
+/// The tokenizer reports character indices while the syntax tree reports byte offsets.
+fn error_offset(text: &str, error: &FormatterError) -> usize {
+    let character_offset = |index: usize| {
+        text.char_indices()
+            .nth(index)
+            .map_or(text.len(), |(offset, _)| offset)
+    };
+
+    match error {
+        FormatterError::ExpectedMoreCharacters(index)
+        | FormatterError::InvalidTokenStartCharacter(index, _)
+        | FormatterError::WrongCharacter {
+            position: index, ..
+        }
+        | FormatterError::InvalidNumberCharacter(index, _)
+        | FormatterError::ExtraDotInNumber(index)
+        | FormatterError::ExtraEInNumber(index)
+        | FormatterError::NumberLiteralEndingInE(index)
+        | FormatterError::NumberCanNotHaveANegativeSignNotAtHead(index) => character_offset(*index),
+        FormatterError::UnexpectedToken(offset)
+        | FormatterError::ExpectedStringLiteral(offset)
+        | FormatterError::ExpectedColonInKeyValuePair(offset)
+        | FormatterError::InvalidNumber(offset) => *offset,
+        _ => text.trim_end().len(),
+    }
+}
+
+fn duplicate_keys(node: &SyntaxNode, found: &mut Vec<Diagnostic>) {
+    let mut keys = HashSet::new();
+
+    for child in node.child_nodes() {
+        if node.kind() == SyntaxKind::Object {
+            if let (Some(key), Some(literal)) = (child.children().first(), child.key()) {
+                if !keys.insert(literal.clone()) {
+                    found.push(Diagnostic {
+                        range: key.offset()..key.end(),
+                        error: FormatterError::DuplicateKeyEntry(literal),
+                    });
+                }
+            }
+        }
+        duplicate_keys(child, found);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_syntax_errors_at_their_position() {
+        let text = "{\"a\": tru }";
+        let found = diagnostics(text);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].range, 6..7);
+
+        let cases = [
+            ("[1, *]", 4..5),
+            ("[1, -]", 4..5),
+            ("[\"é\", 2-1]", 8..9),
+            ("[1e]", 2..3),
+            ("{\"a\" 1}", 5..6),
+            ("[1, ", 3..4),
+        ];
+        for (text, range) in cases {
+            assert_eq!(diagnostics(text)[0].range, range, "{}", text);
+        }
+    }
+
+    #[test]
+    fn reports_each_repeated_key() {
+        let text = r#"{"a": 1, "b": {"a": 2}, "a": 3}"#;
+        let found = diagnostics(text);
+
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].range, 24..27);
+        assert_eq!(
+            found[0].error,
+            FormatterError::DuplicateKeyEntry(String::from("a"))
+        );
+    }
+}
@@ -0,0 +1,382 @@
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+
+use crate::formatter::{Delimiter, DelimiterCount, FormatConfig};
+use crate::navigation::{
+    document_symbols, folding_ranges, selection_ranges, DocumentSymbol, SymbolKind,
+};
+use crate::range::{format_range, TextEdit};
+use crate::value::Value;
+use crate::{format, parse};
+
+mod diagnostics;
+use diagnostics::diagnostics;
+
+mod position;
+use position::{offset_to_position, position_to_offset, Position};
+
+mod transport;
+use transport::{read_message, write_message};
+
+const METHOD_NOT_FOUND: f64 = -32601.0;
+const PARSE_ERROR: f64 = -32700.0;
+
+/// Runs a Language Server Protocol server over `input` and `output` (normally stdin and stdout)
+/// until the client sends `exit` or closes the stream.
+pub fn serve<R: BufRead, W: Write>(mut input: R, mut output: W) -> io::Result<()> {
+    let mut server = Server {
+        documents: HashMap::new(),
+    };
+
+    while let Some(body) = read_message(&mut input)? {
+        let message = match parse(&body) {
+            Ok(message) => message,
+            Err(e) => {
+                let response = error_response(Value::Null, PARSE_ERROR, &e.to_string());
+                write_message(&mut output, &response.to_string())?;
+                continue;
+            }
+        };
+
+        let method = message.get("method").and_then(Value::as_str).unwrap_or("");
+        if method == "exit" {
+            return Ok(());
+        }
+
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+        for outgoing in server.handle(method, message.get("id").cloned(), &params) {
+            write_message(&mut output, &outgoing.to_string())?;
+        }
+    }
+
+    Ok(())
+}
+
+struct Server {
+    documents: HashMap<String, String>,
+}
+
+impl Server {
+    /// Handles one request or notification, returning the messages to send back.
+    fn handle(&mut self, method: &str, id: Option<Value>, params: &Value) -> Vec<Value> {
+        let uri = params
+            .get("textDocument")
+            .and_then(|document| document.get("uri"))
+            .and_then(Value::as_str)
+            .unwrap_or("")
+            .to_owned();
+
+        let result = match method {
+            "initialize" => object(vec![(
+                "capabilities",
+                object(vec![
+                    ("textDocumentSync", Value::Number(1.0)),
+                    ("documentFormattingProvider", Value::Bool(true)),
+                    ("documentRangeFormattingProvider", Value::Bool(true)),
+                    ("documentSymbolProvider", Value::Bool(true)),
+                    ("foldingRangeProvider", Value::Bool(true)),
+                    ("selectionRangeProvider", Value::Bool(true)),
+                ]),
+            )]),
+            "shutdown" => Value::Null,
+            "textDocument/didOpen" => {
+                let text = params
+                    .get("textDocument")
+                    .and_then(|document| document.get("text"))
+                    .and_then(Value::as_str)
+                    .unwrap_or("");
+                self.documents.insert(uri.clone(), text.to_owned());
+                return vec![self.publish_diagnostics(&uri)];
+            }
+            "textDocument/didChange" => {
+                let changes = params.get("contentChanges").and_then(Value::as_array);
+                if let Some(text) = changes
+                    .and_then(|changes| changes.last())
+                    .and_then(|change| change.get("text"))
+                    .and_then(Value::as_str)
+                {
+                    self.documents.insert(uri.clone(), text.to_owned());
+                }
+                return vec![self.publish_diagnostics(&uri)];
+            }
+            "textDocument/didClose" => {
+                self.documents.remove(&uri);
+                return vec![notification(
+                    "textDocument/publishDiagnostics",
+                    object(vec![
+                        ("uri", Value::String(uri)),
+                        ("diagnostics", Value::Array(vec![])),
+                    ]),
+                )];
+            }
+            "textDocument/formatting" => self.formatting(&uri, params),
+            "textDocument/rangeFormatting" => self.range_formatting(&uri, params),
+            "textDocument/documentSymbol" => self.document_symbols(&uri),
+            "textDocument/foldingRange" => self.folding_ranges(&uri),
+            "textDocument/selectionRange" => self.selection_ranges(&uri, params),
+            _ => match id {
+                Some(id) => {
+                    let message = format!("Unknown method {}", method);
+                    return vec![error_response(id, METHOD_NOT_FOUND, &message)];
+                }
+                None => return vec![],
+            },
+        };
+
+        match id {
+            Some(id) => vec![object(vec![
+                ("jsonrpc", Value::String(String::from("2.0"))),
+                ("id", id),
+                ("result", result),
+            ])],
+            None => vec![],
+        }
+    }
+
+    fn formatting(&self, uri: &str, params: &Value) -> Value {
+        let text = match self.documents.get(uri) {
+            Some(text) => text,
+            None => return Value::Null,
+        };
+
+        match format(text.to_owned(), Some(config_from(params))) {
+            Ok(formatted) => {
+                let edit = TextEdit {
+                    range: 0..text.len(),
+                    new_text: formatted,
+                };
+                Value::Array(vec![text_edit(text, edit)])
+            }
+            Err(_) => Value::Null,
+        }
+    }
+
+    fn range_formatting(&self, uri: &str, params: &Value) -> Value {
+        let text = match self.documents.get(uri) {
+            Some(text) => text,
+            None => return Value::Null,
+        };
+        let range = match params.get("range") {
+            Some(range) => range,
+            None => return Value::Null,
+        };
+        let start = position_to_offset(text, position_from(range.get("start")));
+        let end = position_to_offset(text, position_from(range.get("end")));
+
+        match format_range(text, start..end, Some(config_from(params))) {
+            Ok(edits) => Value::Array(
+                edits
+                    .into_iter()
+                    .map(|edit| text_edit(text, edit))
+                    .collect(),
+            ),
+            Err(_) => Value::Null,
+        }
+    }
+
+    fn document_symbols(&self, uri: &str) -> Value {
+        let text = self.documents.get(uri).map_or("", String::as_str);
+        match document_symbols(text) {
+            Ok(symbols) => Value::Array(
+                symbols
+                    .into_iter()
+                    .map(|symbol| symbol_value(text, symbol))
+                    .collect(),
+            ),
+            Err(_) => Value::Null,
+        }
+    }
+
+    fn folding_ranges(&self, uri: &str) -> Value {
+        let text = self.documents.get(uri).map_or("", String::as_str);
+        match folding_ranges(text) {
+            Ok(ranges) => Value::Array(
+                ranges
+                    .into_iter()
+                    .map(|range| {
+                        object(vec![
+                            ("startLine", Value::Number(range.start_line as f64)),
+                            ("endLine", Value::Number(range.end_line as f64)),
+                        ])
+                    })
+                    .collect(),
+            ),
+            Err(_) => Value::Null,
+        }
+    }
+
+    fn selection_ranges(&self, uri: &str, params: &Value) -> Value {
+        let text = self.documents.get(uri).map_or("", String::as_str);
+        let positions = params
+            .get("positions")
+            .and_then(Value::as_array)
+            .cloned()
+            .unwrap_or_default();
+
+        let mut results = vec![];
+        for position in positions {
+            let offset = position_to_offset(text, position_from(Some(&position)));
+            let ranges = match selection_ranges(text, offset) {
+                Ok(ranges) => ranges,
+                Err(_) => return Value::Null,
+            };
+
+            // Each range links to the next larger one as its parent.
+            let linked = ranges.iter().rev().fold(None, |parent, range| {
+                let mut pairs = vec![("range", range_value(text, range))];
+                if let Some(parent) = parent {
+                    pairs.push(("parent", parent));
+                }
+                Some(object(pairs))
+            });
+            results.push(
+                linked.unwrap_or_else(|| {
+                    object(vec![("range", range_value(text, &(offset..offset)))])
+                }),
+            );
+        }
+
+        Value::Array(results)
+    }
+
+    fn publish_diagnostics(&self, uri: &str) -> Value {
+        let text = self.documents.get(uri).map_or("", String::as_str);
+        let found = diagnostics(text)
+            .into_iter()
+            .map(|diagnostic| {
+                object(vec![
+                    ("range", range_value(text, &diagnostic.range)),
+                    ("severity", Value::Number(1.0)),
+                    ("source", Value::String(String::from("jsonist"))),
+                    ("message", Value::String(diagnostic.error.to_string())),
+                ])
+            })
+            .collect();
+
+        notification(
+            "textDocument/publishDiagnostics",
+            object(vec![
+                ("uri", Value::String(uri.to_owned())),
+                ("diagnostics", Value::Array(found)),
+            ]),
+        )
+    }
+}
+
+/// Maps the editor's `FormattingOptions` onto the closest `FormatConfig`.
+fn config_from(params: &Value) -> FormatConfig {
+    let options = params.get("options");
+    let insert_spaces = options
+        .and_then(|options| options.get("insertSpaces"))
+        .and_then(Value::as_bool)
+        .unwrap_or(true);
+    let tab_size = options
+        .and_then(|options| options.get("tabSize"))
+        .and_then(Value::as_f64)
+        .unwrap_or(4.0);
+
+    if !insert_spaces {
+        FormatConfig::new(Delimiter::Tabs)
+    } else if tab_size <= 2.0 {
+        FormatConfig::new(Delimiter::Spaces(DelimiterCount::Two))
+    } else {
+        FormatConfig::new(Delimiter::Spaces(DelimiterCount::Four))
+    }
+}
+
+fn object(pairs: Vec<(&str, Value)>) -> Value {
+    Value::Object(
+        pairs
+            .into_iter()
+            .map(|(key, value)| (key.to_owned(), value))
+            .collect(),
+    )
+}
+
+fn notification(method: &str, params: Value) -> Value {
+    object(vec![
+        ("jsonrpc", Value::String(String::from("2.0"))),
+        ("method", Value::String(method.to_owned())),
+        ("params", params),
+    ])
+}
+
+fn error_response(id: Value, code: f64, message: &str) -> Value {
+    object(vec![
+        ("jsonrpc", Value::String(String::from("2.0"))),
+        ("id", id),
+        (
+            "error",
+            object(vec![
+                ("code", Value::Number(code)),
+                ("message", Value::String(message.to_owned())),
+            ]),
+        ),
+    ])
+}
+
+fn position_from(value: Option<&Value>) -> Position {
+    let field = |name: &str| {
+        value
+            .and_then(|value| value.get(name))
+            .and_then(Value::as_f64)
+            .unwrap_or(0.0) as usize
+    };
+    Position {
+        line: field("line"),
+        character: field("character"),
+    }
+}
+
+fn position_value(position: Position) -> Value {
+    object(vec![
+        ("line", Value::Number(position.line as f64)),
+        ("character", Value::Number(position.character as f64)),
+    ])
+}
+
+fn range_value(text: &str, range: &std::ops::Range<usize>) -> Value {
+    object(vec![
+        (
+            "start",
+            position_value(offset_to_position(text, range.start)),
+        ),
+        ("end", position_value(offset_to_position(text, range.end))),
+    ])
+}
+
+fn symbol_value(text: &str, symbol: DocumentSymbol) -> Value {
+    // Numbers from the Language Server Protocol `SymbolKind` enumeration.
+    let kind = match symbol.kind {
+        SymbolKind::String => 15.0,
+        SymbolKind::Number => 16.0,
+        SymbolKind::Boolean => 17.0,
+        SymbolKind::Array => 18.0,
+        SymbolKind::Object => 19.0,
+        SymbolKind::Null => 21.0,
+    };
+
+    object(vec![
+        ("name", Value::String(symbol.name)),
+        ("kind", Value::Number(kind)),
+        ("range", range_value(text, &symbol.range)),
+        ("selectionRange", range_value(text, &symbol.selection_range)),
+        (
+            "children",
+            Value::Array(
+                symbol
+                    .children
+                    .into_iter()
+                    .map(|child| symbol_value(text, child))
+                    .collect(),
+            ),
+        ),
+    ])
+}
+
+fn text_edit(text: &str, edit: TextEdit) -> Value {
+    object(vec![
+        ("range", range_value(text, &edit.range)),
+        ("newText", Value::String(edit.new_text)),
+    ])
+}
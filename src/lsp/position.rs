@@ -0,0 +1,85 @@
+/// A zero based line and UTF-16 character, as the Language Server Protocol counts them.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Position {
+    pub line: usize,
+    pub character: usize,
+}
+
+pub fn offset_to_position(text: &str, offset: usize) -> Position {
+    let mut position = Position {
+        line: 0,
+        character: 0,
+    };
+
+    for (index, character) in text.char_indices() {
+        if index >= offset {
+            break;
+        }
+        if character == '\n' {
+            position.line += 1;
+            position.character = 0;
+        } else {
+            position.character += character.len_utf16();
+        }
+    }
+
+    position
+}
+
+pub fn position_to_offset(text: &str, position: Position) -> usize {
+    let mut line = 0;
+    let mut character = 0;
+
+    for (index, current) in text.char_indices() {
+        if line == position.line && (character >= position.character || current == '\n') {
+            return index;
+        }
+        if current == '\n' {
+            line += 1;
+            character = 0;
+        } else if line == position.line {
+            character += current.len_utf16();
+        }
+    }
+
+    text.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn offsets_and_positions_agree() {
+        let text = "{\n  \"😀\": 1\n}";
+        let offset = text.find(':').unwrap();
+        let position = Position {
+            line: 1,
+            character: 6,
+        };
+
+        assert_eq!(offset_to_position(text, offset), position);
+        assert_eq!(position_to_offset(text, position), offset);
+    }
+
+    #[test]
+    fn positions_past_the_line_end_clamp_to_it() {
+        let text = "ab\ncd";
+        let position = Position {
+            line: 0,
+            character: 10,
+        };
+
+        assert_eq!(position_to_offset(text, position), 2);
+        assert_eq!(
+            position_to_offset(
+                text,
+                Position {
+                    line: 5,
+                    character: 0
+                }
+            ),
+            5
+        );
+    }
+}
@@ -0,0 +1,526 @@
+extern crate jsonist;
+
+use std::env;
+use std::fs;
+use std::io::{self, Read};
+use std::process;
+
+use jsonist::convert::{self, CsvConfig, Nested};
+use jsonist::diff::{self, DiffConfig};
+use jsonist::flatten::{self, FlattenConfig};
+use jsonist::html::{self, HtmlConfig};
+use jsonist::schema::{self, Schema};
+use jsonist::stats::{self, StatsConfig};
+use jsonist::{
+    filter, format, lsp, parse, parse_ndjson, patch, query, ColorChoice, FormatConfig,
+    FormatterError, LineEnding, Preview, Redaction, Value,
+};
+
+const USAGE: &str = "Usage:
+    jsonist [FILE]                format FILE (or stdin) to stdout
+    jsonist get POINTER [FILE]    print the value at a JSON Pointer, e.g. /address/city
+    jsonist query PATH [FILE]     print the values a JSONPath selects, e.g. '$..price'
+        --paths                   print each match's normalized path, value and span instead
+    jsonist filter EXPR [FILE]    transform FILE (or stdin) with a jq-style filter, e.g. '.[] | .name'
+    jsonist convert --to FORMAT [FILE]  convert FILE (or stdin) to yaml, toml, csv or tsv
+        --nested-json             write nested values as one JSON column instead of flattening
+    jsonist convert --from FORMAT [FILE]  convert csv or tsv in FILE (or stdin) to JSON
+    jsonist flatten [FILE]        turn FILE (or stdin) into one object of leaves, e.g. a.b.0
+        --separator SEP           join keys with SEP instead of .
+        --pointer                 use JSON Pointers as keys
+    jsonist unflatten [FILE]      rebuild a nested document from flattened keys (same options)
+    jsonist preview [FILE]        print a taste of a large FILE (or stdin), eliding the rest
+        --items N                 show N items of each array (10)
+        --strings N               cut strings after N characters (80)
+        --depth N                 collapse objects and arrays nested N deep
+        --comments                write what was left out as comments, for valid JSONC
+    jsonist html [FILE]           format FILE (or stdin) as syntax-highlighted HTML
+        --collapsible             let objects and arrays be folded in a browser
+        --stylesheet              start with a <style> element for the highlighting
+    jsonist merge BASE OVERLAY    apply OVERLAY to BASE as a JSON Merge Patch (RFC 7396)
+    jsonist diff A B              list what changed between two documents, ignoring formatting
+        --key-order               also report objects whose members were reordered
+        --json                    print the changes as JSON
+    jsonist stats [FILE]          summarise the shape of FILE (or stdin) and where its bytes go
+        --top N                   list N entries in each ranking (10)
+        --json                    print the statistics as JSON
+    jsonist validate SCHEMA [FILE]  check FILE (or stdin) against a JSON Schema (2020-12)
+    jsonist infer-schema [FILE...]  infer a JSON Schema that every FILE (or stdin) matches
+        --ndjson                  read each line of the input as a separate sample
+    jsonist lsp                   run the language server over stdio
+
+Options:
+    --color WHEN                  colour the output: auto (the default, only on a terminal
+                                  without NO_COLOR set), always or never
+    --redact SELECTOR             print [REDACTED] for the values of keys matching a pattern
+                                  (password, *_secret), a JSON Pointer or a JSONPath query
+    --mask                        mask card numbers and JSON Web Tokens inside strings
+    --line-ending ENDING          end lines with lf, crlf or auto (the default, whatever the
+                                  input's first line ends with)";
+
+fn main() {
+    let args = env::args().skip(1).collect::<Vec<String>>();
+
+    if let Err(message) = run(&args) {
+        eprintln!("{}", message);
+        process::exit(1);
+    }
+}
+
+fn run(args: &[String]) -> Result<(), String> {
+    let mut args = args.iter().map(String::as_str).collect::<Vec<&str>>();
+    let output = Output {
+        color: take_color(&mut args)?,
+        redaction: take_redaction(&mut args)?,
+        line_ending: take_line_ending(&mut args)?,
+    };
+
+    match args.as_slice() {
+        ["lsp"] => {
+            let stdin = io::stdin();
+            let stdout = io::stdout();
+            lsp::serve(stdin.lock(), stdout.lock()).map_err(|e| e.to_string())
+        }
+        ["get", pointer] => get(pointer, read_input(None)?, &output),
+        ["get", pointer, path] => get(pointer, read_input(Some(path))?, &output),
+        ["query", "--paths", path] => run_query(path, read_input(None)?, true, &output),
+        ["query", "--paths", path, file] => run_query(path, read_input(Some(file))?, true, &output),
+        ["query", path] => run_query(path, read_input(None)?, false, &output),
+        ["query", path, file] => run_query(path, read_input(Some(file))?, false, &output),
+        ["filter", expression] => run_filter(expression, read_input(None)?, &output),
+        ["filter", expression, path] => run_filter(expression, read_input(Some(path))?, &output),
+        ["convert", rest @ ..] => run_convert(rest, &output),
+        ["flatten", rest @ ..] => run_flatten(rest, false, &output),
+        ["unflatten", rest @ ..] => run_flatten(rest, true, &output),
+        ["preview", rest @ ..] => run_preview(rest, &output),
+        ["html", rest @ ..] => run_html(rest, &output),
+        ["merge", base, overlay] => {
+            let merged = patch::merge_documents(
+                &read_input(Some(base))?,
+                &read_input(Some(overlay))?,
+                Some(format_config(&output)),
+            )
+            .map_err(|e| e.to_string())?;
+            print_output(&merged);
+            Ok(())
+        }
+        ["diff", rest @ ..] => run_diff(rest, &output),
+        ["stats", rest @ ..] => run_stats(rest, &output),
+        ["validate", schema] => validate(&read_input(Some(schema))?, read_input(None)?),
+        ["validate", schema, path] => validate(&read_input(Some(schema))?, read_input(Some(path))?),
+        ["infer-schema", rest @ ..] => infer_schema(rest, &output),
+        ["-h"] | ["--help"] => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        [] => print_formatted(read_input(None)?, &output),
+        [path] => print_formatted(read_input(Some(path))?, &output),
+        _ => Err(String::from(USAGE)),
+    }
+}
+
+/// Takes `NAME VALUE` (or `NAME=VALUE`) out of the arguments, if it is there.
+fn take_option<'a>(args: &mut Vec<&'a str>, name: &str) -> Result<Option<&'a str>, String> {
+    let index = match args.iter().position(|arg| arg.starts_with(name)) {
+        Some(index) => index,
+        None => return Ok(None),
+    };
+    let value = match args[index].strip_prefix(name) {
+        Some("") if index + 1 < args.len() => args.remove(index + 1),
+        Some(value) if value.starts_with('=') => &value[1..],
+        _ => return Err(String::from(USAGE)),
+    };
+    args.remove(index);
+    Ok(Some(value))
+}
+
+/// Takes `--color WHEN` (or `--color=WHEN`) out of the arguments, defaulting to `auto`.
+fn take_color(args: &mut Vec<&str>) -> Result<ColorChoice, String> {
+    match take_option(args, "--color")? {
+        None | Some("auto") => Ok(ColorChoice::Auto),
+        Some("always") => Ok(ColorChoice::Always),
+        Some("never") => Ok(ColorChoice::Never),
+        _ => Err(String::from(USAGE)),
+    }
+}
+
+/// Takes `--line-ending ENDING` (or `--line-ending=ENDING`) out of the arguments, defaulting
+/// to `auto`.
+fn take_line_ending(args: &mut Vec<&str>) -> Result<LineEnding, String> {
+    match take_option(args, "--line-ending")? {
+        None | Some("auto") => Ok(LineEnding::Auto),
+        Some("lf") => Ok(LineEnding::Lf),
+        Some("crlf") => Ok(LineEnding::Crlf),
+        _ => Err(String::from(USAGE)),
+    }
+}
+
+/// Takes every `--redact SELECTOR` and `--mask` out of the arguments. A selector starting with
+/// `/` is a JSON Pointer, one starting with `$` a JSONPath query and anything else a key pattern.
+fn take_redaction(args: &mut Vec<&str>) -> Result<Option<Redaction>, String> {
+    let mut redaction = None;
+    while let Some(index) = args
+        .iter()
+        .position(|arg| matches!(*arg, "--redact" | "--mask"))
+    {
+        let current = redaction.take().unwrap_or_else(Redaction::new);
+        redaction = Some(match args.remove(index) {
+            "--mask" => current.with_card_masking(true).with_jwt_masking(true),
+            _ if index < args.len() => {
+                let selector = args.remove(index);
+                match selector.chars().next() {
+                    Some('/') => current.with_pointer(selector),
+                    Some('$') => current.with_path(selector),
+                    _ => Ok(current.with_key(selector)),
+                }
+                .map_err(|e| e.to_string())?
+            }
+            _ => return Err(String::from(USAGE)),
+        });
+    }
+    Ok(redaction)
+}
+
+/// How everything printed as JSON looks.
+struct Output {
+    color: ColorChoice,
+    redaction: Option<Redaction>,
+    line_ending: LineEnding,
+}
+
+fn format_config(output: &Output) -> FormatConfig {
+    let config = layout_config(output);
+    match &output.redaction {
+        Some(redaction) => config.with_redaction(redaction.clone()),
+        None => config,
+    }
+}
+
+/// `format_config` without the redaction, for values already redacted by `redacted`.
+fn layout_config(output: &Output) -> FormatConfig {
+    FormatConfig::default()
+        .with_color(output.color)
+        .with_line_ending(output.line_ending)
+}
+
+/// Parses `input` and hides what `--redact` and `--mask` select, for commands that print
+/// values picked out of the document or another format rather than the document itself.
+fn redacted(input: &str, output: &Output) -> Result<Value, String> {
+    let document = parse(input).map_err(|e| e.to_string())?;
+    Ok(match &output.redaction {
+        Some(redaction) => redaction.redact_value(document),
+        None => document,
+    })
+}
+
+/// Reads the file at `path`, or stdin when there is no path (or it is `-`).
+fn read_input(path: Option<&str>) -> Result<String, String> {
+    match path {
+        Some(path) if path != "-" => {
+            fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))
+        }
+        _ => {
+            let mut input = String::new();
+            io::stdin()
+                .read_to_string(&mut input)
+                .map_err(|e| e.to_string())?;
+            Ok(input)
+        }
+    }
+}
+
+fn print_formatted(input: String, output: &Output) -> Result<(), String> {
+    let formatted = format(input, Some(format_config(output))).map_err(|e| e.to_string())?;
+    print_output(&formatted);
+    Ok(())
+}
+
+fn get(pointer: &str, input: String, output: &Output) -> Result<(), String> {
+    let document = redacted(&input, output)?;
+    let value = document
+        .pointer(pointer)
+        .ok_or_else(|| FormatterError::PointerNotFound(pointer.to_owned()).to_string())?;
+    print_output(&value.format_like(&input, Some(layout_config(output))));
+    Ok(())
+}
+
+fn run_query(path: &str, input: String, with_paths: bool, output: &Output) -> Result<(), String> {
+    let matches = query::query(&input, path).map_err(|e| e.to_string())?;
+    let document = redacted(&input, output)?;
+    let results = matches
+        .into_iter()
+        .map(|mut found| {
+            if let Some(value) = document.pointer(&found.path.to_pointer()) {
+                found.value = value.clone();
+            }
+            found
+        })
+        .map(|found| match with_paths {
+            true => Value::Object(vec![
+                (String::from("path"), Value::String(found.path.to_string())),
+                (String::from("value"), found.value),
+                (
+                    String::from("span"),
+                    Value::Array(vec![
+                        Value::Number(found.span.start as f64),
+                        Value::Number(found.span.end as f64),
+                    ]),
+                ),
+            ]),
+            false => found.value,
+        })
+        .collect();
+    print_output(&Value::Array(results).format_like(&input, Some(layout_config(output))));
+    Ok(())
+}
+
+fn run_filter(expression: &str, input: String, output: &Output) -> Result<(), String> {
+    let output = filter::filter(&input, expression, Some(format_config(output)))
+        .map_err(|e| e.to_string())?;
+    print!("{}", output);
+    Ok(())
+}
+
+fn run_convert(args: &[&str], output: &Output) -> Result<(), String> {
+    let (flags, rest) = args
+        .iter()
+        .partition::<Vec<&str>, _>(|arg| **arg == "--nested-json");
+    let nested = match flags.is_empty() {
+        true => Nested::Flatten,
+        false => Nested::Json,
+    };
+    let (direction, format, input) = match rest.as_slice() {
+        [direction, format] => (*direction, *format, read_input(None)?),
+        [direction, format, path] => (*direction, *format, read_input(Some(path))?),
+        _ => return Err(String::from(USAGE)),
+    };
+    let csv = match format {
+        "csv" => CsvConfig::new(),
+        "tsv" => CsvConfig::tsv(),
+        _ if direction == "--to" => return convert_to(format, input, output),
+        _ => return Err(String::from(USAGE)),
+    };
+
+    match direction {
+        "--to" => {
+            let document = redacted(&input, output)?;
+            let converted =
+                convert::to_csv(&document, &csv.with_nested(nested)).map_err(|e| e.to_string())?;
+            print!("{}", converted);
+        }
+        "--from" => {
+            let json = convert::csv_to_json(&input, Some(csv), Some(format_config(output)))
+                .map_err(|e| e.to_string())?;
+            print_output(&json);
+        }
+        _ => return Err(String::from(USAGE)),
+    }
+    Ok(())
+}
+
+fn convert_to(to: &str, input: String, output: &Output) -> Result<(), String> {
+    let document = redacted(&input, output)?;
+    let converted = match to {
+        "yaml" => convert::to_yaml(&document),
+        "toml" => convert::to_toml(&document).map_err(|e| e.to_string())?,
+        _ => return Err(String::from(USAGE)),
+    };
+    print!("{}", converted);
+    Ok(())
+}
+
+fn run_flatten(args: &[&str], reverse: bool, output: &Output) -> Result<(), String> {
+    let (config, files) = match args {
+        ["--pointer", files @ ..] => (FlattenConfig::new().with_pointer_keys(), files),
+        ["--separator", separator, files @ ..] => {
+            (FlattenConfig::new().with_separator(separator), files)
+        }
+        files => (FlattenConfig::new(), files),
+    };
+    let input = match files {
+        [] => read_input(None)?,
+        [file] => read_input(Some(file))?,
+        _ => return Err(String::from(USAGE)),
+    };
+
+    let output = match reverse {
+        false => flatten::flatten_document(&input, Some(config), Some(format_config(output))),
+        true => flatten::unflatten_document(&input, Some(config), Some(format_config(output))),
+    };
+    print_output(&output.map_err(|e| e.to_string())?);
+    Ok(())
+}
+
+fn run_preview(mut args: &[&str], output: &Output) -> Result<(), String> {
+    let number = |text: &str| text.parse::<usize>().map_err(|_| String::from(USAGE));
+    let mut preview = Preview::new();
+    loop {
+        args = match args {
+            ["--items", items, rest @ ..] => {
+                preview = preview.with_items(number(items)?);
+                rest
+            }
+            ["--strings", length, rest @ ..] => {
+                preview = preview.with_string_length(number(length)?);
+                rest
+            }
+            ["--depth", depth, rest @ ..] => {
+                preview = preview.with_depth(number(depth)?);
+                rest
+            }
+            ["--comments", rest @ ..] => {
+                preview = preview.with_comments(true);
+                rest
+            }
+            _ => break,
+        };
+    }
+    let input = match args {
+        [] => read_input(None)?,
+        [file] => read_input(Some(file))?,
+        _ => return Err(String::from(USAGE)),
+    };
+
+    let config = format_config(output).with_preview(preview);
+    let formatted = format(input, Some(config)).map_err(|e| e.to_string())?;
+    print_output(&formatted);
+    Ok(())
+}
+
+fn run_html(args: &[&str], output: &Output) -> Result<(), String> {
+    let (flags, files) = args
+        .iter()
+        .partition::<Vec<&str>, _>(|arg| arg.starts_with("--"));
+    let input = match files.as_slice() {
+        [] => read_input(None)?,
+        [file] => read_input(Some(file))?,
+        _ => return Err(String::from(USAGE)),
+    };
+
+    let mut config = HtmlConfig::new();
+    for flag in flags {
+        match flag {
+            "--collapsible" => config = config.with_collapsible(true),
+            "--stylesheet" => config = config.with_stylesheet(true),
+            _ => return Err(String::from(USAGE)),
+        }
+    }
+
+    let html = html::render(&input, Some(format_config(output)), Some(config))
+        .map_err(|e| e.to_string())?;
+    print!("{}", html);
+    Ok(())
+}
+
+fn run_stats(mut args: &[&str], output: &Output) -> Result<(), String> {
+    let mut config = StatsConfig::new();
+    let mut as_json = false;
+    loop {
+        args = match args {
+            ["--top", top, rest @ ..] => {
+                config = config.with_top(top.parse().map_err(|_| String::from(USAGE))?);
+                rest
+            }
+            ["--json", rest @ ..] => {
+                as_json = true;
+                rest
+            }
+            _ => break,
+        };
+    }
+    let input = match args {
+        [] => read_input(None)?,
+        [file] => read_input(Some(file))?,
+        _ => return Err(String::from(USAGE)),
+    };
+
+    let stats = stats::stats(&input, Some(config)).map_err(|e| e.to_string())?;
+    match as_json {
+        true => {
+            print_output(&stats::report(&stats).format_like(&input, Some(format_config(output))))
+        }
+        false => print!("{}", stats::render(&stats)),
+    }
+    Ok(())
+}
+
+fn run_diff(args: &[&str], output: &Output) -> Result<(), String> {
+    let (flags, files) = args
+        .iter()
+        .partition::<Vec<&str>, _>(|arg| arg.starts_with("--"));
+    let (from, to) = match files.as_slice() {
+        [from, to] => (read_input(Some(from))?, read_input(Some(to))?),
+        _ => return Err(String::from(USAGE)),
+    };
+
+    let mut config = DiffConfig::new();
+    let mut as_json = false;
+    for flag in flags {
+        match flag {
+            "--key-order" => config = config.with_key_order(true),
+            "--json" => as_json = true,
+            _ => return Err(String::from(USAGE)),
+        }
+    }
+
+    let changes = diff::diff_documents(&from, &to, Some(config)).map_err(|e| e.to_string())?;
+    match as_json {
+        true => {
+            print_output(&diff::report(&changes).format_like(&from, Some(format_config(output))))
+        }
+        false => print!("{}", diff::render(&changes, output.color.enabled())),
+    }
+    Ok(())
+}
+
+fn validate(schema: &str, input: String) -> Result<(), String> {
+    let schema = Schema::parse(schema).map_err(|e| format!("schema: {}", e))?;
+    let violations = schema::validate(&input, &schema).map_err(|e| e.to_string())?;
+    match violations.len() {
+        0 => Ok(()),
+        _ => Err(violations
+            .iter()
+            .map(|violation| violation.to_string())
+            .collect::<Vec<String>>()
+            .join("\n")),
+    }
+}
+
+fn infer_schema(args: &[&str], output: &Output) -> Result<(), String> {
+    let (flags, files) = args
+        .iter()
+        .partition::<Vec<&str>, _>(|arg| arg.starts_with("--"));
+    let ndjson = match flags.as_slice() {
+        [] => false,
+        ["--ndjson"] => true,
+        _ => return Err(String::from(USAGE)),
+    };
+    let inputs = match files.as_slice() {
+        [] => vec![read_input(None)?],
+        files => files
+            .iter()
+            .map(|file| read_input(Some(file)))
+            .collect::<Result<Vec<String>, String>>()?,
+    };
+
+    let mut samples = vec![];
+    for input in &inputs {
+        match ndjson {
+            true => samples.extend(parse_ndjson(input).map_err(|e| e.to_string())?),
+            false => samples.push(parse(input).map_err(|e| e.to_string())?),
+        }
+    }
+    let schema = schema::infer_schema(&samples);
+    print_output(&schema.format_like(&inputs[0], Some(format_config(output))));
+    Ok(())
+}
+
+fn print_output(output: &str) {
+    if output.ends_with('\n') {
+        print!("{}", output);
+    } else if output.contains("\r\n") {
+        print!("{}\r\n", output);
+    } else {
+        println!("{}", output);
+    }
+}
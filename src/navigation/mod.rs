@@ -0,0 +1,207 @@
+use std::ops::Range;
+
+use crate::cst::{self, SyntaxElement, SyntaxKind, SyntaxNode};
+use crate::formatter::errors::FormatterError;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum SymbolKind {
+    Object,
+    Array,
+    String,
+    Number,
+    Boolean,
+    Null,
+}
+
+/// An entry of the document outline: an object key (or array index) and what is below it.
+#[derive(Debug, PartialEq, Clone)]
+pub struct DocumentSymbol {
+    pub name: String,
+    pub kind: SymbolKind,
+    /// The whole member or item.
+    pub range: Range<usize>,
+    /// The key, or the item itself inside an array.
+    pub selection_range: Range<usize>,
+    pub children: Vec<DocumentSymbol>,
+}
+
+/// A multi-line object or array, by zero based line of its opening and closing bracket.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct FoldingRange {
+    pub start_line: usize,
+    pub end_line: usize,
+}
+
+pub fn document_symbols(input: &str) -> Result<Vec<DocumentSymbol>, FormatterError> {
+    let document = cst::parse(input)?;
+    Ok(document.value().map_or(vec![], symbols_below))
+}
+
+pub fn folding_ranges(input: &str) -> Result<Vec<FoldingRange>, FormatterError> {
+    let document = cst::parse(input)?;
+    let mut ranges = vec![];
+    collect_folding_ranges(input, &document, &mut ranges);
+    Ok(ranges)
+}
+
+/// The ranges a "expand selection" command steps through from `offset`, innermost first.
+pub fn selection_ranges(input: &str, offset: usize) -> Result<Vec<Range<usize>>, FormatterError> {
+    let document = cst::parse(input)?;
+    let mut ranges = vec![];
+    collect_selection_ranges(&document, offset, &mut ranges);
+    ranges.reverse();
+    ranges.dedup();
+    Ok(ranges)
+}
+
+fn symbols_below(element: &SyntaxElement) -> Vec<DocumentSymbol> {
+    let node = match element {
+        SyntaxElement::Node(node) => node,
+        SyntaxElement::Token(_) => return vec![],
+    };
+
+    match node.kind() {
+        SyntaxKind::Object => node
+            .child_nodes()
+            .filter_map(|member| {
+                let key = member.children().first()?;
+                let value = member.value()?;
+
+                Some(DocumentSymbol {
+                    name: member.key()?,
+                    kind: symbol_kind(value),
+                    range: member.offset()..member.end(),
+                    selection_range: key.offset()..key.end(),
+                    children: symbols_below(value),
+                })
+            })
+            .collect(),
+        SyntaxKind::Array => node
+            .children()
+            .iter()
+            .filter(|child| child.is_value())
+            .enumerate()
+            .map(|(index, item)| DocumentSymbol {
+                name: index.to_string(),
+                kind: symbol_kind(item),
+                range: item.offset()..item.end(),
+                selection_range: item.offset()..item.end(),
+                children: symbols_below(item),
+            })
+            .collect(),
+        _ => vec![],
+    }
+}
+
+fn collect_folding_ranges(input: &str, node: &SyntaxNode, ranges: &mut Vec<FoldingRange>) {
+    if node.kind() == SyntaxKind::Object || node.kind() == SyntaxKind::Array {
+        let start_line = line_of(input, node.offset());
+        let end_line = line_of(input, node.end() - 1);
+        if start_line < end_line {
+            ranges.push(FoldingRange {
+                start_line,
+                end_line,
+            });
+        }
+    }
+
+    for child in node.child_nodes() {
+        collect_folding_ranges(input, child, ranges);
+    }
+}
+
+fn collect_selection_ranges(node: &SyntaxNode, offset: usize, ranges: &mut Vec<Range<usize>>) {
+    if node.kind() != SyntaxKind::Document {
+        ranges.push(node.offset()..node.end());
+    }
+
+    for child in node.children() {
+        if child.offset() <= offset && offset < child.end() {
+            match child {
+                SyntaxElement::Node(child) => collect_selection_ranges(child, offset, ranges),
+                SyntaxElement::Token(token) if token.kind() != SyntaxKind::WhiteSpace => {
+                    ranges.push(token.offset()..token.end())
+                }
+                SyntaxElement::Token(_) => {}
+            }
+        }
+    }
+}
+
+fn line_of(input: &str, offset: usize) -> usize {
+    input[..offset].matches('\n').count()
+}
+
+fn symbol_kind(element: &SyntaxElement) -> SymbolKind {
+    match element.kind() {
+        SyntaxKind::Object => SymbolKind::Object,
+        SyntaxKind::Array => SymbolKind::Array,
+        SyntaxKind::StringLiteral => SymbolKind::String,
+        SyntaxKind::Number => SymbolKind::Number,
+        SyntaxKind::True | SyntaxKind::False => SymbolKind::Boolean,
+        _ => SymbolKind::Null,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const JSON: &str = "{\n  \"name\": \"x\",\n  \"tags\": [\n    {\"id\": 1}\n  ]\n}\n";
+
+    #[test]
+    fn symbols_follow_the_keys() {
+        let symbols = document_symbols(JSON).unwrap();
+
+        assert_eq!(symbols.len(), 2);
+        assert_eq!(symbols[0].name, "name");
+        assert_eq!(symbols[0].kind, SymbolKind::String);
+        assert_eq!(&JSON[symbols[0].range.clone()], "\"name\": \"x\"");
+        assert_eq!(&JSON[symbols[0].selection_range.clone()], "\"name\"");
+
+        let tags = &symbols[1];
+        assert_eq!(tags.kind, SymbolKind::Array);
+        assert_eq!(tags.children[0].name, "0");
+        assert_eq!(tags.children[0].children[0].name, "id");
+        assert_eq!(tags.children[0].children[0].kind, SymbolKind::Number);
+    }
+
+    #[test]
+    fn folding_ranges_cover_multi_line_containers() {
+        assert_eq!(
+            folding_ranges(JSON).unwrap(),
+            vec![
+                FoldingRange {
+                    start_line: 0,
+                    end_line: 5
+                },
+                FoldingRange {
+                    start_line: 2,
+                    end_line: 4
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn selection_ranges_expand_outwards() {
+        let offset = JSON.find("1}").unwrap();
+        let ranges = selection_ranges(JSON, offset)
+            .unwrap()
+            .into_iter()
+            .map(|range| &JSON[range])
+            .collect::<Vec<&str>>();
+
+        assert_eq!(
+            ranges,
+            vec![
+                "1",
+                "\"id\": 1",
+                "{\"id\": 1}",
+                "[\n    {\"id\": 1}\n  ]",
+                "\"tags\": [\n    {\"id\": 1}\n  ]",
+                JSON.trim_end(),
+            ]
+        );
+    }
+}
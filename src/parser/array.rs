@@ -1,83 +1,104 @@
+use crate::cst::{SyntaxElement, SyntaxNode};
 use crate::formatter::errors::FormatterError;
-use crate::tokenizer::Token;
 
-use super::parse_node;
-use super::JumpNode;
-use super::Node;
+use super::{entries, parse_node, Item, Node, ParseContext};
 
-pub fn parse_array(tokens: &Vec<Token>, position: usize) -> Result<JumpNode, FormatterError> {
+pub fn parse_array(array: &SyntaxNode, context: &mut ParseContext) -> Result<Node, FormatterError> {
+    let items = parse_items(entries(array, context), context)?;
+    Ok(Node::Array { items })
+}
+
+/// Parses the `entries` of an array.
+pub fn parse_items<'a>(
+    entries: impl Iterator<Item = (&'a SyntaxElement, bool)>,
+    context: &mut ParseContext,
+) -> Result<Vec<Item>, FormatterError> {
     let mut items = vec![];
-    let mut jump = position;
 
-    loop {
-        if let Some(token) = tokens.get(jump) {
-            match token {
-                Token::CloseSquareBraket(_) => {
-                    let movement_from_braces = 2;
-                    let net_movement = (jump - position) + movement_from_braces;
-                    return Ok((net_movement, Node::Array { items }));
-                }
-                Token::Comma(_) => {
-                    jump = jump + 1;
-                }
-                _ => {
-                    let (movement, node) = parse_node(&tokens, jump)?;
-                    jump = jump + movement;
-                    items.push(Box::new(node))
-                }
-            }
-        } else {
-            return Err(FormatterError::ExpectedMoreCharacters(6666666));
-        }
+    for (element, blank_line_before) in entries {
+        items.push(Item {
+            node: parse_node(element, context)?,
+            blank_line_before,
+            span: Some(element.offset()..element.end()),
+        });
     }
+    Ok(items)
 }
 
 #[cfg(test)]
 mod tests {
+    use super::super::tests::parse_json;
     use super::*;
 
     #[test]
     fn parse_array_empty() {
-        let open_bracket = Token::OpenSquareBraket(0);
-        let close_bracket = Token::CloseSquareBraket(1);
         let node = Node::Array { items: vec![] };
 
-        match parse_array(&vec![open_bracket, close_bracket], 1) {
-            Ok((_, result)) => assert_eq!(result, node),
+        match parse_json("[]", &mut ParseContext::default()) {
+            Ok(result) => assert_eq!(result, node),
             Err(e) => panic!("{}", e),
         }
     }
 
     #[test]
     fn parse_array_with_a_single_item() {
-        let open_bracket = Token::OpenSquareBraket(0);
-        let false_token = Token::False(1, "false");
-        let close_bracket = Token::CloseSquareBraket(6);
         let node = Node::Array {
-            items: vec![Box::new(Node::False)],
+            items: vec![Item::from(Node::False)],
         };
 
-        match parse_array(&vec![open_bracket, false_token, close_bracket], 1) {
-            Ok((_, result)) => assert_eq!(result, node),
+        match parse_json("[false]", &mut ParseContext::default()) {
+            Ok(result) => assert_eq!(result, node),
             Err(e) => panic!("{}", e),
         }
     }
 
     #[test]
     fn parse_array_with_n_items() {
-        let open_bracket = Token::OpenSquareBraket(0);
-        let false_token = Token::False(1, "false");
-        let comma = Token::Comma(6);
-        let true_token = Token::True(7, "true");
-        let close_bracket = Token::CloseSquareBraket(12);
         let node = Node::Array {
-            items: vec![Box::new(Node::False), Box::new(Node::True)],
+            items: vec![Item::from(Node::False), Item::from(Node::True)],
+        };
+
+        match parse_json("[false,true]", &mut ParseContext::default()) {
+            Ok(result) => assert_eq!(result, node),
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    #[test]
+    fn parse_array_flags_the_item_after_a_blank_line() {
+        let mut context = ParseContext {
+            keep_blank_lines: true,
+            ..ParseContext::default()
+        };
+        let node = Node::Array {
+            items: vec![
+                Item::from(Node::Number { value: 1.0 }),
+                Item {
+                    node: Node::Number { value: 2.0 },
+                    blank_line_before: true,
+                    span: None,
+                },
+                Item::from(Node::Number { value: 3.0 }),
+            ],
         };
 
-        let tokens = vec![open_bracket, false_token, comma, true_token, close_bracket];
-        match parse_array(&tokens, 1) {
-            Ok((_, result)) => assert_eq!(result, node),
+        match parse_json("[1,\r\n\r\n2\n\n,\n3]", &mut context) {
+            Ok(result) => assert_eq!(result, node),
             Err(e) => panic!("{}", e),
         }
     }
+
+    #[test]
+    fn parse_array_records_where_each_item_was() {
+        match parse_json("[1, [true]]", &mut ParseContext::default()) {
+            Ok(Node::Array { items }) => assert_eq!(
+                items
+                    .iter()
+                    .map(|item| item.span.clone())
+                    .collect::<Vec<_>>(),
+                vec![Some(1..2), Some(4..10)]
+            ),
+            result => panic!("{:?}", result),
+        }
+    }
 }
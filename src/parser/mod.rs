@@ -1,21 +1,22 @@
 use std::fmt;
+use std::ops::Range;
 
+use crate::cst::{SyntaxElement, SyntaxKind, SyntaxNode};
 use crate::formatter::errors::FormatterError;
-use crate::tokenizer::Token;
-
-mod utils;
-use utils::remove_whitespace;
+use crate::formatter::FormatConfig;
 
 mod object;
-use object::parse_object;
+pub use object::duplicates::DuplicateKeyPolicy;
+pub(crate) use object::parse_member;
+use object::{parse_object, parse_pairs};
 
 mod array;
-use array::parse_array;
+use array::{parse_array, parse_items};
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum Node {
-    Object { pairs: Vec<Box<Node>> },
-    Array { items: Vec<Box<Node>> },
+    Object { pairs: Vec<Item> },
+    Array { items: Vec<Item> },
     Pair { key: Box<Node>, value: Box<Node> },
     Literal { literal: String },
     Number { value: f64 },
@@ -24,11 +25,36 @@ pub enum Node {
     Null,
 }
 
-type JumpNode = (usize, Node);
-fn wrap_in_jump(node: Node) -> JumpNode {
-    (1, node)
+/// A member of an object (a `Node::Pair`) or an item of an array, with the layout around it
+/// in the input that is worth keeping.
+#[derive(Debug, Clone)]
+pub struct Item {
+    pub node: Node,
+    /// The user left a blank line before it, kept when formatting asks for it.
+    pub blank_line_before: bool,
+    /// The bytes of the input it was parsed from, key included for a member. Items that
+    /// weren't in the input have none.
+    pub span: Option<Range<usize>>,
+}
+
+impl From<Node> for Item {
+    fn from(node: Node) -> Item {
+        Item {
+            node,
+            blank_line_before: false,
+            span: None,
+        }
+    }
+}
+
+/// Items are equal when they print the same, wherever in the input they came from.
+impl PartialEq for Item {
+    fn eq(&self, other: &Item) -> bool {
+        self.node == other.node && self.blank_line_before == other.blank_line_before
+    }
 }
 
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Debug, PartialEq)]
 pub struct AST {
     pub root: Node,
@@ -40,149 +66,202 @@ impl fmt::Display for AST {
     }
 }
 
-pub fn parse(tokens: Vec<Token>) -> Result<AST, FormatterError> {
-    let tokens = remove_whitespace(tokens);
-    let (_, node) = parse_node(&tokens, 0)?;
-
-    Ok(AST { root: node })
+/// Options and collected warnings threaded through a single parse.
+#[derive(Debug, Default)]
+pub struct ParseContext {
+    pub duplicate_keys: DuplicateKeyPolicy,
+    pub keep_blank_lines: bool,
+    pub warnings: Vec<FormatterError>,
 }
 
-fn parse_node(tokens: &Vec<Token>, position: usize) -> Result<JumpNode, FormatterError> {
-    if let Some(value) = tokens.get(position) {
-        match value {
-            Token::OpenBrace(_) => Ok(parse_object(&tokens, position + 1)?),
-            Token::OpenSquareBraket(_) => Ok(parse_array(&tokens, position + 1)?),
-            Token::True(_, _) => Ok(wrap_in_jump(Node::True)),
-            Token::False(_, _) => Ok(wrap_in_jump(Node::False)),
-            Token::Null(_, _) => Ok(wrap_in_jump(Node::Null)),
-            Token::StringLiteral(_, literal) => Ok(wrap_in_jump(Node::Literal {
-                literal: literal.to_string(),
-            })),
-            Token::Number(_, literal) => Ok(wrap_in_jump(Node::Number {
-                value: literal.parse::<f64>().unwrap(),
-            })),
-            _ => return Err(FormatterError::ExpectedMoreCharacters(11111111)),
+impl ParseContext {
+    pub fn new(duplicate_keys: DuplicateKeyPolicy) -> ParseContext {
+        ParseContext {
+            duplicate_keys,
+            keep_blank_lines: false,
+            warnings: vec![],
         }
-    } else {
-        Err(FormatterError::ExpectedMoreTokens())
+    }
+
+    /// The parse `config` asks for.
+    pub(crate) fn for_config(config: &FormatConfig) -> ParseContext {
+        ParseContext {
+            keep_blank_lines: config.keep_blank_lines,
+            ..ParseContext::new(config.duplicate_keys)
+        }
+    }
+}
+
+/// Builds the AST of a `Document` from the syntax tree, so the formatter accepts exactly the
+/// documents the syntax tree does.
+pub fn parse(document: &SyntaxNode, context: &mut ParseContext) -> Result<AST, FormatterError> {
+    let root = document
+        .value()
+        .ok_or(FormatterError::ExpectedMoreTokens())?;
+    Ok(AST {
+        root: parse_node(root, context)?,
+    })
+}
+
+/// Builds the node for an object or array of the syntax tree.
+pub(crate) fn parse_container(
+    node: &SyntaxNode,
+    context: &mut ParseContext,
+) -> Result<Node, FormatterError> {
+    match node.kind() {
+        SyntaxKind::Object => parse_object(node, context),
+        SyntaxKind::Array => parse_array(node, context),
+        _ => Err(FormatterError::UnexpectedToken(node.offset())),
+    }
+}
+
+/// The members or items of `container` that overlap `range`, parsed as if they were all it
+/// held.
+pub(crate) fn parse_entries(
+    container: &SyntaxNode,
+    range: &Range<usize>,
+    context: &mut ParseContext,
+) -> Result<Vec<Item>, FormatterError> {
+    let touched = entries(container, context)
+        .filter(|(element, _)| element.offset() < range.end && element.end() > range.start)
+        .collect::<Vec<_>>();
+    match container.kind() {
+        SyntaxKind::Object => parse_pairs(touched.into_iter(), context),
+        _ => parse_items(touched.into_iter(), context),
+    }
+}
+
+/// Builds the node for a value of the syntax tree.
+fn parse_node(element: &SyntaxElement, context: &mut ParseContext) -> Result<Node, FormatterError> {
+    let token = match element {
+        SyntaxElement::Node(node) => return parse_container(node, context),
+        SyntaxElement::Token(token) => token,
+    };
+    let text = token.text();
+
+    match token.kind() {
+        SyntaxKind::True => Ok(Node::True),
+        SyntaxKind::False => Ok(Node::False),
+        SyntaxKind::Null => Ok(Node::Null),
+        SyntaxKind::StringLiteral => Ok(Node::Literal {
+            literal: text[1..text.len() - 1].to_owned(),
+        }),
+        SyntaxKind::Number => match text.parse::<f64>() {
+            Ok(value) => Ok(Node::Number { value }),
+            Err(_) => Err(FormatterError::InvalidNumber(token.offset())),
+        },
+        _ => Err(FormatterError::UnexpectedToken(token.offset())),
     }
 }
 
+/// The members or items of an object or array, each with whether the user left a blank line
+/// between it and the comma before it (only looked for when `keep_blank_lines` is set).
+fn entries<'a>(
+    container: &'a SyntaxNode,
+    context: &ParseContext,
+) -> impl Iterator<Item = (&'a SyntaxElement, bool)> {
+    let keep_blank_lines = context.keep_blank_lines;
+    let children = container.children();
+
+    children
+        .iter()
+        .enumerate()
+        .filter_map(move |(index, child)| {
+            if !(child.is_value() || child.kind() == SyntaxKind::Member) {
+                return None;
+            }
+            let blank_line_before = keep_blank_lines
+                && index >= 2
+                && children[index - 2].kind() == SyntaxKind::Comma
+                && match &children[index - 1] {
+                    SyntaxElement::Token(space) => space.text().matches('\n').count() > 1,
+                    SyntaxElement::Node(_) => false,
+                };
+            Some((child, blank_line_before))
+        })
+}
+
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::*;
+    use crate::cst;
+
+    /// Parses `json` with `context` and returns its root node.
+    pub(crate) fn parse_json(
+        json: &str,
+        context: &mut ParseContext,
+    ) -> Result<Node, FormatterError> {
+        Ok(parse(&cst::parse(json)?, context)?.root)
+    }
 
     #[test]
     fn r#true() {
-        let true_token = Token::True(0, "true");
-        let ast = AST { root: Node::True };
-
-        match parse(vec![true_token]) {
-            Ok(result) => assert_eq!(result, ast),
+        match parse_json("true", &mut ParseContext::default()) {
+            Ok(result) => assert_eq!(result, Node::True),
             Err(e) => panic!("{}", e),
         }
     }
 
     #[test]
     fn r#false() {
-        let false_token = Token::False(0, "false");
-        let ast = AST { root: Node::False };
-
-        match parse(vec![false_token]) {
-            Ok(result) => assert_eq!(result, ast),
+        match parse_json("false", &mut ParseContext::default()) {
+            Ok(result) => assert_eq!(result, Node::False),
             Err(e) => panic!("{}", e),
         }
     }
 
     #[test]
     fn null() {
-        let null = Token::Null(0, "null");
-        let ast = AST { root: Node::Null };
-
-        match parse(vec![null]) {
-            Ok(result) => assert_eq!(result, ast),
+        match parse_json("null", &mut ParseContext::default()) {
+            Ok(result) => assert_eq!(result, Node::Null),
             Err(e) => panic!("{}", e),
         }
     }
 
     #[test]
     fn string() {
-        let input = String::from("test mc test");
-        let string = Token::StringLiteral(0, input.to_string());
-        let ast = AST {
-            root: Node::Literal { literal: input },
+        let node = Node::Literal {
+            literal: String::from("test mc \\\"test"),
         };
 
-        match parse(vec![string]) {
-            Ok(result) => assert_eq!(result, ast),
+        match parse_json(r#""test mc \"test""#, &mut ParseContext::default()) {
+            Ok(result) => assert_eq!(result, node),
             Err(e) => panic!("{}", e),
         }
     }
 
     #[test]
     fn number() {
-        let number = Token::Number(0, String::from("34.4e3"));
-        let ast = AST {
-            root: Node::Number { value: 34400.0 },
-        };
-
-        match parse(vec![number]) {
-            Ok(result) => assert_eq!(result, ast),
+        match parse_json("34.4e3", &mut ParseContext::default()) {
+            Ok(result) => assert_eq!(result, Node::Number { value: 34400.0 }),
             Err(e) => panic!("{}", e),
         }
     }
 
     #[test]
     fn array() {
-        // let json = r#" [ false, 23.23, true ]  "#;
-
-        let whitespace = Token::WhiteSpace(0, ' ');
-        let open_bracket = Token::OpenSquareBraket(1);
-        let whitespace2 = Token::WhiteSpace(2, ' ');
-        let false_token = Token::False(3, "false");
-        let comma = Token::Comma(8);
-        let whitespace3 = Token::WhiteSpace(9, ' ');
-        let number = Token::Number(10, String::from("23.23"));
-        let comma2 = Token::Comma(15);
-
-        let whitespace4 = Token::WhiteSpace(16, ' ');
-
-        let true_token = Token::True(17, "true");
-        let whitespace5 = Token::WhiteSpace(21, ' ');
-        let close_bracket = Token::CloseSquareBraket(22);
-        let whitespace6 = Token::WhiteSpace(23, ' ');
-        let whitespace7 = Token::WhiteSpace(24, ' ');
-
-        let tokens = vec![
-            whitespace,
-            open_bracket,
-            whitespace2,
-            false_token,
-            comma,
-            whitespace3,
-            number,
-            comma2,
-            whitespace4,
-            true_token,
-            whitespace5,
-            close_bracket,
-            whitespace6,
-            whitespace7,
-        ];
-
-        let ast = AST {
-            root: Node::Array {
-                items: vec![
-                    Box::new(Node::False),
-                    Box::new(Node::Number { value: 23.23_f64 }),
-                    Box::new(Node::True),
-                ],
-            },
+        let node = Node::Array {
+            items: vec![
+                Item::from(Node::False),
+                Item::from(Node::Number { value: 23.23_f64 }),
+                Item::from(Node::True),
+            ],
         };
 
-        match parse(tokens) {
-            Ok(result) => assert_eq!(result, ast),
+        match parse_json(" [ false, 23.23, true ]  ", &mut ParseContext::default()) {
+            Ok(result) => assert_eq!(result, node),
             Err(e) => panic!("{}", e),
         }
     }
+
+    #[test]
+    fn rejects_what_the_syntax_tree_rejects() {
+        for json in ["[1 2]", "[1,]", "[1,,2]", "[1] 2", "{\"a\": 1,}", ""] {
+            assert!(
+                parse_json(json, &mut ParseContext::default()).is_err(),
+                "{}",
+                json
+            );
+        }
+    }
 }
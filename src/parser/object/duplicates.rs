@@ -0,0 +1,163 @@
+use std::collections::HashMap;
+
+use crate::value::unescape;
+
+use super::super::Node;
+
+/// What to do when an object contains the same key more than once.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum DuplicateKeyPolicy {
+    /// Fail with `FormatterError::DuplicateKeyEntry` (the default).
+    #[default]
+    Error,
+    /// Keep the first value seen for the key.
+    KeepFirst,
+    /// Keep the last value seen for the key, in the position of the first (JavaScript semantics).
+    KeepLast,
+    /// Keep every entry and record a `DuplicateKeyEntry` warning for each repeat.
+    KeepAll,
+    /// Merge duplicate objects key by key, otherwise behave like `KeepLast`.
+    Merge,
+}
+
+pub fn merge_nodes(existing: Node, incoming: Node) -> Node {
+    match (existing, incoming) {
+        (
+            Node::Object { mut pairs },
+            Node::Object {
+                pairs: incoming_pairs,
+            },
+        ) => {
+            let mut indices: HashMap<String, usize> = pairs
+                .iter()
+                .enumerate()
+                .filter_map(|(index, pair)| key_of(&pair.node).map(|key| (key, index)))
+                .collect();
+
+            for pair in incoming_pairs {
+                let key = key_of(&pair.node);
+                match key.as_ref().and_then(|key| indices.get(key)) {
+                    Some(&index) => {
+                        let existing_pair = std::mem::replace(&mut pairs[index].node, Node::Null);
+                        pairs[index].node = merge_pairs(existing_pair, pair.node);
+                    }
+                    None => {
+                        if let Some(key) = key {
+                            indices.insert(key, pairs.len());
+                        }
+                        pairs.push(pair);
+                    }
+                }
+            }
+
+            Node::Object { pairs }
+        }
+        (_, incoming) => incoming,
+    }
+}
+
+/// Merges the values of two members with the same key, keeping the first one's key.
+pub fn merge_pairs(existing: Node, incoming: Node) -> Node {
+    match (existing, incoming) {
+        (
+            Node::Pair { key, value },
+            Node::Pair {
+                value: incoming_value,
+                ..
+            },
+        ) => Node::Pair {
+            key,
+            value: Box::new(merge_nodes(*value, *incoming_value)),
+        },
+        (_, incoming) => incoming,
+    }
+}
+
+fn key_of(pair: &Node) -> Option<String> {
+    match pair {
+        Node::Pair { key, .. } => match &**key {
+            Node::Literal { literal } => Some(unescape(literal)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::Item;
+    use super::*;
+
+    fn pair(key: &str, value: Node) -> Item {
+        Item::from(Node::Pair {
+            key: Box::new(Node::Literal {
+                literal: String::from(key),
+            }),
+            value: Box::new(value),
+        })
+    }
+
+    #[test]
+    fn merge_nodes_combines_nested_objects() {
+        let existing = Node::Object {
+            pairs: vec![
+                pair("a", Node::True),
+                pair(
+                    "nested",
+                    Node::Object {
+                        pairs: vec![pair("x", Node::Null)],
+                    },
+                ),
+            ],
+        };
+        let incoming = Node::Object {
+            pairs: vec![
+                pair(
+                    "nested",
+                    Node::Object {
+                        pairs: vec![pair("x", Node::False), pair("y", Node::True)],
+                    },
+                ),
+                pair("b", Node::False),
+            ],
+        };
+
+        let merged = Node::Object {
+            pairs: vec![
+                pair("a", Node::True),
+                pair(
+                    "nested",
+                    Node::Object {
+                        pairs: vec![pair("x", Node::False), pair("y", Node::True)],
+                    },
+                ),
+                pair("b", Node::False),
+            ],
+        };
+
+        assert_eq!(merge_nodes(existing, incoming), merged)
+    }
+
+    #[test]
+    fn merge_nodes_prefers_the_incoming_value_for_non_objects() {
+        let existing = Node::Object { pairs: vec![] };
+        assert_eq!(merge_nodes(existing, Node::True), Node::True)
+    }
+
+    #[test]
+    fn merge_nodes_matches_escaped_keys() {
+        let existing = Node::Object {
+            pairs: vec![pair("a", Node::True)],
+        };
+        let incoming = Node::Object {
+            pairs: vec![pair("\\u0061", Node::False)],
+        };
+
+        assert_eq!(
+            merge_nodes(existing, incoming),
+            Node::Object {
+                pairs: vec![pair("a", Node::False)],
+            }
+        )
+    }
+}
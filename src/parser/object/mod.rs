@@ -1,257 +1,277 @@
+use std::collections::HashMap;
+
+use crate::cst::{SyntaxElement, SyntaxKind, SyntaxNode};
 use crate::formatter::errors::FormatterError;
-use crate::tokenizer::Token;
-
-use super::parse_node;
-use super::JumpNode;
-use super::Node;
-
-mod literal;
-use literal::parse_literal;
-
-pub fn parse_object(tokens: &Vec<Token>, position: usize) -> Result<JumpNode, FormatterError> {
-    let mut keys: Vec<String> = vec![];
-    let mut pairs = vec![];
-    let mut jump = position;
-
-    loop {
-        if let Some(token) = tokens.get(jump) {
-            match token {
-                Token::CloseBrace(_) => {
-                    let movement_from_braces = 2;
-                    let net_movement = (jump - position) + movement_from_braces;
-                    return Ok((net_movement, Node::Object { pairs }));
-                }
-                Token::Comma(_) => {
-                    jump = jump + 1;
-                }
-                _ => {
-                    let (movement, key) = parse_literal(&tokens, jump)?;
-                    jump = jump + movement;
-
-                    // Ensure there is a colon
-                    if let Some(token) = tokens.get(jump) {
-                        match token {
-                            Token::Colon(_) => {
-                                jump = jump + 1;
-                            }
-                            _ => return Err(FormatterError::ExpectedColonInKeyValuePair()),
-                        }
-                    }
-
-                    let (movement, value) = parse_node(&tokens, jump)?;
-                    jump = jump + movement;
-
-                    // Check for duplicate keys
-                    if let Node::Literal { literal } = &key {
-                        if keys.contains(literal) {
-                            return Err(FormatterError::DuplicateKeyEntry(literal.to_string()));
-                        }
-                        keys.push(literal.to_string())
-                    }
-
-                    pairs.push(Box::new(Node::Pair {
-                        key: Box::new(key),
-                        value: Box::new(value),
-                    }))
-                }
+use crate::value::unescape;
+
+use super::{entries, parse_node, Item, Node, ParseContext};
+
+pub mod duplicates;
+use duplicates::{merge_pairs, DuplicateKeyPolicy};
+
+pub fn parse_object(
+    object: &SyntaxNode,
+    context: &mut ParseContext,
+) -> Result<Node, FormatterError> {
+    let pairs = parse_pairs(entries(object, context), context)?;
+    Ok(Node::Object { pairs })
+}
+
+/// Parses the `entries` of an object, applying the duplicate-key policy to them.
+pub fn parse_pairs<'a>(
+    entries: impl Iterator<Item = (&'a SyntaxElement, bool)>,
+    context: &mut ParseContext,
+) -> Result<Vec<Item>, FormatterError> {
+    let mut indices: HashMap<String, usize> = HashMap::new();
+    let mut pairs: Vec<Item> = vec![];
+
+    for (element, blank_line_before) in entries {
+        let member = match element {
+            SyntaxElement::Node(member) => member,
+            _ => return Err(FormatterError::ExpectedStringLiteral(element.offset())),
+        };
+        let (literal, pair) = parse_member(member, context)?;
+        let span = Some(member.offset()..member.end());
+        // Check for duplicate keys
+        let index = match indices.get(&literal) {
+            Some(&index) => index,
+            None => {
+                indices.insert(literal, pairs.len());
+                pairs.push(Item {
+                    node: pair,
+                    blank_line_before,
+                    span,
+                });
+                continue;
+            }
+        };
+
+        match context.duplicate_keys {
+            DuplicateKeyPolicy::Error => {
+                return Err(FormatterError::DuplicateKeyEntry(literal));
+            }
+            DuplicateKeyPolicy::KeepFirst => {}
+            DuplicateKeyPolicy::KeepLast => {
+                pairs[index].node = pair;
+                pairs[index].span = span;
+            }
+            DuplicateKeyPolicy::KeepAll => {
+                context
+                    .warnings
+                    .push(FormatterError::DuplicateKeyEntry(literal));
+                pairs.push(Item {
+                    node: pair,
+                    blank_line_before,
+                    span,
+                });
+            }
+            DuplicateKeyPolicy::Merge => {
+                let existing = std::mem::replace(&mut pairs[index].node, Node::Null);
+                pairs[index].node = merge_pairs(existing, pair);
             }
-        } else {
-            return Err(FormatterError::ExpectedMoreCharacters(99999999));
         }
     }
+    Ok(pairs)
+}
+
+/// Parses a `Member` into a `Node::Pair`, along with its unescaped key.
+pub fn parse_member(
+    member: &SyntaxNode,
+    context: &mut ParseContext,
+) -> Result<(String, Node), FormatterError> {
+    let (key, value) = match (member.kind(), member.children().first(), member.value()) {
+        (SyntaxKind::Member, Some(key), Some(value)) if key.kind() == SyntaxKind::StringLiteral => {
+            (parse_node(key, context)?, parse_node(value, context)?)
+        }
+        _ => return Err(FormatterError::ExpectedStringLiteral(member.offset())),
+    };
+
+    // Compare keys by what they say, so `"a"` and `"\u0061"` are the same key.
+    let literal = match &key {
+        Node::Literal { literal } => unescape(literal),
+        _ => return Err(FormatterError::ExpectedStringLiteral(member.offset())),
+    };
+    let pair = Node::Pair {
+        key: Box::new(key),
+        value: Box::new(value),
+    };
+    Ok((literal, pair))
 }
 
 #[cfg(test)]
 mod tests {
+    use super::super::tests::parse_json;
     use super::*;
 
     #[test]
     fn parse_object_empty() {
-        let open_brace = Token::OpenBrace(0);
-        let close_brace = Token::CloseBrace(1);
         let node = Node::Object { pairs: vec![] };
 
-        match parse_object(&vec![open_brace, close_brace], 1) {
-            Ok((_, result)) => assert_eq!(result, node),
+        match parse_json("{}", &mut ParseContext::default()) {
+            Ok(result) => assert_eq!(result, node),
             Err(e) => panic!("{}", e),
         }
     }
 
     #[test]
     fn parse_object_one_pair() {
-        // let json = r#" { "w in" : true }  "#;
-
-        let open_brace = Token::OpenBrace(1);
-        let win = Token::StringLiteral(3, String::from("w in"));
-        let colon = Token::Colon(10);
-        let true_token = Token::True(12, "true");
-        let close_brace = Token::CloseBrace(17);
-
-        let tokens = vec![open_brace, win, colon, true_token, close_brace];
-
         let node = Node::Object {
-            pairs: vec![Box::new(Node::Pair {
-                key: Box::new(Node::Literal {
-                    literal: String::from("w in"),
-                }),
-                value: Box::new(Node::True),
-            })],
+            pairs: vec![pair("w in", Node::True)],
         };
 
-        match parse_object(&tokens, 1) {
-            Ok((_, result)) => assert_eq!(result, node),
+        match parse_json(r#" { "w in" : true }  "#, &mut ParseContext::default()) {
+            Ok(result) => assert_eq!(result, node),
             Err(e) => panic!("{}", e),
         }
     }
 
     #[test]
-    #[should_panic(expected = "Key value pairs must be delimited by colons (:).")]
+    #[should_panic(
+        expected = "Key value pairs must be delimited by colons (:), expected one at position (10)."
+    )]
     fn parse_object_one_pair_no_colon() {
-        // let json = r#" { "w in" } true }  "#;
-
-        let open_brace = Token::OpenBrace(1);
-        let win = Token::StringLiteral(3, String::from("w in"));
-        let bad_char = Token::CloseBrace(10);
-        let true_token = Token::True(12, "true");
-        let close_brace = Token::CloseBrace(17);
+        match parse_json(r#" { "w in" } true }  "#, &mut ParseContext::default()) {
+            Ok(_) => (),
+            Err(e) => panic!("{}", e),
+        }
+    }
 
-        let tokens = vec![open_brace, win, bad_char, true_token, close_brace];
+    #[test]
+    #[should_panic(expected = "Expected string literal at position (3).")]
+    fn parse_object_key_must_be_a_literal() {
+        match parse_json(r#" { false : true }  "#, &mut ParseContext::default()) {
+            Ok(_) => (),
+            Err(e) => panic!("{}", e),
+        }
+    }
 
+    #[test]
+    fn parse_object_with_n_pair() {
         let node = Node::Object {
-            pairs: vec![Box::new(Node::Pair {
-                key: Box::new(Node::Literal {
-                    literal: String::from("w in"),
-                }),
-                value: Box::new(Node::True),
-            })],
+            pairs: vec![pair("w in", Node::True), pair("wow", Node::False)],
         };
 
-        match parse_object(&tokens, 1) {
-            Ok((_, result)) => assert_eq!(result, node),
+        match parse_json(
+            r#"{"w in": true, "wow": false}"#,
+            &mut ParseContext::default(),
+        ) {
+            Ok(result) => assert_eq!(result, node),
             Err(e) => panic!("{}", e),
         }
     }
 
     #[test]
-    #[should_panic(expected = "Expected string literal at position (1).")]
-    fn parse_object_key_must_be_a_literal() {
-        // let json = r#" { false : true }  "#;
+    #[should_panic(expected = "Duplicate key ('w in') entry.")]
+    fn parse_object_with_duplicate_keys_should_fail() {
+        match parse_json(
+            r#"{"w in": true, "w in": true}"#,
+            &mut ParseContext::default(),
+        ) {
+            Ok(_) => (),
+            Err(e) => panic!("{}", e),
+        }
+    }
 
-        let open_brace = Token::OpenBrace(1);
-        let false_token = Token::False(12, "false");
-        let colon = Token::Colon(10);
-        let true_token = Token::True(12, "true");
-        let close_brace = Token::CloseBrace(17);
+    const DUPLICATE_KEYS: &str = r#"{ "a": true, "b": null, "a": false }"#;
 
-        let tokens = vec![open_brace, false_token, colon, true_token, close_brace];
+    fn pair(key: &str, value: Node) -> Item {
+        Item::from(Node::Pair {
+            key: Box::new(Node::Literal {
+                literal: String::from(key),
+            }),
+            value: Box::new(value),
+        })
+    }
 
+    #[test]
+    fn parse_object_with_duplicate_keys_keep_first() {
+        let mut context = ParseContext::new(DuplicateKeyPolicy::KeepFirst);
         let node = Node::Object {
-            pairs: vec![Box::new(Node::Pair {
-                key: Box::new(Node::Literal {
-                    literal: String::from("w in"),
-                }),
-                value: Box::new(Node::True),
-            })],
+            pairs: vec![pair("a", Node::True), pair("b", Node::Null)],
         };
 
-        match parse_object(&tokens, 1) {
-            Ok((_, result)) => assert_eq!(result, node),
+        match parse_json(DUPLICATE_KEYS, &mut context) {
+            Ok(result) => assert_eq!(result, node),
             Err(e) => panic!("{}", e),
         }
     }
 
     #[test]
-    fn parse_object_with_n_pair() {
-        let open_brace = Token::OpenBrace(1);
-        let win = Token::StringLiteral(3, String::from("w in"));
-        let colon = Token::Colon(10);
-        let true_token = Token::True(12, "true");
-        let comma = Token::Comma(18);
-        let win2 = Token::StringLiteral(19, String::from("wow"));
-        let colon2 = Token::Colon(39);
-        let false_token = Token::False(45, "false");
-        let close_brace = Token::CloseBrace(56);
-
-        let tokens = vec![
-            open_brace,
-            win,
-            colon,
-            true_token,
-            comma,
-            win2,
-            colon2,
-            false_token,
-            close_brace,
-        ];
-
+    fn parse_object_with_duplicate_keys_keep_last() {
+        let mut context = ParseContext::new(DuplicateKeyPolicy::KeepLast);
         let node = Node::Object {
-            pairs: vec![
-                Box::new(Node::Pair {
-                    key: Box::new(Node::Literal {
-                        literal: String::from("w in"),
-                    }),
-                    value: Box::new(Node::True),
-                }),
-                Box::new(Node::Pair {
-                    key: Box::new(Node::Literal {
-                        literal: String::from("wow"),
-                    }),
-                    value: Box::new(Node::False),
-                }),
-            ],
+            pairs: vec![pair("a", Node::False), pair("b", Node::Null)],
         };
 
-        match parse_object(&tokens, 1) {
-            Ok((_, result)) => assert_eq!(result, node),
+        match parse_json(DUPLICATE_KEYS, &mut context) {
+            Ok(result) => assert_eq!(result, node),
             Err(e) => panic!("{}", e),
         }
     }
 
     #[test]
-    #[should_panic(expected = "Duplicate key ('w in') entry.")]
-    fn parse_object_with_duplicate_keys_should_fail() {
-        let open_brace = Token::OpenBrace(1);
-        let win = Token::StringLiteral(3, String::from("w in"));
-        let colon = Token::Colon(10);
-        let true_token = Token::True(12, "true");
-        let comma = Token::Comma(18);
-        let win2 = Token::StringLiteral(19, String::from("w in"));
-        let colon2 = Token::Colon(39);
-        let false_token = Token::True(45, "true");
-        let close_brace = Token::CloseBrace(56);
-
-        let tokens = vec![
-            open_brace,
-            win,
-            colon,
-            true_token,
-            comma,
-            win2,
-            colon2,
-            false_token,
-            close_brace,
-        ];
+    fn parse_object_keep_last_records_where_the_kept_member_was() {
+        let mut context = ParseContext::new(DuplicateKeyPolicy::KeepLast);
+
+        match parse_json(DUPLICATE_KEYS, &mut context) {
+            Ok(Node::Object { pairs }) => assert_eq!(
+                pairs
+                    .iter()
+                    .map(|pair| pair.span.clone())
+                    .collect::<Vec<_>>(),
+                vec![Some(24..34), Some(13..22)]
+            ),
+            result => panic!("{:?}", result),
+        }
+    }
 
+    #[test]
+    fn parse_object_with_duplicate_keys_keep_all_warns() {
+        let mut context = ParseContext::new(DuplicateKeyPolicy::KeepAll);
         let node = Node::Object {
             pairs: vec![
-                Box::new(Node::Pair {
-                    key: Box::new(Node::Literal {
-                        literal: String::from("w in"),
-                    }),
-                    value: Box::new(Node::True),
-                }),
-                Box::new(Node::Pair {
-                    key: Box::new(Node::Literal {
-                        literal: String::from("w in"),
-                    }),
-                    value: Box::new(Node::True),
-                }),
+                pair("a", Node::True),
+                pair("b", Node::Null),
+                pair("a", Node::False),
             ],
         };
 
-        match parse_object(&tokens, 1) {
-            Ok((_, result)) => assert_eq!(result, node),
+        match parse_json(DUPLICATE_KEYS, &mut context) {
+            Ok(result) => assert_eq!(result, node),
             Err(e) => panic!("{}", e),
         }
+        assert_eq!(
+            context.warnings,
+            vec![FormatterError::DuplicateKeyEntry(String::from("a"))]
+        );
+    }
+
+    #[test]
+    fn parse_object_with_duplicate_keys_merge_takes_the_last_scalar() {
+        let mut context = ParseContext::new(DuplicateKeyPolicy::Merge);
+        let node = Node::Object {
+            pairs: vec![pair("a", Node::False), pair("b", Node::Null)],
+        };
+
+        match parse_json(DUPLICATE_KEYS, &mut context) {
+            Ok(result) => assert_eq!(result, node),
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    #[test]
+    fn parse_object_compares_unescaped_keys() {
+        let json = r#"{"a": 1, "\u0061": 2}"#;
+
+        assert_eq!(
+            parse_json(json, &mut ParseContext::default()).unwrap_err(),
+            FormatterError::DuplicateKeyEntry(String::from("a"))
+        );
+
+        let mut context = ParseContext::new(DuplicateKeyPolicy::KeepLast);
+        let node = Node::Object {
+            pairs: vec![pair("\\u0061", Node::Number { value: 2.0 })],
+        };
+        assert_eq!(parse_json(json, &mut context).unwrap(), node);
     }
 }
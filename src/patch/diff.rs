@@ -0,0 +1,139 @@
+use crate::diff::walk::{walk, Differences};
+use crate::value::{to_pointer, Value};
+
+use super::PatchOperation;
+
+/// A patch that turns `from` into `to`.
+///
+/// Objects are compared member by member and arrays item by item after trimming their common
+/// prefix and suffix, so a single insertion or removal becomes a single operation. Anything
+/// else that differs is replaced whole.
+pub fn diff_to_patch(from: &Value, to: &Value) -> Vec<PatchOperation> {
+    let mut patch = vec![];
+    walk(from, to, false, &mut patch);
+    patch
+}
+
+impl Differences for Vec<PatchOperation> {
+    fn added(&mut self, path: &[String], value: &Value) {
+        self.push(PatchOperation::Add {
+            path: to_pointer(path.iter()),
+            value: value.clone(),
+        });
+    }
+
+    fn removed(&mut self, path: &[String], _value: &Value) {
+        self.push(PatchOperation::Remove {
+            path: to_pointer(path.iter()),
+        });
+    }
+
+    fn changed(&mut self, path: &[String], _from: &Value, to: &Value) {
+        self.push(PatchOperation::Replace {
+            path: to_pointer(path.iter()),
+            value: to.clone(),
+        });
+    }
+
+    fn removed_items(&mut self, path: &mut Vec<String>, start: usize, items: &[Value]) {
+        // From the last, so the indexes of the items still to go don't shift.
+        for (offset, item) in items.iter().enumerate().rev() {
+            path.push((start + offset).to_string());
+            self.removed(path, item);
+            path.pop();
+        }
+    }
+
+    fn added_items(&mut self, path: &mut Vec<String>, start: usize, items: &[Value], at_end: bool) {
+        for (offset, item) in items.iter().enumerate() {
+            // Nothing follows the inserted items, so they can be appended.
+            path.push(match at_end {
+                true => String::from("-"),
+                false => (start + offset).to_string(),
+            });
+            self.added(path, item);
+            path.pop();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::patch::apply_patch;
+    use crate::value::values_equal;
+
+    fn round_trip(from: &str, to: &str) -> Vec<PatchOperation> {
+        let from = crate::parse(from).unwrap();
+        let to = crate::parse(to).unwrap();
+        let patch = diff_to_patch(&from, &to);
+
+        let mut patched = from.clone();
+        apply_patch(&mut patched, &patch).unwrap();
+        assert!(values_equal(&patched, &to), "{} != {}", patched, to);
+        patch
+    }
+
+    #[test]
+    fn equal_documents_need_no_patch() {
+        assert_eq!(
+            round_trip(r#"{"a": 1, "b": 2}"#, r#"{"b": 2, "a": 1}"#),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn objects_diff_by_member() {
+        assert_eq!(
+            round_trip(
+                r#"{"a": 1, "b": {"c": 2}}"#,
+                r#"{"b": {"c": 3}, "d": null}"#
+            ),
+            vec![
+                PatchOperation::Remove {
+                    path: String::from("/a")
+                },
+                PatchOperation::Replace {
+                    path: String::from("/b/c"),
+                    value: Value::Number(3.0)
+                },
+                PatchOperation::Add {
+                    path: String::from("/d"),
+                    value: Value::Null
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn single_array_changes_need_one_operation() {
+        assert_eq!(
+            round_trip("[1, 2, 3]", "[1, 9, 2, 3]"),
+            vec![PatchOperation::Add {
+                path: String::from("/1"),
+                value: Value::Number(9.0)
+            }]
+        );
+        assert_eq!(
+            round_trip("[1, 2, 3]", "[1, 3]"),
+            vec![PatchOperation::Remove {
+                path: String::from("/1")
+            }]
+        );
+        assert_eq!(
+            round_trip("[1]", "[1, 2]"),
+            vec![PatchOperation::Add {
+                path: String::from("/-"),
+                value: Value::Number(2.0)
+            }]
+        );
+    }
+
+    #[test]
+    fn arrays_and_types_round_trip() {
+        round_trip("[1, 2, 3, 4]", "[5]");
+        round_trip("[1, [2, 3], 4]", "[0, [2], 4, 5, 6]");
+        round_trip(r#"{"a/b": [1]}"#, r#"{"a/b": "x", "~": true}"#);
+        round_trip("[]", r#"{"a": []}"#);
+    }
+}
@@ -0,0 +1,134 @@
+use crate::formatter::errors::FormatterError;
+use crate::formatter::FormatConfig;
+use crate::value::{values_equal, Value};
+
+/// Applies an RFC 7396 merge patch: object members are merged recursively, `null` removes a
+/// member and anything else replaces the target outright.
+pub fn merge_patch(target: &mut Value, patch: &Value) {
+    let members = match patch {
+        Value::Object(members) => members,
+        _ => {
+            *target = patch.clone();
+            return;
+        }
+    };
+
+    if !matches!(target, Value::Object(_)) {
+        *target = Value::Object(vec![]);
+    }
+    if let Value::Object(pairs) = target {
+        for (key, value) in members {
+            let existing = pairs.iter().position(|(name, _)| name == key);
+            match (existing, value) {
+                (Some(index), Value::Null) => {
+                    pairs.remove(index);
+                }
+                (None, Value::Null) => {}
+                (Some(index), value) => merge_patch(&mut pairs[index].1, value),
+                (None, value) => {
+                    let mut merged = Value::Null;
+                    merge_patch(&mut merged, value);
+                    pairs.push((key.clone(), merged));
+                }
+            }
+        }
+    }
+}
+
+/// The merge patch that turns `from` into `to`.
+///
+/// Merge patches cannot set a member to `null`, so `null` values in `to` come out as removals.
+pub fn create_merge_patch(from: &Value, to: &Value) -> Value {
+    let (from, to) = match (from, to) {
+        (Value::Object(from), Value::Object(to)) => (from, to),
+        _ => return to.clone(),
+    };
+
+    let mut patch = vec![];
+    for (key, value) in from {
+        match to.iter().find(|(name, _)| name == key) {
+            Some((_, other)) if values_equal(value, other) => {}
+            Some((_, other)) => patch.push((key.clone(), create_merge_patch(value, other))),
+            None => patch.push((key.clone(), Value::Null)),
+        }
+    }
+    for (key, value) in to {
+        if !from.iter().any(|(name, _)| name == key) {
+            patch.push((key.clone(), value.clone()));
+        }
+    }
+    Value::Object(patch)
+}
+
+/// Parses `base` and the merge patch in `overlay`, merges them and formats the result.
+pub fn merge_documents(
+    base: &str,
+    overlay: &str,
+    config: Option<FormatConfig>,
+) -> Result<String, FormatterError> {
+    let mut document = crate::parse(base)?;
+    merge_patch(&mut document, &crate::parse(overlay)?);
+    Ok(document.format_like(base, config))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn merged(target: &str, patch: &str) -> String {
+        let mut target = crate::parse(target).unwrap();
+        merge_patch(&mut target, &crate::parse(patch).unwrap());
+        target.to_string()
+    }
+
+    #[test]
+    fn merges_follow_the_rfc_examples() {
+        let examples = [
+            (r#"{"a":"b"}"#, r#"{"a":"c"}"#, r#"{"a":"c"}"#),
+            (r#"{"a":"b"}"#, r#"{"b":"c"}"#, r#"{"a":"b","b":"c"}"#),
+            (r#"{"a":"b"}"#, r#"{"a":null}"#, r#"{}"#),
+            (r#"{"a":"b","b":"c"}"#, r#"{"a":null}"#, r#"{"b":"c"}"#),
+            (r#"{"a":["b"]}"#, r#"{"a":"c"}"#, r#"{"a":"c"}"#),
+            (r#"{"a":"c"}"#, r#"{"a":["b"]}"#, r#"{"a":["b"]}"#),
+            (
+                r#"{"a":{"b":"c"}}"#,
+                r#"{"a":{"b":"d","c":null}}"#,
+                r#"{"a":{"b":"d"}}"#,
+            ),
+            (r#"{"a":[{"b":"c"}]}"#, r#"{"a":[1]}"#, r#"{"a":[1]}"#),
+            (r#"["a","b"]"#, r#"["c","d"]"#, r#"["c","d"]"#),
+            (r#"{"a":"b"}"#, r#"["c"]"#, r#"["c"]"#),
+            (r#"{"a":"foo"}"#, "null", "null"),
+            (r#"{"a":"foo"}"#, r#""bar""#, r#""bar""#),
+            (r#"{"e":null}"#, r#"{"a":1}"#, r#"{"e":null,"a":1}"#),
+            (r#"[1,2]"#, r#"{"a":"b","c":null}"#, r#"{"a":"b"}"#),
+            ("{}", r#"{"a":{"bb":{"ccc":null}}}"#, r#"{"a":{"bb":{}}}"#),
+        ];
+
+        for (target, patch, expected) in examples.iter() {
+            assert_eq!(merged(target, patch), *expected, "{} + {}", target, patch);
+        }
+    }
+
+    #[test]
+    fn created_patches_reproduce_the_target() {
+        let from = crate::parse(r#"{"a": 1, "b": {"c": 2, "d": 3}, "e": [1]}"#).unwrap();
+        let to = crate::parse(r#"{"a": 1, "b": {"c": 4}, "e": [1, 2], "f": true}"#).unwrap();
+
+        let patch = create_merge_patch(&from, &to);
+        assert_eq!(
+            patch.to_string(),
+            r#"{"b":{"c":4,"d":null},"e":[1,2],"f":true}"#
+        );
+
+        let mut patched = from.clone();
+        merge_patch(&mut patched, &patch);
+        assert!(values_equal(&patched, &to));
+    }
+
+    #[test]
+    fn equal_documents_create_an_empty_patch() {
+        let value = crate::parse(r#"{"a": [1, {"b": null}]}"#).unwrap();
+        assert_eq!(create_merge_patch(&value, &value), Value::Object(vec![]));
+    }
+}
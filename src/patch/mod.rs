@@ -0,0 +1,287 @@
+//! JSON Patch (RFC 6902) and JSON Merge Patch (RFC 7396) documents.
+use crate::formatter::errors::FormatterError;
+use crate::formatter::FormatConfig;
+use crate::value::{parse_pointer, values_equal, Value};
+
+mod diff;
+mod merge;
+pub use diff::diff_to_patch;
+pub use merge::{create_merge_patch, merge_documents, merge_patch};
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum PatchOperation {
+    Add { path: String, value: Value },
+    Remove { path: String },
+    Replace { path: String, value: Value },
+    Move { from: String, path: String },
+    Copy { from: String, path: String },
+    Test { path: String, value: Value },
+}
+
+impl PatchOperation {
+    /// Reads one operation object, e.g. `{"op": "add", "path": "/a", "value": 1}`.
+    /// `index` is the operation's position in the patch and is only used for errors.
+    pub fn from_value(operation: &Value, index: usize) -> Result<PatchOperation, FormatterError> {
+        let invalid = || FormatterError::InvalidPatchOperation(index);
+        let member = |name: &str| operation.get(name).ok_or_else(invalid);
+        let pointer = |name: &str| member(name)?.as_str().map(String::from).ok_or_else(invalid);
+
+        match member("op")?.as_str() {
+            Some("add") => Ok(PatchOperation::Add {
+                path: pointer("path")?,
+                value: member("value")?.clone(),
+            }),
+            Some("remove") => Ok(PatchOperation::Remove {
+                path: pointer("path")?,
+            }),
+            Some("replace") => Ok(PatchOperation::Replace {
+                path: pointer("path")?,
+                value: member("value")?.clone(),
+            }),
+            Some("move") => Ok(PatchOperation::Move {
+                from: pointer("from")?,
+                path: pointer("path")?,
+            }),
+            Some("copy") => Ok(PatchOperation::Copy {
+                from: pointer("from")?,
+                path: pointer("path")?,
+            }),
+            Some("test") => Ok(PatchOperation::Test {
+                path: pointer("path")?,
+                value: member("value")?.clone(),
+            }),
+            _ => Err(invalid()),
+        }
+    }
+
+    pub fn to_value(&self) -> Value {
+        let string = |name: &str, text: &str| (String::from(name), Value::String(text.to_owned()));
+        let value = |value: &Value| (String::from("value"), value.clone());
+
+        Value::Object(match self {
+            PatchOperation::Add { path, value: v } => {
+                vec![string("op", "add"), string("path", path), value(v)]
+            }
+            PatchOperation::Remove { path } => vec![string("op", "remove"), string("path", path)],
+            PatchOperation::Replace { path, value: v } => {
+                vec![string("op", "replace"), string("path", path), value(v)]
+            }
+            PatchOperation::Move { from, path } => {
+                vec![
+                    string("op", "move"),
+                    string("from", from),
+                    string("path", path),
+                ]
+            }
+            PatchOperation::Copy { from, path } => {
+                vec![
+                    string("op", "copy"),
+                    string("from", from),
+                    string("path", path),
+                ]
+            }
+            PatchOperation::Test { path, value: v } => {
+                vec![string("op", "test"), string("path", path), value(v)]
+            }
+        })
+    }
+}
+
+/// Reads a patch document: an array of operation objects.
+pub fn parse_patch(patch: &Value) -> Result<Vec<PatchOperation>, FormatterError> {
+    match patch {
+        Value::Array(operations) => operations
+            .iter()
+            .enumerate()
+            .map(|(index, operation)| PatchOperation::from_value(operation, index))
+            .collect(),
+        _ => Err(FormatterError::InvalidPatchOperation(0)),
+    }
+}
+
+/// The patch as a JSON array, ready to be formatted.
+pub fn patch_to_value(patch: &[PatchOperation]) -> Value {
+    Value::Array(patch.iter().map(PatchOperation::to_value).collect())
+}
+
+/// Applies every operation in order. If any operation fails the document is left untouched.
+pub fn apply_patch(document: &mut Value, patch: &[PatchOperation]) -> Result<(), FormatterError> {
+    let mut patched = document.clone();
+    for (index, operation) in patch.iter().enumerate() {
+        apply_operation(&mut patched, operation, index)?;
+    }
+    *document = patched;
+    Ok(())
+}
+
+/// Parses `input` and the JSON Patch in `patch`, applies it and formats the result.
+pub fn patch_document(
+    input: &str,
+    patch: &str,
+    config: Option<FormatConfig>,
+) -> Result<String, FormatterError> {
+    let mut document = crate::parse(input)?;
+    let operations = parse_patch(&crate::parse(patch)?)?;
+    apply_patch(&mut document, &operations)?;
+    Ok(document.format_like(input, config))
+}
+
+fn apply_operation(
+    document: &mut Value,
+    operation: &PatchOperation,
+    index: usize,
+) -> Result<(), FormatterError> {
+    match operation {
+        PatchOperation::Add { path, value } => {
+            document.insert_at_pointer(path, value.clone())?;
+        }
+        PatchOperation::Remove { path } => {
+            document.remove_at_pointer(path)?;
+        }
+        PatchOperation::Replace { path, value } => {
+            *lookup_mut(document, path)? = value.clone();
+        }
+        PatchOperation::Move { from, path } => {
+            let source = parse_pointer(from)?;
+            let target = parse_pointer(path)?;
+            if source == target {
+                lookup_mut(document, from)?;
+                return Ok(());
+            }
+            if target.starts_with(&source) {
+                return Err(FormatterError::InvalidPatchOperation(index));
+            }
+            let value = document.remove_at_pointer(from)?;
+            document.insert_at_pointer(path, value)?;
+        }
+        PatchOperation::Copy { from, path } => {
+            let value = lookup_mut(document, from)?.clone();
+            document.insert_at_pointer(path, value)?;
+        }
+        PatchOperation::Test { path, value } => {
+            if !values_equal(lookup_mut(document, path)?, value) {
+                return Err(FormatterError::PatchTestFailed(path.clone()));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn lookup_mut<'a>(document: &'a mut Value, path: &str) -> Result<&'a mut Value, FormatterError> {
+    parse_pointer(path)?;
+    document
+        .pointer_mut(path)
+        .ok_or_else(|| FormatterError::PointerNotFound(path.to_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn patch(json: &str) -> Vec<PatchOperation> {
+        parse_patch(&crate::parse(json).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn operations_round_trip_through_values() {
+        let json = r#"[
+            {"op": "test", "path": "/a", "value": 1},
+            {"op": "remove", "path": "/a"},
+            {"op": "add", "path": "/b", "value": [1]},
+            {"op": "replace", "path": "/b/0", "value": 2},
+            {"op": "move", "from": "/b", "path": "/c"},
+            {"op": "copy", "from": "/c", "path": "/d"}
+        ]"#;
+        let operations = patch(json);
+        assert_eq!(operations.len(), 6);
+        assert_eq!(patch_to_value(&operations), crate::parse(json).unwrap());
+    }
+
+    #[test]
+    fn malformed_operations_report_their_index() {
+        let value =
+            crate::parse(r#"[{"op": "add", "path": "/a", "value": 1}, {"op": "add"}]"#).unwrap();
+        assert_eq!(
+            parse_patch(&value),
+            Err(FormatterError::InvalidPatchOperation(1))
+        );
+    }
+
+    #[test]
+    fn patches_apply_in_order() {
+        let mut document = crate::parse(r#"{"a": 1, "b": {"c": [1, 2]}}"#).unwrap();
+        apply_patch(
+            &mut document,
+            &patch(
+                r#"[
+                    {"op": "replace", "path": "/a", "value": 2},
+                    {"op": "add", "path": "/b/c/1", "value": 5},
+                    {"op": "move", "from": "/b/c", "path": "/c"},
+                    {"op": "copy", "from": "/a", "path": "/b/a"},
+                    {"op": "test", "path": "/c", "value": [1, 5, 2]}
+                ]"#,
+            ),
+        )
+        .unwrap();
+        assert_eq!(document.to_string(), r#"{"a":2,"b":{"a":2},"c":[1,5,2]}"#);
+    }
+
+    #[test]
+    fn failed_patches_roll_back() {
+        let original = crate::parse(r#"{"a": 1}"#).unwrap();
+        let mut document = original.clone();
+        let result = apply_patch(
+            &mut document,
+            &patch(
+                r#"[
+                    {"op": "remove", "path": "/a"},
+                    {"op": "test", "path": "/a", "value": 1}
+                ]"#,
+            ),
+        );
+        assert_eq!(
+            result,
+            Err(FormatterError::PointerNotFound(String::from("/a")))
+        );
+        assert_eq!(document, original);
+    }
+
+    #[test]
+    fn tests_compare_objects_regardless_of_order() {
+        let mut document = crate::parse(r#"{"a": {"x": 1, "y": 2}}"#).unwrap();
+        let test = patch(r#"[{"op": "test", "path": "/a", "value": {"y": 2, "x": 1}}]"#);
+        assert_eq!(apply_patch(&mut document, &test), Ok(()));
+
+        let test = patch(r#"[{"op": "test", "path": "/a/x", "value": "1"}]"#);
+        assert_eq!(
+            apply_patch(&mut document, &test),
+            Err(FormatterError::PatchTestFailed(String::from("/a/x")))
+        );
+    }
+
+    #[test]
+    fn values_cannot_move_into_themselves() {
+        let mut document = crate::parse(r#"{"a": {"b": 1}}"#).unwrap();
+        let operations = patch(r#"[{"op": "move", "from": "/a", "path": "/a/b/c"}]"#);
+        assert_eq!(
+            apply_patch(&mut document, &operations),
+            Err(FormatterError::InvalidPatchOperation(0))
+        );
+    }
+
+    #[test]
+    fn patched_documents_are_formatted() {
+        let formatted = patch_document(
+            r#"{"name": "jsonist"}"#,
+            r#"[{"op": "add", "path": "/version", "value": "0.0.3"}]"#,
+            Some(FormatConfig::new(crate::Delimiter::Spaces(
+                crate::DelimiterCount::Two,
+            ))),
+        )
+        .unwrap();
+        assert_eq!(
+            formatted,
+            "{\n  \"name\": \"jsonist\",\n  \"version\": \"0.0.3\"\n}\n"
+        );
+    }
+}
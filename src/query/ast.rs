@@ -0,0 +1,76 @@
+use crate::value::Value;
+
+/// A parsed JSONPath query, either rooted at the document (`$`) or, inside a filter,
+/// at the current node (`@`).
+#[derive(Debug, PartialEq, Clone)]
+pub struct Path {
+    pub relative: bool,
+    pub segments: Vec<Segment>,
+}
+
+impl Path {
+    /// Singular queries only use name and index selectors, so they select at most one node.
+    pub fn is_singular(&self) -> bool {
+        self.segments.iter().all(|segment| match segment {
+            Segment::Child(selectors) => {
+                selectors.len() == 1
+                    && matches!(selectors[0], Selector::Name(_) | Selector::Index(_))
+            }
+            Segment::Descendant(_) => false,
+        })
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Segment {
+    Child(Vec<Selector>),
+    Descendant(Vec<Selector>),
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Selector {
+    Name(String),
+    Wildcard,
+    Index(i64),
+    Slice {
+        start: Option<i64>,
+        end: Option<i64>,
+        step: Option<i64>,
+    },
+    Filter(Expression),
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Expression {
+    Or(Vec<Expression>),
+    And(Vec<Expression>),
+    Not(Box<Expression>),
+    Comparison(Operand, Comparison, Operand),
+    Test(Operand),
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Comparison {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Operand {
+    Literal(Value),
+    Query(Path),
+    Function(Function, Vec<Operand>),
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Function {
+    Length,
+    Count,
+    Match,
+    Search,
+    Value,
+}
@@ -0,0 +1,318 @@
+use crate::regex::Regex;
+use crate::value::{values_equal, Value};
+
+use super::ast::{Comparison, Expression, Function, Operand, Path, Segment, Selector};
+use super::PathSegment;
+
+pub type Located<'a> = (Vec<PathSegment>, &'a Value);
+
+pub fn evaluate<'a>(path: &Path, root: &'a Value) -> Vec<Located<'a>> {
+    apply_segments(&path.segments, root, vec![(vec![], root)])
+}
+
+fn apply_segments<'a>(
+    segments: &[Segment],
+    root: &'a Value,
+    nodes: Vec<Located<'a>>,
+) -> Vec<Located<'a>> {
+    segments.iter().fold(nodes, |nodes, segment| {
+        let mut selected = vec![];
+        for (location, value) in nodes {
+            match segment {
+                Segment::Child(selectors) => {
+                    select(selectors, root, &location, value, &mut selected)
+                }
+                Segment::Descendant(selectors) => {
+                    descend(selectors, root, location, value, &mut selected)
+                }
+            }
+        }
+        selected
+    })
+}
+
+/// Applies the selectors to `value` and then to each of its descendants, in document order.
+fn descend<'a>(
+    selectors: &[Selector],
+    root: &'a Value,
+    location: Vec<PathSegment>,
+    value: &'a Value,
+    selected: &mut Vec<Located<'a>>,
+) {
+    select(selectors, root, &location, value, selected);
+
+    match value {
+        Value::Object(pairs) => {
+            for (key, child) in pairs {
+                let location = extend(&location, PathSegment::Key(key.to_owned()));
+                descend(selectors, root, location, child, selected);
+            }
+        }
+        Value::Array(items) => {
+            for (index, child) in items.iter().enumerate() {
+                let location = extend(&location, PathSegment::Index(index));
+                descend(selectors, root, location, child, selected);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn select<'a>(
+    selectors: &[Selector],
+    root: &'a Value,
+    location: &[PathSegment],
+    value: &'a Value,
+    selected: &mut Vec<Located<'a>>,
+) {
+    for selector in selectors {
+        match (selector, value) {
+            (Selector::Name(name), Value::Object(pairs)) => {
+                if let Some((key, child)) = pairs.iter().find(|(key, _)| key == name) {
+                    selected.push((extend(location, PathSegment::Key(key.to_owned())), child));
+                }
+            }
+            (Selector::Wildcard, Value::Object(pairs)) => {
+                for (key, child) in pairs {
+                    selected.push((extend(location, PathSegment::Key(key.to_owned())), child));
+                }
+            }
+            (Selector::Wildcard, Value::Array(items)) => {
+                for (index, child) in items.iter().enumerate() {
+                    selected.push((extend(location, PathSegment::Index(index)), child));
+                }
+            }
+            (Selector::Index(index), Value::Array(items)) => {
+                let length = items.len() as i64;
+                let index = if *index < 0 { length + index } else { *index };
+                if 0 <= index && index < length {
+                    let index = index as usize;
+                    selected.push((extend(location, PathSegment::Index(index)), &items[index]));
+                }
+            }
+            (Selector::Slice { start, end, step }, Value::Array(items)) => {
+                for index in slice_indices(items.len(), *start, *end, *step) {
+                    selected.push((extend(location, PathSegment::Index(index)), &items[index]));
+                }
+            }
+            (Selector::Filter(expression), Value::Object(pairs)) => {
+                for (key, child) in pairs {
+                    if test(expression, root, child) {
+                        selected.push((extend(location, PathSegment::Key(key.to_owned())), child));
+                    }
+                }
+            }
+            (Selector::Filter(expression), Value::Array(items)) => {
+                for (index, child) in items.iter().enumerate() {
+                    if test(expression, root, child) {
+                        selected.push((extend(location, PathSegment::Index(index)), child));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn extend(location: &[PathSegment], segment: PathSegment) -> Vec<PathSegment> {
+    let mut extended = location.to_vec();
+    extended.push(segment);
+    extended
+}
+
+/// The array slice algorithm from RFC 9535 section 2.3.4.2.2.
+fn slice_indices(
+    length: usize,
+    start: Option<i64>,
+    end: Option<i64>,
+    step: Option<i64>,
+) -> Vec<usize> {
+    let length = length as i64;
+    let step = step.unwrap_or(1);
+    let normalize = |index: i64| if index >= 0 { index } else { length + index };
+    let mut indices = vec![];
+
+    if step > 0 {
+        let lower = normalize(start.unwrap_or(0)).clamp(0, length);
+        let upper = normalize(end.unwrap_or(length)).clamp(0, length);
+        let mut index = lower;
+        while index < upper {
+            indices.push(index as usize);
+            index += step;
+        }
+    } else if step < 0 {
+        let upper = normalize(start.unwrap_or(length - 1)).clamp(-1, length - 1);
+        let lower = normalize(end.unwrap_or(-length - 1)).clamp(-1, length - 1);
+        let mut index = upper;
+        while lower < index {
+            indices.push(index as usize);
+            index += step;
+        }
+    }
+
+    indices
+}
+
+fn test(expression: &Expression, root: &Value, current: &Value) -> bool {
+    match expression {
+        Expression::Or(expressions) => expressions
+            .iter()
+            .any(|expression| test(expression, root, current)),
+        Expression::And(expressions) => expressions
+            .iter()
+            .all(|expression| test(expression, root, current)),
+        Expression::Not(expression) => !test(expression, root, current),
+        Expression::Comparison(left, comparison, right) => compare(
+            value_of(left, root, current),
+            *comparison,
+            value_of(right, root, current),
+        ),
+        Expression::Test(Operand::Query(path)) => !nodes_of(path, root, current).is_empty(),
+        Expression::Test(Operand::Function(function, arguments)) => {
+            logical(*function, arguments, root, current)
+        }
+        Expression::Test(Operand::Literal(_)) => false,
+    }
+}
+
+fn nodes_of<'a>(path: &Path, root: &'a Value, current: &'a Value) -> Vec<Located<'a>> {
+    let start = if path.relative { current } else { root };
+    apply_segments(&path.segments, root, vec![(vec![], start)])
+}
+
+/// The value of a comparable, or `None` for the special result "Nothing".
+fn value_of(operand: &Operand, root: &Value, current: &Value) -> Option<Value> {
+    match operand {
+        Operand::Literal(value) => Some(value.clone()),
+        Operand::Query(path) => single(nodes_of(path, root, current)),
+        Operand::Function(Function::Length, arguments) => {
+            match value_of(&arguments[0], root, current)? {
+                Value::String(string) => Some(Value::Number(string.chars().count() as f64)),
+                Value::Array(items) => Some(Value::Number(items.len() as f64)),
+                Value::Object(pairs) => Some(Value::Number(pairs.len() as f64)),
+                _ => None,
+            }
+        }
+        Operand::Function(Function::Count, arguments) => match &arguments[0] {
+            Operand::Query(path) => Some(Value::Number(nodes_of(path, root, current).len() as f64)),
+            _ => None,
+        },
+        Operand::Function(Function::Value, arguments) => match &arguments[0] {
+            Operand::Query(path) => single(nodes_of(path, root, current)),
+            _ => None,
+        },
+        Operand::Function(Function::Match, _) | Operand::Function(Function::Search, _) => None,
+    }
+}
+
+fn single(mut nodes: Vec<Located>) -> Option<Value> {
+    if nodes.len() == 1 {
+        nodes.pop().map(|(_, value)| value.clone())
+    } else {
+        None
+    }
+}
+
+fn logical(function: Function, arguments: &[Operand], root: &Value, current: &Value) -> bool {
+    let string = |operand: &Operand| match value_of(operand, root, current) {
+        Some(Value::String(string)) => Some(string),
+        _ => None,
+    };
+
+    let (input, pattern) = match (function, arguments) {
+        (Function::Match, [input, pattern]) | (Function::Search, [input, pattern]) => {
+            match (string(input), string(pattern)) {
+                (Some(input), Some(pattern)) => (input, pattern),
+                _ => return false,
+            }
+        }
+        _ => return false,
+    };
+
+    match Regex::new(&pattern) {
+        Some(regex) if function == Function::Match => regex.is_full_match(&input),
+        Some(regex) => regex.is_match(&input),
+        None => false,
+    }
+}
+
+fn compare(left: Option<Value>, comparison: Comparison, right: Option<Value>) -> bool {
+    match comparison {
+        Comparison::Equal => equal(&left, &right),
+        Comparison::NotEqual => !equal(&left, &right),
+        Comparison::Less => less(&left, &right),
+        Comparison::LessOrEqual => less(&left, &right) || equal(&left, &right),
+        Comparison::Greater => less(&right, &left),
+        Comparison::GreaterOrEqual => less(&right, &left) || equal(&left, &right),
+    }
+}
+
+fn equal(left: &Option<Value>, right: &Option<Value>) -> bool {
+    match (left, right) {
+        (None, None) => true,
+        (Some(left), Some(right)) => values_equal(left, right),
+        _ => false,
+    }
+}
+
+fn less(left: &Option<Value>, right: &Option<Value>) -> bool {
+    match (left, right) {
+        (Some(Value::Number(left)), Some(Value::Number(right))) => left < right,
+        (Some(Value::String(left)), Some(Value::String(right))) => left < right,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slices_follow_the_rfc_examples() {
+        assert_eq!(slice_indices(7, Some(1), Some(3), None), vec![1, 2]);
+        assert_eq!(slice_indices(7, Some(5), None, None), vec![5, 6]);
+        assert_eq!(slice_indices(7, Some(1), Some(5), Some(2)), vec![1, 3]);
+        assert_eq!(slice_indices(7, Some(5), Some(1), Some(-2)), vec![5, 3]);
+        assert_eq!(
+            slice_indices(7, None, None, Some(-1)),
+            vec![6, 5, 4, 3, 2, 1, 0]
+        );
+        assert_eq!(slice_indices(7, None, None, Some(0)), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn comparisons_with_nothing() {
+        assert!(compare(None, Comparison::Equal, None));
+        assert!(!compare(Some(Value::Null), Comparison::Equal, None));
+        assert!(compare(None, Comparison::LessOrEqual, None));
+        assert!(!compare(
+            Some(Value::Number(1.0)),
+            Comparison::Less,
+            Some(Value::String(String::from("2")))
+        ));
+    }
+
+    #[test]
+    fn objects_compare_regardless_of_order() {
+        let left = Value::Object(vec![
+            (String::from("a"), Value::Null),
+            (String::from("b"), Value::Number(1.0)),
+        ]);
+        let right = Value::Object(vec![
+            (String::from("b"), Value::Number(1.0)),
+            (String::from("a"), Value::Null),
+        ]);
+        assert!(values_equal(&left, &right));
+    }
+
+    #[test]
+    fn match_handles_long_strings() {
+        let document = Value::Array(vec![Value::String("a".repeat(100_000))]);
+        let found = document.query("$[?match(@, 'a*')]").unwrap();
+        assert_eq!(found.len(), 1);
+        assert!(document
+            .query("$[?search(@, '(a|a)*b')]")
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,161 @@
+//! JSONPath queries as described in RFC 9535.
+use std::fmt;
+use std::ops::Range;
+
+use crate::cst;
+use crate::formatter::errors::FormatterError;
+use crate::value::{to_pointer, Value};
+
+mod ast;
+mod evaluate;
+mod parser;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum PathSegment {
+    Key(String),
+    Index(usize),
+}
+
+/// The location of a single node, printed in the normalized form of RFC 9535 (`$['a'][0]`).
+#[derive(Debug, PartialEq, Clone)]
+pub struct NormalizedPath(pub Vec<PathSegment>);
+
+impl NormalizedPath {
+    /// The same location as a JSON Pointer.
+    pub fn to_pointer(&self) -> String {
+        to_pointer(self.0.iter().map(|segment| match segment {
+            PathSegment::Key(key) => key.clone(),
+            PathSegment::Index(index) => index.to_string(),
+        }))
+    }
+}
+
+impl fmt::Display for NormalizedPath {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "$")?;
+        for segment in &self.0 {
+            match segment {
+                PathSegment::Key(key) => {
+                    write!(f, "['")?;
+                    for character in key.chars() {
+                        match character {
+                            '\\' => write!(f, "\\\\")?,
+                            '\'' => write!(f, "\\'")?,
+                            '\u{8}' => write!(f, "\\b")?,
+                            '\u{c}' => write!(f, "\\f")?,
+                            '\n' => write!(f, "\\n")?,
+                            '\r' => write!(f, "\\r")?,
+                            '\t' => write!(f, "\\t")?,
+                            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+                            c => write!(f, "{}", c)?,
+                        }
+                    }
+                    write!(f, "']")?;
+                }
+                PathSegment::Index(index) => write!(f, "[{}]", index)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A node selected by a query, with the byte range of its value in the source text.
+#[derive(Debug, PartialEq, Clone)]
+pub struct QueryMatch {
+    pub path: NormalizedPath,
+    pub value: Value,
+    pub span: Range<usize>,
+}
+
+/// Runs the JSONPath `path` against the document in `input`, returning matches in document order.
+pub fn query(input: &str, path: &str) -> Result<Vec<QueryMatch>, FormatterError> {
+    let path = parser::parse_path(path)?;
+    let value = crate::parse(input)?;
+    let document = cst::parse(input)?;
+
+    Ok(evaluate::evaluate(&path, &value)
+        .into_iter()
+        .map(|(location, found)| {
+            let path = NormalizedPath(location);
+            QueryMatch {
+                span: document
+                    .pointer(&path.to_pointer())
+                    .map_or(0..0, |element| element.offset()..element.end()),
+                path,
+                value: found.clone(),
+            }
+        })
+        .collect())
+}
+
+impl Value {
+    /// Runs the JSONPath `path` against this value.
+    pub fn query(&self, path: &str) -> Result<Vec<(NormalizedPath, &Value)>, FormatterError> {
+        let path = parser::parse_path(path)?;
+        Ok(evaluate::evaluate(&path, self)
+            .into_iter()
+            .map(|(location, found)| (NormalizedPath(location), found))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalized_paths_escape_keys() {
+        let path = NormalizedPath(vec![
+            PathSegment::Key(String::from("it's\n")),
+            PathSegment::Index(2),
+            PathSegment::Key(String::from("\u{1}")),
+        ]);
+        assert_eq!(path.to_string(), r"$['it\'s\n'][2]['\u0001']");
+    }
+
+    #[test]
+    fn normalized_paths_convert_to_pointers() {
+        let path = NormalizedPath(vec![
+            PathSegment::Key(String::from("a/b")),
+            PathSegment::Index(0),
+        ]);
+        assert_eq!(path.to_pointer(), "/a~1b/0");
+    }
+
+    #[test]
+    fn query_reports_spans() {
+        let input = r#"{"a": [1, {"b": true}], "c": null}"#;
+        let matches = query(input, "$..b").unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].path.to_string(), "$['a'][1]['b']");
+        assert_eq!(matches[0].value, Value::Bool(true));
+        assert_eq!(&input[matches[0].span.clone()], "true");
+    }
+
+    #[test]
+    fn query_spans_cover_containers() {
+        let input = "[\n  {\"x\": 1},\n  {\"x\": 2}\n]";
+        let matches = query(input, "$[?@.x > 1]").unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(&input[matches[0].span.clone()], "{\"x\": 2}");
+    }
+
+    #[test]
+    fn value_query_borrows_results() {
+        let value = crate::parse(r#"{"store": {"book": [{"price": 8}, {"price": 22}]}}"#).unwrap();
+        let found = value.query("$.store.book[*].price").unwrap();
+        let prices = found
+            .iter()
+            .map(|(_, value)| value.as_f64())
+            .collect::<Vec<_>>();
+        assert_eq!(prices, vec![Some(8.0), Some(22.0)]);
+    }
+
+    #[test]
+    fn invalid_queries_report_positions() {
+        assert_eq!(
+            query("{}", "$.a[").unwrap_err(),
+            FormatterError::InvalidQuery(4)
+        );
+    }
+}
@@ -0,0 +1,671 @@
+use crate::formatter::errors::FormatterError;
+use crate::value::Value;
+
+use super::ast::{Comparison, Expression, Function, Operand, Path, Segment, Selector};
+
+/// Parses an RFC 9535 JSONPath query such as `$.items[?@.price < 10].name`.
+pub fn parse_path(path: &str) -> Result<Path, FormatterError> {
+    let mut parser = Parser {
+        characters: path.chars().collect(),
+        position: 0,
+    };
+
+    parser.expect('$')?;
+    let segments = parser.segments()?;
+    if parser.position != parser.characters.len() {
+        return Err(parser.error());
+    }
+
+    Ok(Path {
+        relative: false,
+        segments,
+    })
+}
+
+struct Parser {
+    characters: Vec<char>,
+    position: usize,
+}
+
+impl Parser {
+    fn error(&self) -> FormatterError {
+        FormatterError::InvalidQuery(self.position)
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.characters.get(self.position).copied()
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<char> {
+        self.characters.get(self.position + offset).copied()
+    }
+
+    fn eat(&mut self, character: char) -> bool {
+        if self.peek() == Some(character) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn eat_str(&mut self, literal: &str) -> bool {
+        let matches = literal
+            .chars()
+            .enumerate()
+            .all(|(offset, character)| self.peek_at(offset) == Some(character));
+        if matches {
+            self.position += literal.chars().count();
+        }
+        matches
+    }
+
+    fn expect(&mut self, character: char) -> Result<(), FormatterError> {
+        if self.eat(character) {
+            Ok(())
+        } else {
+            Err(self.error())
+        }
+    }
+
+    fn skip_blank(&mut self) {
+        while matches!(
+            self.peek(),
+            Some(' ') | Some('\t') | Some('\n') | Some('\r')
+        ) {
+            self.position += 1;
+        }
+    }
+
+    fn segments(&mut self) -> Result<Vec<Segment>, FormatterError> {
+        let mut segments = vec![];
+
+        loop {
+            let start = self.position;
+            self.skip_blank();
+            match (self.peek(), self.peek_at(1)) {
+                (Some('.'), Some('.')) => {
+                    self.position += 2;
+                    let selectors = match self.peek() {
+                        Some('[') => self.bracketed_selection()?,
+                        Some('*') => {
+                            self.position += 1;
+                            vec![Selector::Wildcard]
+                        }
+                        _ => vec![Selector::Name(self.member_name()?)],
+                    };
+                    segments.push(Segment::Descendant(selectors));
+                }
+                (Some('.'), _) => {
+                    self.position += 1;
+                    if self.eat('*') {
+                        segments.push(Segment::Child(vec![Selector::Wildcard]));
+                    } else {
+                        segments.push(Segment::Child(vec![Selector::Name(self.member_name()?)]));
+                    }
+                }
+                (Some('['), _) => segments.push(Segment::Child(self.bracketed_selection()?)),
+                _ => {
+                    self.position = start;
+                    return Ok(segments);
+                }
+            }
+        }
+    }
+
+    fn member_name(&mut self) -> Result<String, FormatterError> {
+        let is_first = |character: char| {
+            character.is_ascii_alphabetic() || character == '_' || !character.is_ascii()
+        };
+
+        match self.peek() {
+            Some(character) if is_first(character) => {}
+            _ => return Err(self.error()),
+        }
+
+        let mut name = String::new();
+        while let Some(character) = self.peek() {
+            if is_first(character) || character.is_ascii_digit() {
+                name.push(character);
+                self.position += 1;
+            } else {
+                break;
+            }
+        }
+        Ok(name)
+    }
+
+    fn bracketed_selection(&mut self) -> Result<Vec<Selector>, FormatterError> {
+        self.expect('[')?;
+        let mut selectors = vec![];
+
+        loop {
+            self.skip_blank();
+            selectors.push(self.selector()?);
+            self.skip_blank();
+            if self.eat(']') {
+                return Ok(selectors);
+            }
+            self.expect(',')?;
+        }
+    }
+
+    fn selector(&mut self) -> Result<Selector, FormatterError> {
+        match self.peek() {
+            Some('\'') | Some('"') => Ok(Selector::Name(self.string_literal()?)),
+            Some('*') => {
+                self.position += 1;
+                Ok(Selector::Wildcard)
+            }
+            Some('?') => {
+                self.position += 1;
+                self.skip_blank();
+                Ok(Selector::Filter(self.logical_or()?))
+            }
+            _ => self.index_or_slice(),
+        }
+    }
+
+    fn index_or_slice(&mut self) -> Result<Selector, FormatterError> {
+        let start = self.optional_integer()?;
+        self.skip_blank();
+        if !self.eat(':') {
+            return start.map(Selector::Index).ok_or_else(|| self.error());
+        }
+
+        self.skip_blank();
+        let end = self.optional_integer()?;
+        self.skip_blank();
+        let step = if self.eat(':') {
+            self.skip_blank();
+            self.optional_integer()?
+        } else {
+            None
+        };
+
+        Ok(Selector::Slice { start, end, step })
+    }
+
+    fn optional_integer(&mut self) -> Result<Option<i64>, FormatterError> {
+        match self.peek() {
+            Some('-') | Some('0'..='9') => self.integer().map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    fn integer(&mut self) -> Result<i64, FormatterError> {
+        let start = self.position;
+        let negative = self.eat('-');
+        let digits_start = self.position;
+        while matches!(self.peek(), Some('0'..='9')) {
+            self.position += 1;
+        }
+
+        let digits = self.characters[digits_start..self.position]
+            .iter()
+            .collect::<String>();
+        let leading_zero = digits.len() > 1 && digits.starts_with('0');
+        if digits.is_empty() || leading_zero || (negative && digits == "0") {
+            self.position = start;
+            return Err(self.error());
+        }
+
+        // Indices must stay inside the I-JSON exact integer range.
+        let limit = 9_007_199_254_740_991_i64;
+        match digits.parse::<i64>() {
+            Ok(value) if value <= limit => Ok(if negative { -value } else { value }),
+            _ => {
+                self.position = start;
+                Err(self.error())
+            }
+        }
+    }
+
+    fn string_literal(&mut self) -> Result<String, FormatterError> {
+        let quote = match self.peek() {
+            Some(quote @ '\'') | Some(quote @ '"') => quote,
+            _ => return Err(self.error()),
+        };
+        self.position += 1;
+
+        let mut string = String::new();
+        loop {
+            match self.peek() {
+                None => return Err(self.error()),
+                Some(character) if character == quote => {
+                    self.position += 1;
+                    return Ok(string);
+                }
+                Some('\\') => {
+                    self.position += 1;
+                    let escaped = match self.peek() {
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('/') => '/',
+                        Some('\\') => '\\',
+                        Some(character) if character == quote => quote,
+                        Some('u') => {
+                            self.position += 1;
+                            let character = self.unicode_escape()?;
+                            string.push(character);
+                            continue;
+                        }
+                        _ => return Err(self.error()),
+                    };
+                    self.position += 1;
+                    string.push(escaped);
+                }
+                Some(character) if (character as u32) < 0x20 => return Err(self.error()),
+                Some(character) => {
+                    self.position += 1;
+                    string.push(character);
+                }
+            }
+        }
+    }
+
+    fn hex4(&mut self) -> Result<u32, FormatterError> {
+        let mut code = 0;
+        for _ in 0..4 {
+            let digit = self
+                .peek()
+                .and_then(|character| character.to_digit(16))
+                .ok_or_else(|| self.error())?;
+            code = code * 16 + digit;
+            self.position += 1;
+        }
+        Ok(code)
+    }
+
+    fn unicode_escape(&mut self) -> Result<char, FormatterError> {
+        let high = self.hex4()?;
+        let code = if (0xD800..0xDC00).contains(&high) {
+            if !self.eat_str("\\u") {
+                return Err(self.error());
+            }
+            let low = self.hex4()?;
+            if !(0xDC00..0xE000).contains(&low) {
+                return Err(self.error());
+            }
+            0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+        } else {
+            high
+        };
+        std::char::from_u32(code).ok_or_else(|| self.error())
+    }
+
+    fn logical_or(&mut self) -> Result<Expression, FormatterError> {
+        let mut operands = vec![self.logical_and()?];
+        loop {
+            self.skip_blank();
+            if !self.eat_str("||") {
+                break;
+            }
+            self.skip_blank();
+            operands.push(self.logical_and()?);
+        }
+        Ok(match operands.len() {
+            1 => operands.remove(0),
+            _ => Expression::Or(operands),
+        })
+    }
+
+    fn logical_and(&mut self) -> Result<Expression, FormatterError> {
+        let mut operands = vec![self.basic_expression()?];
+        loop {
+            let start = self.position;
+            self.skip_blank();
+            if !self.eat_str("&&") {
+                self.position = start;
+                break;
+            }
+            self.skip_blank();
+            operands.push(self.basic_expression()?);
+        }
+        Ok(match operands.len() {
+            1 => operands.remove(0),
+            _ => Expression::And(operands),
+        })
+    }
+
+    fn basic_expression(&mut self) -> Result<Expression, FormatterError> {
+        if self.eat('!') {
+            self.skip_blank();
+            if self.eat('(') {
+                let expression = self.parenthesized()?;
+                return Ok(Expression::Not(Box::new(expression)));
+            }
+            let operand = self.test_operand()?;
+            return Ok(Expression::Not(Box::new(Expression::Test(operand))));
+        }
+
+        if self.eat('(') {
+            return self.parenthesized();
+        }
+
+        let start = self.position;
+        let left = self.operand()?;
+        let after_left = self.position;
+        self.skip_blank();
+
+        match self.comparison() {
+            Some(comparison) => {
+                self.skip_blank();
+                let right_start = self.position;
+                let right = self.operand()?;
+                self.check_comparable(&left, start)?;
+                self.check_comparable(&right, right_start)?;
+                Ok(Expression::Comparison(left, comparison, right))
+            }
+            None => {
+                self.position = after_left;
+                match left {
+                    Operand::Literal(_) => {
+                        self.position = start;
+                        Err(self.error())
+                    }
+                    Operand::Function(Function::Length, _)
+                    | Operand::Function(Function::Count, _)
+                    | Operand::Function(Function::Value, _) => {
+                        self.position = start;
+                        Err(self.error())
+                    }
+                    operand => Ok(Expression::Test(operand)),
+                }
+            }
+        }
+    }
+
+    fn parenthesized(&mut self) -> Result<Expression, FormatterError> {
+        self.skip_blank();
+        let expression = self.logical_or()?;
+        self.skip_blank();
+        self.expect(')')?;
+        Ok(expression)
+    }
+
+    fn test_operand(&mut self) -> Result<Operand, FormatterError> {
+        let start = self.position;
+        match self.operand()? {
+            operand @ Operand::Query(_) => Ok(operand),
+            operand @ Operand::Function(Function::Match, _)
+            | operand @ Operand::Function(Function::Search, _) => Ok(operand),
+            _ => {
+                self.position = start;
+                Err(self.error())
+            }
+        }
+    }
+
+    /// Comparisons need a single value on each side.
+    fn check_comparable(&mut self, operand: &Operand, start: usize) -> Result<(), FormatterError> {
+        let comparable = match operand {
+            Operand::Literal(_) => true,
+            Operand::Query(path) => path.is_singular(),
+            Operand::Function(function, _) => {
+                !matches!(function, Function::Match | Function::Search)
+            }
+        };
+        if comparable {
+            Ok(())
+        } else {
+            self.position = start;
+            Err(self.error())
+        }
+    }
+
+    fn comparison(&mut self) -> Option<Comparison> {
+        let operators = [
+            ("==", Comparison::Equal),
+            ("!=", Comparison::NotEqual),
+            ("<=", Comparison::LessOrEqual),
+            (">=", Comparison::GreaterOrEqual),
+            ("<", Comparison::Less),
+            (">", Comparison::Greater),
+        ];
+        operators
+            .iter()
+            .find(|(literal, _)| self.eat_str(literal))
+            .map(|(_, comparison)| *comparison)
+    }
+
+    fn operand(&mut self) -> Result<Operand, FormatterError> {
+        match self.peek() {
+            Some('@') => {
+                self.position += 1;
+                Ok(Operand::Query(Path {
+                    relative: true,
+                    segments: self.segments()?,
+                }))
+            }
+            Some('$') => {
+                self.position += 1;
+                Ok(Operand::Query(Path {
+                    relative: false,
+                    segments: self.segments()?,
+                }))
+            }
+            Some('\'') | Some('"') => Ok(Operand::Literal(Value::String(self.string_literal()?))),
+            Some('-') | Some('0'..='9') => Ok(Operand::Literal(Value::Number(self.number()?))),
+            _ if self.eat_str("true") => Ok(Operand::Literal(Value::Bool(true))),
+            _ if self.eat_str("false") => Ok(Operand::Literal(Value::Bool(false))),
+            _ if self.eat_str("null") => Ok(Operand::Literal(Value::Null)),
+            Some('a'..='z') => self.function(),
+            _ => Err(self.error()),
+        }
+    }
+
+    fn number(&mut self) -> Result<f64, FormatterError> {
+        let start = self.position;
+        self.eat('-');
+        let digits_start = self.position;
+        while matches!(self.peek(), Some('0'..='9')) {
+            self.position += 1;
+        }
+        let integer_digits = self.position - digits_start;
+        if integer_digits == 0 || (integer_digits > 1 && self.characters[digits_start] == '0') {
+            self.position = start;
+            return Err(self.error());
+        }
+        if self.peek() == Some('.') && matches!(self.peek_at(1), Some('0'..='9')) {
+            self.position += 1;
+            while matches!(self.peek(), Some('0'..='9')) {
+                self.position += 1;
+            }
+        }
+        if matches!(self.peek(), Some('e') | Some('E')) {
+            self.position += 1;
+            if matches!(self.peek(), Some('+') | Some('-')) {
+                self.position += 1;
+            }
+            if !matches!(self.peek(), Some('0'..='9')) {
+                return Err(self.error());
+            }
+            while matches!(self.peek(), Some('0'..='9')) {
+                self.position += 1;
+            }
+        }
+
+        self.characters[start..self.position]
+            .iter()
+            .collect::<String>()
+            .parse::<f64>()
+            .map_err(|_| self.error())
+    }
+
+    fn function(&mut self) -> Result<Operand, FormatterError> {
+        let start = self.position;
+        let mut name = String::new();
+        while let Some(character) = self.peek() {
+            if character.is_ascii_lowercase() || character.is_ascii_digit() || character == '_' {
+                name.push(character);
+                self.position += 1;
+            } else {
+                break;
+            }
+        }
+
+        let function = match name.as_str() {
+            "length" => Function::Length,
+            "count" => Function::Count,
+            "match" => Function::Match,
+            "search" => Function::Search,
+            "value" => Function::Value,
+            _ => {
+                self.position = start;
+                return Err(self.error());
+            }
+        };
+
+        self.expect('(')?;
+        let mut arguments = vec![];
+        self.skip_blank();
+        if !self.eat(')') {
+            loop {
+                self.skip_blank();
+                arguments.push(self.operand()?);
+                self.skip_blank();
+                if self.eat(')') {
+                    break;
+                }
+                self.expect(',')?;
+            }
+        }
+
+        let well_typed = match function {
+            Function::Length => arguments.len() == 1 && is_value_argument(&arguments[0]),
+            Function::Count | Function::Value => {
+                arguments.len() == 1 && matches!(arguments[0], Operand::Query(_))
+            }
+            Function::Match | Function::Search => {
+                arguments.len() == 2 && arguments.iter().all(is_value_argument)
+            }
+        };
+        if !well_typed {
+            self.position = start;
+            return Err(self.error());
+        }
+
+        Ok(Operand::Function(function, arguments))
+    }
+}
+
+fn is_value_argument(operand: &Operand) -> bool {
+    match operand {
+        Operand::Literal(_) => true,
+        Operand::Query(path) => path.is_singular(),
+        Operand::Function(function, _) => {
+            matches!(
+                function,
+                Function::Length | Function::Count | Function::Value
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn name(name: &str) -> Selector {
+        Selector::Name(String::from(name))
+    }
+
+    #[test]
+    fn parses_dot_and_bracket_notation() {
+        let path = parse_path("$.store['book'][0, -1]..author.*").unwrap();
+        assert_eq!(
+            path.segments,
+            vec![
+                Segment::Child(vec![name("store")]),
+                Segment::Child(vec![name("book")]),
+                Segment::Child(vec![Selector::Index(0), Selector::Index(-1)]),
+                Segment::Descendant(vec![name("author")]),
+                Segment::Child(vec![Selector::Wildcard]),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_slices() {
+        let path = parse_path("$[1:3][::-1][ :2 ]").unwrap();
+        assert_eq!(
+            path.segments,
+            vec![
+                Segment::Child(vec![Selector::Slice {
+                    start: Some(1),
+                    end: Some(3),
+                    step: None
+                }]),
+                Segment::Child(vec![Selector::Slice {
+                    start: None,
+                    end: None,
+                    step: Some(-1)
+                }]),
+                Segment::Child(vec![Selector::Slice {
+                    start: None,
+                    end: Some(2),
+                    step: None
+                }]),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_filters() {
+        let path = parse_path("$[?@.price < 10 && !@.sold]").unwrap();
+        let price = Path {
+            relative: true,
+            segments: vec![Segment::Child(vec![name("price")])],
+        };
+        let sold = Path {
+            relative: true,
+            segments: vec![Segment::Child(vec![name("sold")])],
+        };
+
+        assert_eq!(
+            path.segments,
+            vec![Segment::Child(vec![Selector::Filter(Expression::And(
+                vec![
+                    Expression::Comparison(
+                        Operand::Query(price),
+                        Comparison::Less,
+                        Operand::Literal(Value::Number(10.0))
+                    ),
+                    Expression::Not(Box::new(Expression::Test(Operand::Query(sold)))),
+                ]
+            ))])]
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_queries() {
+        let invalid = [
+            "",
+            "store",
+            "$.",
+            "$[01]",
+            "$[-0]",
+            "$['a'",
+            "$[?@.* == 1]",
+            "$[?length(@.a)]",
+            "$[?count(1) == 1]",
+            "$[?1]",
+            "$ .a b",
+        ];
+        for path in invalid.iter() {
+            assert!(parse_path(path).is_err(), "{} should not parse", path);
+        }
+    }
+
+    #[test]
+    fn reports_the_position_of_the_problem() {
+        assert_eq!(
+            parse_path("$.a[?@ ~ 1]"),
+            Err(FormatterError::InvalidQuery(7))
+        );
+    }
+}
@@ -0,0 +1,254 @@
+use std::ops::Range;
+
+use crate::cst::{self, SyntaxElement, SyntaxKind, SyntaxNode};
+use crate::formatter::errors::FormatterError;
+use crate::formatter::{
+    indentation, stringify_at_depth, stringify_items, stringify_with_config, FormatConfig,
+};
+use crate::parser::{self, parse_container, parse_entries, parse_member, ParseContext};
+
+/// A replacement of the bytes in `range` with `new_text`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct TextEdit {
+    pub range: Range<usize>,
+    pub new_text: String,
+}
+
+/// The byte range covering the (zero based, end exclusive) `lines` of `input`.
+pub fn line_range(input: &str, lines: Range<usize>) -> Range<usize> {
+    let mut line_starts = vec![0];
+    line_starts.extend(
+        input
+            .char_indices()
+            .filter(|(_, character)| *character == '\n')
+            .map(|(index, _)| index + 1),
+    );
+
+    let start = *line_starts.get(lines.start).unwrap_or(&input.len());
+    let end = *line_starts.get(lines.end).unwrap_or(&input.len());
+    start..end.max(start)
+}
+
+/// Formats only the smallest syntax node(s) covering the byte `range` of `input`,
+/// returning the edits to apply rather than the whole document.
+pub fn format_range(
+    input: &str,
+    range: Range<usize>,
+    config: Option<FormatConfig>,
+) -> Result<Vec<TextEdit>, FormatterError> {
+    let mut config = config.unwrap_or_default();
+    config.detect_line_ending(input);
+    let document = cst::parse(input)?;
+
+    let root = match document.value() {
+        Some(root) => root,
+        None => return Ok(vec![]),
+    };
+
+    if covers(root, &range) {
+        return format_document(input, &document, &config);
+    }
+
+    let (node, depth) = match root {
+        SyntaxElement::Node(node) => deepest_covering(node, &range, 0),
+        SyntaxElement::Token(_) => return format_document(input, &document, &config),
+    };
+
+    let mut context = ParseContext::for_config(&config);
+    match node.kind() {
+        SyntaxKind::Member => {
+            let (_, pair) = parse_member(node, &mut context)?;
+            let printed = stringify_at_depth(pair, depth, &config);
+            Ok(vec![edit(
+                input,
+                node.offset()..node.end(),
+                printed,
+                depth,
+                &config,
+            )])
+        }
+        _ if node.offset() >= range.start && node.end() <= range.end => {
+            let printed = stringify_at_depth(parse_container(node, &mut context)?, depth, &config);
+            Ok(vec![edit(
+                input,
+                node.offset()..node.end(),
+                printed,
+                depth,
+                &config,
+            )])
+        }
+        _ => format_items(input, node, &range, depth, &config),
+    }
+}
+
+fn format_document(
+    input: &str,
+    document: &SyntaxNode,
+    config: &FormatConfig,
+) -> Result<Vec<TextEdit>, FormatterError> {
+    let ast = parser::parse(document, &mut ParseContext::for_config(config))?;
+    Ok(vec![TextEdit {
+        range: 0..input.len(),
+        new_text: stringify_with_config(ast, config),
+    }])
+}
+
+/// Reformats the run of members or items of `container`, `depth` containers deep, touched by
+/// `range`.
+fn format_items(
+    input: &str,
+    container: &SyntaxNode,
+    range: &Range<usize>,
+    depth: usize,
+    config: &FormatConfig,
+) -> Result<Vec<TextEdit>, FormatterError> {
+    let items = container
+        .children()
+        .iter()
+        .filter(|child| child.is_value() || child.kind() == SyntaxKind::Member)
+        .filter(|child| child.offset() < range.end && child.end() > range.start)
+        .collect::<Vec<&SyntaxElement>>();
+
+    let (first, last) = match (items.first(), items.last()) {
+        (Some(first), Some(last)) => (first.offset(), last.end()),
+        _ => return Ok(vec![]),
+    };
+
+    let items = parse_entries(container, range, &mut ParseContext::for_config(config))?;
+    let printed = stringify_items(items, depth, config);
+
+    Ok(vec![edit(input, first..last, printed, depth + 1, config)])
+}
+
+/// Builds an edit, re-indenting the first line when the node starts its own line.
+fn edit(
+    input: &str,
+    range: Range<usize>,
+    printed: String,
+    depth: usize,
+    config: &FormatConfig,
+) -> TextEdit {
+    let line_start = input[..range.start]
+        .rfind('\n')
+        .map_or(0, |index| index + 1);
+    let leading = &input[line_start..range.start];
+
+    if leading.chars().all(char::is_whitespace) {
+        TextEdit {
+            range: line_start..range.end,
+            new_text: format!("{}{}", indentation(depth, config), printed),
+        }
+    } else {
+        TextEdit {
+            range,
+            new_text: printed,
+        }
+    }
+}
+
+fn covers(element: &SyntaxElement, range: &Range<usize>) -> bool {
+    element.offset() >= range.start && element.end() <= range.end
+}
+
+/// Walks down from `node` while a single child node still contains the whole range,
+/// counting the containers passed on the way.
+fn deepest_covering<'a>(
+    node: &'a SyntaxNode,
+    range: &Range<usize>,
+    depth: usize,
+) -> (&'a SyntaxNode, usize) {
+    let child = node
+        .child_nodes()
+        .find(|child| child.offset() <= range.start && child.end() >= range.end);
+
+    match child {
+        Some(child) if node.kind() == SyntaxKind::Member => deepest_covering(child, range, depth),
+        Some(child) => deepest_covering(child, range, depth + 1),
+        None => (node, depth),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apply(input: &str, edits: Vec<TextEdit>) -> String {
+        let mut output = input.to_owned();
+        for edit in edits.into_iter().rev() {
+            output.replace_range(edit.range, &edit.new_text);
+        }
+        output
+    }
+
+    #[test]
+    fn line_range_finds_line_offsets() {
+        let input = "{\n\"a\": 1,\n\"b\": 2\n}";
+        assert_eq!(line_range(input, 1..2), 2..10);
+        assert_eq!(line_range(input, 3..9), 17..18);
+    }
+
+    #[test]
+    fn formats_only_the_selected_member() {
+        let input = "{\n  \"a\":[1,2],\n      \"b\":{\"c\":true}\n}\n";
+        let start = input.find("\"b\"").unwrap();
+        let edits = format_range(input, start..start + 3, None).unwrap();
+
+        assert_eq!(
+            apply(input, edits),
+            "{\n  \"a\":[1,2],\n    \"b\": {\n        \"c\": true\n    }\n}\n"
+        );
+    }
+
+    #[test]
+    fn formats_a_nested_value_at_its_depth() {
+        let input = "{\"a\": {\"b\": [1,2]}}";
+        let start = input.find('[').unwrap();
+        let edits = format_range(input, start..start + 5, None).unwrap();
+
+        assert_eq!(
+            apply(input, edits),
+            "{\"a\": {\"b\": [\n            1,\n            2\n        ]}}"
+        );
+    }
+
+    #[test]
+    fn formats_the_touched_items_of_a_container() {
+        let input = "[\n1,\n   [2,3],   4,\n5]";
+        let range = line_range(input, 2..3);
+        let edits = format_range(input, range, None).unwrap();
+
+        assert_eq!(
+            apply(input, edits),
+            "[\n1,\n    [\n        2,\n        3\n    ],\n    4,\n5]"
+        );
+    }
+
+    #[test]
+    fn formats_everything_when_the_whole_document_is_selected() {
+        let input = "[1,2]";
+        let edits = format_range(input, 0..input.len(), None).unwrap();
+
+        assert_eq!(apply(input, edits), "[\n    1,\n    2\n]");
+    }
+
+    #[test]
+    fn keeps_the_documents_line_endings() {
+        let input = "{\r\n  \"a\":[1,2]\r\n}\r\n";
+        let start = input.find('[').unwrap();
+        let edits = format_range(input, start..start + 5, None).unwrap();
+
+        assert_eq!(
+            apply(input, edits),
+            "{\r\n  \"a\":[\r\n        1,\r\n        2\r\n    ]\r\n}\r\n"
+        );
+    }
+
+    #[test]
+    fn keeps_blank_lines_between_the_touched_items() {
+        let input = "[\n1,\n\n2,\n3]";
+        let config = FormatConfig::default().with_blank_lines(true);
+        let edits = format_range(input, line_range(input, 1..4), Some(config)).unwrap();
+
+        assert_eq!(apply(input, edits), "[\n    1,\n\n    2,\n3]");
+    }
+}
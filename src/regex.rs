@@ -0,0 +1,557 @@
+//! A matcher for I-Regexp (RFC 9485), the regular expressions used by the JSONPath `match()`
+//! and `search()` functions and by JSON Schema `pattern`. Patterns compile to a Thompson NFA
+//! that is run over the input one character at a time, Pike VM style, so matching takes time
+//! linear in the input and never recurses, however long the input or awkward the pattern.
+
+type Alternation = Vec<Vec<Piece>>;
+
+/// The most instructions a pattern may compile to. Counted repetition copies its atom, so
+/// `(a{1000}){1000}` would otherwise grow without bound.
+const MAX_PROGRAM_SIZE: usize = 100_000;
+/// The largest count `{n,m}` accepts.
+const MAX_REPEAT: usize = 1_000;
+/// How deeply groups may nest; the parser and compiler recurse once per level.
+const MAX_DEPTH: usize = 100;
+
+#[derive(Debug, Clone)]
+enum Atom {
+    Character(char),
+    Any,
+    Class {
+        negated: bool,
+        items: Vec<ClassItem>,
+    },
+    Group(Alternation),
+}
+
+#[derive(Debug, Clone)]
+enum ClassItem {
+    Range(char, char),
+    Category(Category, bool),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Category {
+    Letter,
+    Uppercase,
+    Lowercase,
+    Number,
+    Digit,
+    Punctuation,
+    Separator,
+}
+
+#[derive(Debug, Clone)]
+struct Piece {
+    atom: Atom,
+    min: usize,
+    max: Option<usize>,
+}
+
+#[derive(Debug)]
+enum Instruction {
+    /// Consume one character the atom (never a group) matches.
+    Character(Atom),
+    /// Carry on at both instructions.
+    Split(usize, usize),
+    Jump(usize),
+    Match,
+}
+
+pub struct Regex {
+    program: Vec<Instruction>,
+}
+
+impl Regex {
+    pub fn new(pattern: &str) -> Option<Regex> {
+        let mut parser = RegexParser {
+            characters: pattern.chars().collect(),
+            position: 0,
+            depth: 0,
+        };
+        let alternation = parser.alternation()?;
+        if parser.position != parser.characters.len() {
+            return None;
+        }
+        let mut program = vec![];
+        compile_alternation(&alternation, &mut program, 0)?;
+        program.push(Instruction::Match);
+        Some(Regex { program })
+    }
+
+    /// Whether the whole of `input` matches.
+    pub fn is_full_match(&self, input: &str) -> bool {
+        self.is_anchored_match(input, true, true)
+    }
+
+    /// Whether any substring of `input` matches.
+    pub fn is_match(&self, input: &str) -> bool {
+        self.is_anchored_match(input, false, false)
+    }
+
+    /// Whether a substring of `input` matches, optionally required to start at its beginning
+    /// or run to its end.
+    pub fn is_anchored_match(&self, input: &str, at_start: bool, at_end: bool) -> bool {
+        let program = &self.program;
+        let mut seen = vec![false; program.len()];
+        let (mut threads, mut next) = (vec![], vec![]);
+        add_thread(program, 0, &mut threads, &mut seen);
+
+        let mut characters = input.chars();
+        loop {
+            let character = characters.next();
+            let matched = threads
+                .iter()
+                .any(|&pc| matches!(program[pc], Instruction::Match));
+            if matched && (!at_end || character.is_none()) {
+                return true;
+            }
+            let character = match character {
+                Some(character) => character,
+                None => return false,
+            };
+
+            seen.fill(false);
+            for &pc in &threads {
+                if let Instruction::Character(atom) = &program[pc] {
+                    if matches_character(atom, character) {
+                        add_thread(program, pc + 1, &mut next, &mut seen);
+                    }
+                }
+            }
+            if !at_start {
+                // A match may also start after this character.
+                add_thread(program, 0, &mut next, &mut seen);
+            }
+            if next.is_empty() {
+                return false;
+            }
+            std::mem::swap(&mut threads, &mut next);
+            next.clear();
+        }
+    }
+}
+
+/// Adds a thread at `pc` to `threads`, following splits and jumps to the instructions that
+/// consume a character or match. `seen` keeps each instruction to one thread per step.
+fn add_thread(program: &[Instruction], pc: usize, threads: &mut Vec<usize>, seen: &mut [bool]) {
+    let mut pending = vec![pc];
+    while let Some(pc) = pending.pop() {
+        if std::mem::replace(&mut seen[pc], true) {
+            continue;
+        }
+        match program[pc] {
+            Instruction::Split(first, second) => pending.extend([second, first]),
+            Instruction::Jump(to) => pending.push(to),
+            _ => threads.push(pc),
+        }
+    }
+}
+
+/// Compiles `alternation` onto `program`, giving up once the program grows past
+/// `MAX_PROGRAM_SIZE` or groups nest deeper than `MAX_DEPTH`.
+fn compile_alternation(
+    alternation: &[Vec<Piece>],
+    program: &mut Vec<Instruction>,
+    depth: usize,
+) -> Option<()> {
+    if depth > MAX_DEPTH {
+        return None;
+    }
+    let mut jumps = vec![];
+    for (index, branch) in alternation.iter().enumerate() {
+        if index + 1 == alternation.len() {
+            compile_branch(branch, program, depth)?;
+            break;
+        }
+        let split = program.len();
+        program.push(Instruction::Split(split + 1, 0));
+        compile_branch(branch, program, depth)?;
+        jumps.push(program.len());
+        program.push(Instruction::Jump(0));
+        program[split] = Instruction::Split(split + 1, program.len());
+    }
+    let end = program.len();
+    for jump in jumps {
+        program[jump] = Instruction::Jump(end);
+    }
+    Some(())
+}
+
+fn compile_branch(pieces: &[Piece], program: &mut Vec<Instruction>, depth: usize) -> Option<()> {
+    for piece in pieces {
+        for _ in 0..piece.min {
+            compile_atom(&piece.atom, program, depth)?;
+        }
+        match piece.max {
+            None => {
+                let split = program.len();
+                program.push(Instruction::Split(split + 1, 0));
+                compile_atom(&piece.atom, program, depth)?;
+                program.push(Instruction::Jump(split));
+                program[split] = Instruction::Split(split + 1, program.len());
+            }
+            Some(max) => {
+                let mut splits = vec![];
+                for _ in piece.min..max {
+                    splits.push(program.len());
+                    program.push(Instruction::Split(program.len() + 1, 0));
+                    compile_atom(&piece.atom, program, depth)?;
+                }
+                let end = program.len();
+                for split in splits {
+                    program[split] = Instruction::Split(split + 1, end);
+                }
+            }
+        }
+    }
+    Some(())
+}
+
+fn compile_atom(atom: &Atom, program: &mut Vec<Instruction>, depth: usize) -> Option<()> {
+    if program.len() > MAX_PROGRAM_SIZE {
+        return None;
+    }
+    match atom {
+        Atom::Group(alternation) => compile_alternation(alternation, program, depth + 1)?,
+        atom => program.push(Instruction::Character(atom.clone())),
+    }
+    Some(())
+}
+
+fn matches_character(atom: &Atom, character: char) -> bool {
+    match atom {
+        Atom::Character(expected) => *expected == character,
+        Atom::Any => character != '\n' && character != '\r',
+        Atom::Class { negated, items } => {
+            let found = items.iter().any(|item| match item {
+                ClassItem::Range(low, high) => *low <= character && character <= *high,
+                ClassItem::Category(category, negated) => {
+                    in_category(*category, character) != *negated
+                }
+            });
+            found != *negated
+        }
+        Atom::Group(_) => false,
+    }
+}
+
+fn in_category(category: Category, character: char) -> bool {
+    match category {
+        Category::Letter => character.is_alphabetic(),
+        Category::Uppercase => character.is_uppercase(),
+        Category::Lowercase => character.is_lowercase(),
+        Category::Number => character.is_numeric(),
+        Category::Digit => character.is_ascii_digit(),
+        Category::Punctuation => character.is_ascii_punctuation(),
+        Category::Separator => character.is_whitespace() && !character.is_control(),
+    }
+}
+
+struct RegexParser {
+    characters: Vec<char>,
+    position: usize,
+    depth: usize,
+}
+
+impl RegexParser {
+    fn peek(&self) -> Option<char> {
+        self.characters.get(self.position).copied()
+    }
+
+    fn eat(&mut self, character: char) -> bool {
+        if self.peek() == Some(character) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn alternation(&mut self) -> Option<Alternation> {
+        let mut branches = vec![self.branch()?];
+        while self.eat('|') {
+            branches.push(self.branch()?);
+        }
+        Some(branches)
+    }
+
+    fn branch(&mut self) -> Option<Vec<Piece>> {
+        let mut pieces = vec![];
+        while !matches!(self.peek(), None | Some('|') | Some(')')) {
+            let atom = self.atom()?;
+            let (min, max) = self.quantifier()?;
+            pieces.push(Piece { atom, min, max });
+        }
+        Some(pieces)
+    }
+
+    fn quantifier(&mut self) -> Option<(usize, Option<usize>)> {
+        match self.peek() {
+            Some('*') => {
+                self.position += 1;
+                Some((0, None))
+            }
+            Some('+') => {
+                self.position += 1;
+                Some((1, None))
+            }
+            Some('?') => {
+                self.position += 1;
+                Some((0, Some(1)))
+            }
+            Some('{') => {
+                self.position += 1;
+                let min = self.count()?;
+                let max = if self.eat(',') {
+                    if self.peek() == Some('}') {
+                        None
+                    } else {
+                        Some(self.count()?)
+                    }
+                } else {
+                    Some(min)
+                };
+                if !self.eat('}') || max.is_some_and(|max| max < min) {
+                    return None;
+                }
+                Some((min, max))
+            }
+            _ => Some((1, Some(1))),
+        }
+    }
+
+    fn count(&mut self) -> Option<usize> {
+        let start = self.position;
+        while matches!(self.peek(), Some('0'..='9')) {
+            self.position += 1;
+        }
+        self.characters[start..self.position]
+            .iter()
+            .collect::<String>()
+            .parse()
+            .ok()
+            .filter(|&count| count <= MAX_REPEAT)
+    }
+
+    fn atom(&mut self) -> Option<Atom> {
+        let character = self.peek()?;
+        self.position += 1;
+        match character {
+            '.' => Some(Atom::Any),
+            '(' => {
+                if self.depth == MAX_DEPTH {
+                    return None;
+                }
+                self.depth += 1;
+                let alternation = self.alternation()?;
+                self.depth -= 1;
+                if self.eat(')') {
+                    Some(Atom::Group(alternation))
+                } else {
+                    None
+                }
+            }
+            '[' => self.class(),
+            '\\' => match self.escape()? {
+                ClassItem::Range(low, _) => Some(Atom::Character(low)),
+                category => Some(Atom::Class {
+                    negated: false,
+                    items: vec![category],
+                }),
+            },
+            '*' | '+' | '?' | '{' | '}' | ']' | ')' => None,
+            character => Some(Atom::Character(character)),
+        }
+    }
+
+    fn class(&mut self) -> Option<Atom> {
+        let negated = self.eat('^');
+        let mut items = vec![];
+
+        loop {
+            let low = match self.peek()? {
+                ']' if !items.is_empty() => {
+                    self.position += 1;
+                    return Some(Atom::Class { negated, items });
+                }
+                '\\' => {
+                    self.position += 1;
+                    match self.escape()? {
+                        ClassItem::Range(low, _) => low,
+                        category => {
+                            items.push(category);
+                            continue;
+                        }
+                    }
+                }
+                '[' => return None,
+                character => {
+                    self.position += 1;
+                    character
+                }
+            };
+
+            let is_range = self.peek() == Some('-')
+                && !matches!(self.characters.get(self.position + 1), Some(']') | None);
+            if is_range {
+                self.position += 1;
+                let high = match self.peek()? {
+                    '\\' => {
+                        self.position += 1;
+                        match self.escape()? {
+                            ClassItem::Range(high, _) => high,
+                            ClassItem::Category(_, _) => return None,
+                        }
+                    }
+                    character => {
+                        self.position += 1;
+                        character
+                    }
+                };
+                if high < low {
+                    return None;
+                }
+                items.push(ClassItem::Range(low, high));
+            } else {
+                items.push(ClassItem::Range(low, low));
+            }
+        }
+    }
+
+    fn escape(&mut self) -> Option<ClassItem> {
+        let character = self.peek()?;
+        self.position += 1;
+        let literal = match character {
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            '(' | ')' | '*' | '+' | '-' | '.' | '?' | '[' | '\\' | ']' | '^' | '{' | '|' | '}' => {
+                character
+            }
+            'p' | 'P' => {
+                let category = self.category()?;
+                return Some(ClassItem::Category(category, character == 'P'));
+            }
+            _ => return None,
+        };
+        Some(ClassItem::Range(literal, literal))
+    }
+
+    fn category(&mut self) -> Option<Category> {
+        if !self.eat('{') {
+            return None;
+        }
+        let start = self.position;
+        while self.peek().is_some_and(|character| character != '}') {
+            self.position += 1;
+        }
+        let name = self.characters[start..self.position]
+            .iter()
+            .collect::<String>();
+        if !self.eat('}') {
+            return None;
+        }
+
+        match name.as_str() {
+            "L" => Some(Category::Letter),
+            "Lu" => Some(Category::Uppercase),
+            "Ll" => Some(Category::Lowercase),
+            "N" => Some(Category::Number),
+            "Nd" => Some(Category::Digit),
+            "P" => Some(Category::Punctuation),
+            "Z" | "Zs" => Some(Category::Separator),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn full_matches() {
+        let regex = Regex::new("[a-c]+(x|yz)?.").unwrap();
+        assert!(regex.is_full_match("abcyz!"));
+        assert!(regex.is_full_match("aa1"));
+        assert!(!regex.is_full_match("d1"));
+        assert!(!regex.is_full_match("a\n"));
+    }
+
+    #[test]
+    fn searches() {
+        let regex = Regex::new("b.{2}").unwrap();
+        assert!(regex.is_match("abcde"));
+        assert!(!regex.is_match("ab"));
+    }
+
+    #[test]
+    fn anchored_matches() {
+        let regex = Regex::new("ab").unwrap();
+        assert!(regex.is_anchored_match("abc", true, false));
+        assert!(!regex.is_anchored_match("cab", true, false));
+        assert!(regex.is_anchored_match("cab", false, true));
+        assert!(!regex.is_anchored_match("abc", false, true));
+    }
+
+    #[test]
+    fn classes_and_categories() {
+        let regex = Regex::new("[^0-9]\\p{Lu}\\.").unwrap();
+        assert!(regex.is_full_match("xY."));
+        assert!(!regex.is_full_match("1Y."));
+        assert!(!regex.is_full_match("xy."));
+    }
+
+    #[test]
+    fn rejects_invalid_patterns() {
+        for pattern in ["(a", "a{2,1}", "[b-a]", "*", "\\d", "\\p{Xx}"].iter() {
+            assert!(Regex::new(pattern).is_none(), "{}", pattern);
+        }
+    }
+
+    #[test]
+    fn matches_long_input_without_recursing() {
+        let long = "a".repeat(100_000);
+        assert!(Regex::new("a*").unwrap().is_full_match(&long));
+        assert!(Regex::new("(a|b)+").unwrap().is_full_match(&long));
+        assert!(!Regex::new("a*b").unwrap().is_match(&long));
+    }
+
+    #[test]
+    fn matches_pathological_patterns_in_linear_time() {
+        let input = "a".repeat(30);
+        assert!(!Regex::new("(a|a)*b").unwrap().is_full_match(&input));
+        assert!(!Regex::new("(a*)*b").unwrap().is_match(&input));
+        assert!(Regex::new("(a|a)*").unwrap().is_full_match(&input));
+        assert!(Regex::new("(a?){30}a{30}").unwrap().is_full_match(&input));
+    }
+
+    #[test]
+    fn counted_repetition() {
+        let regex = Regex::new("x(ab){1,2}y{2,}").unwrap();
+        assert!(regex.is_full_match("xabyy"));
+        assert!(regex.is_full_match("xababyyy"));
+        assert!(!regex.is_full_match("xy"));
+        assert!(!regex.is_full_match("xabababyy"));
+        assert!(!regex.is_full_match("xaby"));
+    }
+
+    #[test]
+    fn rejects_patterns_too_large_to_compile() {
+        let nested = format!("{}a{}", "(".repeat(10_000), ")".repeat(10_000));
+        for pattern in [
+            "a{99999999999}",
+            "((a{1000}){1000}){1000}",
+            "(){0,1001}",
+            &nested,
+        ] {
+            assert!(
+                Regex::new(pattern).is_none(),
+                "{}",
+                &pattern[..20.min(pattern.len())]
+            );
+        }
+        assert!(Regex::new("(a{1000}){10}").is_some());
+    }
+}
@@ -0,0 +1,136 @@
+use crate::formatter::errors::FormatterError;
+use crate::value::Value;
+
+use super::keywords::resolve;
+use super::{pointer, Pattern};
+
+const TYPES: [&str; 7] = [
+    "null", "boolean", "object", "array", "number", "string", "integer",
+];
+
+/// Rejects schemas the validator would otherwise silently misread.
+pub fn check(root: &Value) -> Result<(), FormatterError> {
+    check_schema(root, root, &mut vec![])
+}
+
+fn check_schema(
+    root: &Value,
+    schema: &Value,
+    location: &mut Vec<String>,
+) -> Result<(), FormatterError> {
+    let keywords = match schema {
+        Value::Bool(_) => return Ok(()),
+        Value::Object(keywords) => keywords,
+        _ => return Err(invalid(location)),
+    };
+
+    for (keyword, value) in keywords {
+        location.push(keyword.clone());
+        check_keyword(root, keyword, value, location)?;
+        location.pop();
+    }
+    Ok(())
+}
+
+fn check_keyword(
+    root: &Value,
+    keyword: &str,
+    value: &Value,
+    location: &mut Vec<String>,
+) -> Result<(), FormatterError> {
+    match keyword {
+        "$ref" => match value.as_str() {
+            Some(reference) if resolve(root, reference).is_some() => Ok(()),
+            _ => Err(invalid(location)),
+        },
+        "type" => {
+            let valid = |name: &Value| name.as_str().is_some_and(|name| TYPES.contains(&name));
+            match value {
+                Value::Array(names) if names.iter().all(valid) => Ok(()),
+                name if valid(name) => Ok(()),
+                _ => Err(invalid(location)),
+            }
+        }
+        "pattern" => match value.as_str().and_then(Pattern::new) {
+            Some(_) => Ok(()),
+            None => Err(invalid(location)),
+        },
+        "enum" | "required" | "allOf" | "anyOf" | "oneOf" | "prefixItems"
+            if value.as_array().is_none() =>
+        {
+            Err(invalid(location))
+        }
+        "allOf" | "anyOf" | "oneOf" | "prefixItems" => {
+            for (index, schema) in value.as_array().into_iter().flatten().enumerate() {
+                location.push(index.to_string());
+                check_schema(root, schema, location)?;
+                location.pop();
+            }
+            Ok(())
+        }
+        "items"
+        | "contains"
+        | "additionalProperties"
+        | "propertyNames"
+        | "not"
+        | "if"
+        | "then"
+        | "else" => check_schema(root, value, location),
+        "properties" | "patternProperties" | "$defs" | "definitions" | "dependentSchemas" => {
+            let members = value.as_object().ok_or_else(|| invalid(location))?;
+            for (name, schema) in members {
+                location.push(name.clone());
+                if keyword == "patternProperties" && Pattern::new(name).is_none() {
+                    return Err(invalid(location));
+                }
+                check_schema(root, schema, location)?;
+                location.pop();
+            }
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+fn invalid(location: &[String]) -> FormatterError {
+    FormatterError::InvalidSchema(pointer(location))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(schema: &str) -> Option<FormatterError> {
+        check(&crate::parse(schema).unwrap()).err()
+    }
+
+    #[test]
+    fn accepts_well_formed_schemas() {
+        assert_eq!(
+            error(
+                r##"{"$defs": {"a": true}, "items": {"$ref": "#/$defs/a"}, "type": ["string"]}"##
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn reports_where_a_schema_is_malformed() {
+        let invalid = |location: &str| Some(FormatterError::InvalidSchema(String::from(location)));
+        assert_eq!(error(r#"{"type": "text"}"#), invalid("/type"));
+        assert_eq!(
+            error(r##"{"items": {"$ref": "#/$defs/missing"}}"##),
+            invalid("/items/$ref")
+        );
+        assert_eq!(
+            error(r#"{"$ref": "https://example.com/schema.json"}"#),
+            invalid("/$ref")
+        );
+        assert_eq!(
+            error(r#"{"properties": {"a": {"pattern": "(\\d"}}}"#),
+            invalid("/properties/a/pattern")
+        );
+        assert_eq!(error(r#"{"anyOf": [true, 1]}"#), invalid("/anyOf/1"));
+        assert_eq!(error(r#"{"required": "a"}"#), invalid("/required"));
+    }
+}
@@ -0,0 +1,271 @@
+use crate::datetime::{is_date, is_date_time};
+use crate::value::Value;
+
+const DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Everything seen at one location across all the samples.
+#[derive(Default)]
+struct Shape {
+    null: bool,
+    boolean: bool,
+    integer: bool,
+    float: bool,
+    strings: usize,
+    /// The format every string seen so far shares, if any.
+    format: Option<&'static str>,
+    arrays: usize,
+    items: Option<Box<Shape>>,
+    objects: usize,
+    /// Each property with the number of objects it appeared in.
+    properties: Vec<(String, usize, Shape)>,
+}
+
+impl Shape {
+    fn add(&mut self, value: &Value) {
+        match value {
+            Value::Null => self.null = true,
+            Value::Bool(_) => self.boolean = true,
+            Value::Number(number) if number.fract() == 0.0 => self.integer = true,
+            Value::Number(_) => self.float = true,
+            Value::String(string) => {
+                let format = detect_format(string);
+                if self.strings == 0 {
+                    self.format = format;
+                } else if self.format != format {
+                    self.format = None;
+                }
+                self.strings += 1;
+            }
+            Value::Array(items) => {
+                self.arrays += 1;
+                for item in items {
+                    self.items.get_or_insert_with(Box::default).add(item);
+                }
+            }
+            Value::Object(pairs) => {
+                self.objects += 1;
+                for (key, value) in pairs {
+                    let index = match self.properties.iter().position(|(name, _, _)| name == key) {
+                        Some(index) => index,
+                        None => {
+                            self.properties.push((key.clone(), 0, Shape::default()));
+                            self.properties.len() - 1
+                        }
+                    };
+                    let (_, seen, shape) = &mut self.properties[index];
+                    *seen += 1;
+                    shape.add(value);
+                }
+            }
+        }
+    }
+
+    fn to_schema(&self) -> Value {
+        let mut types = vec![];
+        let mut keywords = vec![];
+
+        if self.null {
+            types.push("null");
+        }
+        if self.boolean {
+            types.push("boolean");
+        }
+        match (self.integer, self.float) {
+            (true, false) => types.push("integer"),
+            (_, true) => types.push("number"),
+            _ => {}
+        }
+        if self.strings > 0 {
+            types.push("string");
+            if let Some(format) = self.format {
+                keywords.push(string("format", format));
+            }
+        }
+        if self.arrays > 0 {
+            types.push("array");
+            if let Some(items) = &self.items {
+                keywords.push((String::from("items"), items.to_schema()));
+            }
+        }
+        if self.objects > 0 {
+            types.push("object");
+            let properties = self
+                .properties
+                .iter()
+                .map(|(name, _, shape)| (name.clone(), shape.to_schema()))
+                .collect();
+            keywords.push((String::from("properties"), Value::Object(properties)));
+
+            let required = self
+                .properties
+                .iter()
+                .filter(|(_, seen, _)| *seen == self.objects)
+                .map(|(name, _, _)| Value::String(name.clone()))
+                .collect::<Vec<Value>>();
+            if !required.is_empty() {
+                keywords.push((String::from("required"), Value::Array(required)));
+            }
+        }
+
+        let kind = match types.as_slice() {
+            [] => vec![],
+            [single] => vec![string("type", single)],
+            types => vec![(
+                String::from("type"),
+                Value::Array(
+                    types
+                        .iter()
+                        .map(|name| Value::String(name.to_string()))
+                        .collect(),
+                ),
+            )],
+        };
+        Value::Object(kind.into_iter().chain(keywords).collect())
+    }
+}
+
+/// A schema every sample validates against. Keys missing from some objects are optional,
+/// numbers are integers unless any sample has a fraction, and strings that all share a
+/// recognisable format (`date-time`, `date`, `uuid` or `email`) get a `format`.
+pub fn infer_schema(samples: &[Value]) -> Value {
+    let mut shape = Shape::default();
+    for sample in samples {
+        shape.add(sample);
+    }
+
+    match shape.to_schema() {
+        Value::Object(keywords) => Value::Object(
+            std::iter::once(string("$schema", DIALECT))
+                .chain(keywords)
+                .collect(),
+        ),
+        schema => schema,
+    }
+}
+
+fn string(name: &str, value: &str) -> (String, Value) {
+    (String::from(name), Value::String(value.to_owned()))
+}
+
+fn detect_format(string: &str) -> Option<&'static str> {
+    if is_date_time(string) {
+        Some("date-time")
+    } else if is_date(string) {
+        Some("date")
+    } else if is_uuid(string) {
+        Some("uuid")
+    } else if is_email(string) {
+        Some("email")
+    } else {
+        None
+    }
+}
+
+fn is_uuid(text: &str) -> bool {
+    let parts = text.split('-').collect::<Vec<&str>>();
+    parts.len() == 5
+        && parts
+            .iter()
+            .zip([8, 4, 4, 4, 12].iter())
+            .all(|(part, length)| {
+                part.len() == *length && part.bytes().all(|b| b.is_ascii_hexdigit())
+            })
+}
+
+fn is_email(text: &str) -> bool {
+    match text.split_once('@') {
+        Some((local, domain)) => {
+            !local.is_empty()
+                && !domain.contains('@')
+                && !text.chars().any(char::is_whitespace)
+                && domain.split('.').count() > 1
+                && domain.split('.').all(|label| !label.is_empty())
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::Schema;
+
+    fn infer(samples: &[&str]) -> String {
+        let samples = samples
+            .iter()
+            .map(|sample| crate::parse(sample).unwrap())
+            .collect::<Vec<Value>>();
+        let schema = infer_schema(&samples);
+
+        let checked = Schema::new(schema.clone()).unwrap();
+        for sample in &samples {
+            assert_eq!(checked.validate(sample), vec![], "{}", sample);
+        }
+        match schema {
+            Value::Object(keywords) => {
+                assert_eq!(keywords[0], string("$schema", DIALECT));
+                Value::Object(keywords[1..].to_vec()).to_string()
+            }
+            schema => panic!("{}", schema),
+        }
+    }
+
+    #[test]
+    fn merges_types_across_samples() {
+        assert_eq!(infer(&["1", "2"]), r#"{"type":"integer"}"#);
+        assert_eq!(infer(&["1", "2.5"]), r#"{"type":"number"}"#);
+        assert_eq!(
+            infer(&["1", "null", "true"]),
+            r#"{"type":["null","boolean","integer"]}"#
+        );
+    }
+
+    #[test]
+    fn keys_missing_from_a_sample_are_optional() {
+        assert_eq!(
+            infer(&[r#"{"id": 1, "name": "a"}"#, r#"{"id": 2, "tags": []}"#]),
+            r#"{"type":"object","properties":{"id":{"type":"integer"},"name":{"type":"string"},"tags":{"type":"array"}},"required":["id"]}"#
+        );
+    }
+
+    #[test]
+    fn array_items_are_unions() {
+        assert_eq!(
+            infer(&[r#"[1, "a", {"x": null}]"#, r#"[{"x": 1}]"#]),
+            r#"{"type":"array","items":{"type":["integer","string","object"],"properties":{"x":{"type":["null","integer"]}},"required":["x"]}}"#
+        );
+    }
+
+    #[test]
+    fn detects_string_formats() {
+        assert_eq!(
+            infer(&[
+                r#""2024-05-01T12:30:00.5Z""#,
+                r#""1999-12-31t23:59:59+02:00""#
+            ]),
+            r#"{"type":"string","format":"date-time"}"#
+        );
+        assert_eq!(
+            infer(&[r#""2024-05-01""#]),
+            r#"{"type":"string","format":"date"}"#
+        );
+        assert_eq!(
+            infer(&[r#""123e4567-e89b-12d3-a456-426614174000""#]),
+            r#"{"type":"string","format":"uuid"}"#
+        );
+        assert_eq!(
+            infer(&[r#""someone@example.com""#]),
+            r#"{"type":"string","format":"email"}"#
+        );
+        assert_eq!(
+            infer(&[r#""someone@example.com""#, r#""2024-05-01""#]),
+            r#"{"type":"string"}"#
+        );
+        assert_eq!(infer(&[r#""12:30""#, r#""a@b""#]), r#"{"type":"string"}"#);
+    }
+
+    #[test]
+    fn no_samples_allow_anything() {
+        assert_eq!(infer(&[]), "{}");
+    }
+}
@@ -0,0 +1,712 @@
+use crate::value::{values_equal, Value};
+
+use super::{pointer, Pattern, Violation};
+
+/// Walks an instance alongside its schema, collecting a `Violation` for every keyword that fails.
+pub struct Validator<'a> {
+    root: &'a Value,
+    /// `$ref`s being followed, with the instance each was followed for, so a schema that
+    /// refers to itself without moving through the instance stops instead of recursing forever.
+    active_refs: Vec<(String, String)>,
+    pub violations: Vec<Violation>,
+}
+
+impl<'a> Validator<'a> {
+    pub fn new(root: &'a Value) -> Validator<'a> {
+        Validator {
+            root,
+            active_refs: vec![],
+            violations: vec![],
+        }
+    }
+
+    pub fn validate(
+        &mut self,
+        schema: &Value,
+        location: &mut Vec<String>,
+        instance: &Value,
+        path: &mut Vec<String>,
+    ) {
+        let keywords = match schema {
+            Value::Bool(true) => return,
+            Value::Bool(false) => {
+                return self.fail(location, path, String::from("no value is allowed here"))
+            }
+            Value::Object(keywords) => keywords,
+            _ => return,
+        };
+
+        for (keyword, value) in keywords {
+            location.push(keyword.clone());
+            self.keyword(keyword, value, schema, location, instance, path);
+            location.pop();
+        }
+    }
+
+    /// Whether `instance` is valid against `schema`, without keeping any violations.
+    fn passes(
+        &mut self,
+        schema: &Value,
+        location: &mut Vec<String>,
+        instance: &Value,
+        path: &mut Vec<String>,
+    ) -> bool {
+        let before = self.violations.len();
+        self.validate(schema, location, instance, path);
+        let passed = self.violations.len() == before;
+        self.violations.truncate(before);
+        passed
+    }
+
+    fn fail(&mut self, location: &[String], path: &[String], message: String) {
+        self.violations.push(Violation {
+            instance_path: pointer(path),
+            keyword_location: pointer(location),
+            message,
+            position: None,
+        });
+    }
+
+    fn keyword(
+        &mut self,
+        keyword: &str,
+        value: &Value,
+        schema: &Value,
+        location: &mut Vec<String>,
+        instance: &Value,
+        path: &mut Vec<String>,
+    ) {
+        match keyword {
+            "$ref" => self.reference(value, location, instance, path),
+            "type" => {
+                if !type_matches(value, instance) {
+                    let expected = match value {
+                        Value::Array(names) => names
+                            .iter()
+                            .filter_map(Value::as_str)
+                            .collect::<Vec<&str>>()
+                            .join(" or "),
+                        value => value.as_str().unwrap_or_default().to_owned(),
+                    };
+                    let message = format!("expected {}, found {}", expected, type_of(instance));
+                    self.fail(location, path, message);
+                }
+            }
+            "enum" => {
+                let allowed = value.as_array().map_or(&[][..], |items| &items[..]);
+                if !allowed.iter().any(|item| values_equal(item, instance)) {
+                    let message = format!("{} is not one of the allowed values", instance);
+                    self.fail(location, path, message);
+                }
+            }
+            "const" => {
+                if !values_equal(value, instance) {
+                    self.fail(location, path, format!("value must be {}", value));
+                }
+            }
+            "allOf" | "anyOf" | "oneOf" => {
+                self.combination(keyword, value, location, instance, path)
+            }
+            "not" => {
+                if self.passes(value, location, instance, path) {
+                    let message = String::from("value must not match the schema in not");
+                    self.fail(location, path, message);
+                }
+            }
+            "if" => self.conditional(value, schema, location, instance, path),
+            _ => match instance {
+                Value::Number(number) => self.number(keyword, value, *number, location, path),
+                Value::String(string) => self.string(keyword, value, string, location, path),
+                Value::Array(items) => self.array(keyword, value, schema, items, location, path),
+                Value::Object(pairs) => self.object(keyword, value, schema, pairs, location, path),
+                _ => {}
+            },
+        }
+    }
+
+    fn reference(
+        &mut self,
+        reference: &Value,
+        location: &mut Vec<String>,
+        instance: &Value,
+        path: &mut Vec<String>,
+    ) {
+        let reference = reference.as_str().unwrap_or_default();
+        let target = match resolve(self.root, reference) {
+            Some(target) => target,
+            None => return,
+        };
+        let active = (reference.to_owned(), pointer(path));
+        if self.active_refs.contains(&active) {
+            return;
+        }
+
+        self.active_refs.push(active);
+        self.validate(target, location, instance, path);
+        self.active_refs.pop();
+    }
+
+    fn combination(
+        &mut self,
+        keyword: &str,
+        schemas: &Value,
+        location: &mut Vec<String>,
+        instance: &Value,
+        path: &mut Vec<String>,
+    ) {
+        let schemas = schemas.as_array().map_or(&[][..], |items| &items[..]);
+        if keyword == "allOf" {
+            for (index, schema) in schemas.iter().enumerate() {
+                location.push(index.to_string());
+                self.validate(schema, location, instance, path);
+                location.pop();
+            }
+            return;
+        }
+
+        let mut matching = 0;
+        for (index, schema) in schemas.iter().enumerate() {
+            location.push(index.to_string());
+            if self.passes(schema, location, instance, path) {
+                matching += 1;
+            }
+            location.pop();
+        }
+
+        match (keyword, matching) {
+            (_, 0) => {
+                let message = format!("value does not match any schema in {}", keyword);
+                self.fail(location, path, message);
+            }
+            ("oneOf", matching) if matching > 1 => {
+                let message = format!(
+                    "value matches {} schemas in oneOf, expected exactly one",
+                    matching
+                );
+                self.fail(location, path, message);
+            }
+            _ => {}
+        }
+    }
+
+    fn conditional(
+        &mut self,
+        condition: &Value,
+        schema: &Value,
+        location: &mut Vec<String>,
+        instance: &Value,
+        path: &mut Vec<String>,
+    ) {
+        let branch = match self.passes(condition, location, instance, path) {
+            true => "then",
+            false => "else",
+        };
+        if let Some(branch_schema) = schema.get(branch) {
+            location.pop();
+            location.push(String::from(branch));
+            self.validate(branch_schema, location, instance, path);
+            location.pop();
+            location.push(String::from("if"));
+        }
+    }
+
+    fn number(
+        &mut self,
+        keyword: &str,
+        limit: &Value,
+        number: f64,
+        location: &[String],
+        path: &[String],
+    ) {
+        let limit = match limit.as_f64() {
+            Some(limit) => limit,
+            None => return,
+        };
+        let message = match keyword {
+            "minimum" if number < limit => {
+                format!("{} is less than the minimum of {}", number, limit)
+            }
+            "maximum" if number > limit => {
+                format!("{} is greater than the maximum of {}", number, limit)
+            }
+            "exclusiveMinimum" if number <= limit => {
+                format!("{} must be greater than {}", number, limit)
+            }
+            "exclusiveMaximum" if number >= limit => {
+                format!("{} must be less than {}", number, limit)
+            }
+            "multipleOf" if limit > 0.0 && !is_multiple(number, limit) => {
+                format!("{} is not a multiple of {}", number, limit)
+            }
+            _ => return,
+        };
+        self.fail(location, path, message);
+    }
+
+    fn string(
+        &mut self,
+        keyword: &str,
+        value: &Value,
+        string: &str,
+        location: &[String],
+        path: &[String],
+    ) {
+        let length = string.chars().count();
+        let message = match (keyword, value) {
+            ("minLength", Value::Number(limit)) if (length as f64) < *limit => {
+                format!("string is shorter than {} characters", limit)
+            }
+            ("maxLength", Value::Number(limit)) if (length as f64) > *limit => {
+                format!("string is longer than {} characters", limit)
+            }
+            ("pattern", Value::String(pattern)) => match Pattern::new(pattern) {
+                Some(regex) if !regex.is_match(string) => {
+                    format!("string does not match the pattern '{}'", pattern)
+                }
+                _ => return,
+            },
+            _ => return,
+        };
+        self.fail(location, path, message);
+    }
+
+    fn array(
+        &mut self,
+        keyword: &str,
+        value: &Value,
+        schema: &Value,
+        items: &[Value],
+        location: &mut Vec<String>,
+        path: &mut Vec<String>,
+    ) {
+        match (keyword, value) {
+            ("minItems", Value::Number(limit)) if (items.len() as f64) < *limit => {
+                let message = format!("array has fewer than {} items", limit);
+                self.fail(location, path, message);
+            }
+            ("maxItems", Value::Number(limit)) if (items.len() as f64) > *limit => {
+                let message = format!("array has more than {} items", limit);
+                self.fail(location, path, message);
+            }
+            ("uniqueItems", Value::Bool(true)) => {
+                let duplicate = (0..items.len()).find_map(|first| {
+                    (first + 1..items.len())
+                        .find(|second| values_equal(&items[first], &items[*second]))
+                        .map(|second| (first, second))
+                });
+                if let Some((first, second)) = duplicate {
+                    let message = format!("items {} and {} are equal", first, second);
+                    self.fail(location, path, message);
+                }
+            }
+            ("prefixItems", Value::Array(schemas)) => {
+                for (index, (schema, item)) in schemas.iter().zip(items).enumerate() {
+                    location.push(index.to_string());
+                    path.push(index.to_string());
+                    self.validate(schema, location, item, path);
+                    path.pop();
+                    location.pop();
+                }
+            }
+            ("items", items_schema) => {
+                let skip = schema
+                    .get("prefixItems")
+                    .and_then(Value::as_array)
+                    .map_or(0, Vec::len);
+                for (index, item) in items.iter().enumerate().skip(skip) {
+                    path.push(index.to_string());
+                    self.validate(items_schema, location, item, path);
+                    path.pop();
+                }
+            }
+            ("contains", contains) => {
+                let mut matching = 0;
+                for (index, item) in items.iter().enumerate() {
+                    path.push(index.to_string());
+                    if self.passes(contains, location, item, path) {
+                        matching += 1;
+                    }
+                    path.pop();
+                }
+
+                let limit = |name: &str| schema.get(name).and_then(Value::as_f64);
+                let minimum = limit("minContains").unwrap_or(1.0);
+                let message = if (matching as f64) < minimum {
+                    match matching {
+                        0 => String::from("no items match the schema in contains"),
+                        _ => format!("fewer than {} items match the schema in contains", minimum),
+                    }
+                } else if limit("maxContains").is_some_and(|maximum| matching as f64 > maximum) {
+                    format!(
+                        "more than {} items match the schema in contains",
+                        limit("maxContains").unwrap_or_default()
+                    )
+                } else {
+                    return;
+                };
+                self.fail(location, path, message);
+            }
+            _ => {}
+        }
+    }
+
+    fn object(
+        &mut self,
+        keyword: &str,
+        value: &Value,
+        schema: &Value,
+        pairs: &[(String, Value)],
+        location: &mut Vec<String>,
+        path: &mut Vec<String>,
+    ) {
+        match (keyword, value) {
+            ("required", Value::Array(required)) => {
+                for name in required.iter().filter_map(Value::as_str) {
+                    if !pairs.iter().any(|(key, _)| key == name) {
+                        let message = format!("missing required property '{}'", name);
+                        self.fail(location, path, message);
+                    }
+                }
+            }
+            ("minProperties", Value::Number(limit)) if (pairs.len() as f64) < *limit => {
+                let message = format!("object has fewer than {} properties", limit);
+                self.fail(location, path, message);
+            }
+            ("maxProperties", Value::Number(limit)) if (pairs.len() as f64) > *limit => {
+                let message = format!("object has more than {} properties", limit);
+                self.fail(location, path, message);
+            }
+            ("properties", Value::Object(properties)) => {
+                for (name, property_schema) in properties {
+                    if let Some((_, item)) = pairs.iter().find(|(key, _)| key == name) {
+                        location.push(name.clone());
+                        path.push(name.clone());
+                        self.validate(property_schema, location, item, path);
+                        path.pop();
+                        location.pop();
+                    }
+                }
+            }
+            ("patternProperties", Value::Object(patterns)) => {
+                for (pattern, property_schema) in patterns {
+                    let regex = match Pattern::new(pattern) {
+                        Some(regex) => regex,
+                        None => continue,
+                    };
+                    for (key, item) in pairs.iter().filter(|(key, _)| regex.is_match(key)) {
+                        location.push(pattern.clone());
+                        path.push(key.clone());
+                        self.validate(property_schema, location, item, path);
+                        path.pop();
+                        location.pop();
+                    }
+                }
+            }
+            ("additionalProperties", additional) => {
+                for (key, item) in pairs.iter().filter(|(key, _)| is_additional(schema, key)) {
+                    path.push(key.clone());
+                    match additional {
+                        Value::Bool(false) => {
+                            let message = format!("property '{}' is not allowed", key);
+                            self.fail(location, path, message);
+                        }
+                        additional => self.validate(additional, location, item, path),
+                    }
+                    path.pop();
+                }
+            }
+            ("propertyNames", names) => {
+                for (key, _) in pairs {
+                    path.push(key.clone());
+                    self.validate(names, location, &Value::String(key.clone()), path);
+                    path.pop();
+                }
+            }
+            ("dependentRequired", Value::Object(dependencies)) => {
+                for (name, required) in dependencies {
+                    if !pairs.iter().any(|(key, _)| key == name) {
+                        continue;
+                    }
+                    let required = required.as_array().map_or(&[][..], |items| &items[..]);
+                    for dependency in required.iter().filter_map(Value::as_str) {
+                        if !pairs.iter().any(|(key, _)| key == dependency) {
+                            let message =
+                                format!("property '{}' requires property '{}'", name, dependency);
+                            self.fail(location, path, message);
+                        }
+                    }
+                }
+            }
+            ("dependentSchemas", Value::Object(dependencies)) => {
+                let object = Value::Object(pairs.to_vec());
+                for (name, dependent) in dependencies {
+                    if pairs.iter().any(|(key, _)| key == name) {
+                        location.push(name.clone());
+                        self.validate(dependent, location, &object, path);
+                        location.pop();
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Finds the schema a local `$ref` points to: `#`, a JSON Pointer fragment or a `$anchor`.
+pub fn resolve<'a>(root: &'a Value, reference: &str) -> Option<&'a Value> {
+    let fragment = percent_decode(reference.strip_prefix('#')?)?;
+    if fragment.is_empty() || fragment.starts_with('/') {
+        return root.pointer(&fragment);
+    }
+    find_anchor(root, &fragment)
+}
+
+fn find_anchor<'a>(schema: &'a Value, anchor: &str) -> Option<&'a Value> {
+    match schema {
+        Value::Object(keywords) => {
+            if schema.get("$anchor").and_then(Value::as_str) == Some(anchor) {
+                return Some(schema);
+            }
+            keywords
+                .iter()
+                .filter(|(keyword, _)| keyword != "enum" && keyword != "const")
+                .find_map(|(_, value)| find_anchor(value, anchor))
+        }
+        Value::Array(items) => items.iter().find_map(|item| find_anchor(item, anchor)),
+        _ => None,
+    }
+}
+
+fn percent_decode(fragment: &str) -> Option<String> {
+    let mut bytes = vec![];
+    let mut rest = fragment.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte == b'%' {
+            let hex = std::str::from_utf8(tail.get(..2)?).ok()?;
+            bytes.push(u8::from_str_radix(hex, 16).ok()?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(byte);
+            rest = tail;
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+fn is_additional(schema: &Value, key: &str) -> bool {
+    let named = schema
+        .get("properties")
+        .and_then(Value::as_object)
+        .is_some_and(|properties| properties.iter().any(|(name, _)| name == key));
+    let patterned = schema
+        .get("patternProperties")
+        .and_then(Value::as_object)
+        .is_some_and(|patterns| {
+            patterns
+                .iter()
+                .filter_map(|(pattern, _)| Pattern::new(pattern))
+                .any(|regex| regex.is_match(key))
+        });
+    !named && !patterned
+}
+
+fn type_matches(expected: &Value, instance: &Value) -> bool {
+    let matches = |name: &str| match name {
+        "integer" => instance
+            .as_f64()
+            .is_some_and(|number| number.fract() == 0.0),
+        "number" => instance.as_f64().is_some(),
+        name => name == type_of(instance),
+    };
+    match expected {
+        Value::String(name) => matches(name),
+        Value::Array(names) => names.iter().filter_map(Value::as_str).any(matches),
+        _ => true,
+    }
+}
+
+fn type_of(instance: &Value) -> &'static str {
+    match instance {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn is_multiple(number: f64, divisor: f64) -> bool {
+    let quotient = number / divisor;
+    (quotient - quotient.round()).abs() <= f64::EPSILON * quotient.abs().max(1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::schema::Schema;
+
+    fn messages(schema: &str, instance: &str) -> Vec<String> {
+        let schema = Schema::parse(schema).unwrap();
+        schema
+            .validate(&crate::parse(instance).unwrap())
+            .iter()
+            .map(|violation| violation.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn types_enums_and_consts() {
+        assert_eq!(
+            messages(r#"{"type": ["string", "null"]}"#, "1"),
+            vec!["(root): expected string or null, found number"]
+        );
+        assert_eq!(
+            messages(r#"{"type": "integer"}"#, "2.0"),
+            Vec::<String>::new()
+        );
+        assert_eq!(
+            messages(r#"{"enum": ["a", 1]}"#, r#""b""#),
+            vec![r#"(root): "b" is not one of the allowed values"#]
+        );
+        assert_eq!(
+            messages(r#"{"const": {"a": [1]}}"#, r#"{"a": [2]}"#),
+            vec![r#"(root): value must be {"a":[1]}"#]
+        );
+        assert_eq!(
+            messages("false", "null"),
+            vec!["(root): no value is allowed here"]
+        );
+    }
+
+    #[test]
+    fn numeric_and_string_limits() {
+        let schema = r#"{"properties": {
+            "a": {"minimum": 1, "exclusiveMaximum": 3},
+            "b": {"multipleOf": 0.1},
+            "c": {"minLength": 2, "pattern": "^[a-z]+$"}
+        }}"#;
+        assert_eq!(
+            messages(schema, r#"{"a": 3, "b": 0.3, "c": "A"}"#),
+            vec![
+                "/a: 3 must be less than 3",
+                "/c: string is shorter than 2 characters",
+                "/c: string does not match the pattern '^[a-z]+$'",
+            ]
+        );
+    }
+
+    #[test]
+    fn patterns_match_long_strings() {
+        let long = format!("\"{}\"", "a".repeat(100_000));
+        assert_eq!(
+            messages(r#"{"pattern": "^(a|a)*$"}"#, &long),
+            Vec::<String>::new()
+        );
+        assert_eq!(
+            messages(r#"{"pattern": "(a|a)*b"}"#, &long),
+            vec!["(root): string does not match the pattern '(a|a)*b'"]
+        );
+    }
+
+    #[test]
+    fn arrays() {
+        let schema = r#"{
+            "prefixItems": [{"type": "string"}],
+            "items": {"type": "number"},
+            "maxItems": 3,
+            "uniqueItems": true,
+            "contains": {"const": 1}, "maxContains": 1
+        }"#;
+        assert_eq!(messages(schema, r#"["a", 1, 2]"#), Vec::<String>::new());
+        assert_eq!(
+            messages(schema, r#"[1, 1, "b", 1]"#),
+            vec![
+                "/0: expected string, found number",
+                "/2: expected number, found string",
+                "(root): array has more than 3 items",
+                "(root): items 0 and 1 are equal",
+                "(root): more than 1 items match the schema in contains",
+            ]
+        );
+    }
+
+    #[test]
+    fn objects() {
+        let schema = r#"{
+            "properties": {"name": {"type": "string"}},
+            "patternProperties": {"^x-": true},
+            "additionalProperties": false,
+            "required": ["name", "id"],
+            "dependentRequired": {"name": ["version"]},
+            "propertyNames": {"maxLength": 8}
+        }"#;
+        assert_eq!(
+            messages(schema, r#"{"name": 1, "x-extra": 2, "unexpected": 3}"#),
+            vec![
+                "/name: expected string, found number",
+                "/unexpected: property 'unexpected' is not allowed",
+                "(root): missing required property 'id'",
+                "(root): property 'name' requires property 'version'",
+                "/unexpected: string is longer than 8 characters",
+            ]
+        );
+    }
+
+    #[test]
+    fn applicators_and_conditionals() {
+        let schema = r#"{
+            "oneOf": [{"type": "number"}, {"type": "integer"}],
+            "not": {"const": 5},
+            "if": {"minimum": 10}, "then": {"multipleOf": 10}, "else": {"maximum": 3}
+        }"#;
+        assert_eq!(
+            messages(schema, "5"),
+            vec![
+                "(root): value matches 2 schemas in oneOf, expected exactly one",
+                "(root): value must not match the schema in not",
+                "(root): 5 is greater than the maximum of 3",
+            ]
+        );
+        assert_eq!(
+            messages(schema, "15.5"),
+            vec!["(root): 15.5 is not a multiple of 10"]
+        );
+        assert_eq!(
+            messages(r#"{"anyOf": [{"type": "null"}, {"type": "array"}]}"#, "{}"),
+            vec!["(root): value does not match any schema in anyOf"]
+        );
+    }
+
+    #[test]
+    fn local_references() {
+        let schema = r##"{
+            "$defs": {
+                "node": {
+                    "type": "object",
+                    "properties": {"children": {"items": {"$ref": "#/$defs/node"}}},
+                    "required": ["name"]
+                },
+                "named": {"$anchor": "name", "type": "string"}
+            },
+            "$ref": "#/$defs/node",
+            "properties": {"name": {"$ref": "#name"}}
+        }"##;
+        assert_eq!(
+            messages(schema, r#"{"name": 1, "children": [{"name": "a"}, {}]}"#),
+            vec![
+                "/children/1: missing required property 'name'",
+                "/name: expected string, found number",
+            ]
+        );
+        assert_eq!(messages(r##"{"$ref": "#"}"##, "1"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn keyword_locations_point_into_the_schema() {
+        let schema =
+            Schema::parse(r#"{"allOf": [{"properties": {"a": {"type": "null"}}}]}"#).unwrap();
+        let violations = schema.validate(&crate::parse(r#"{"a": 1}"#).unwrap());
+        assert_eq!(violations[0].keyword_location, "/allOf/0/properties/a/type");
+        assert_eq!(violations[0].instance_path, "/a");
+    }
+}
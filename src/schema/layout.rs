@@ -0,0 +1,117 @@
+use crate::value::Value;
+
+use super::keywords::resolve;
+use super::Schema;
+
+/// The schema that applies at one point of a document being printed.
+#[derive(Clone, Copy)]
+pub struct SchemaCursor<'a> {
+    root: &'a Value,
+    schema: &'a Value,
+}
+
+impl<'a> SchemaCursor<'a> {
+    pub fn new(schema: &'a Schema) -> SchemaCursor<'a> {
+        SchemaCursor {
+            root: &schema.root,
+            schema: &schema.root,
+        }
+    }
+
+    /// The keys `properties` declares, in declaration order, including those reached
+    /// through `$ref` and `allOf`.
+    pub fn property_order(&self) -> Vec<&'a str> {
+        let mut order = vec![];
+        for schema in self.subschemas() {
+            let properties = schema.get("properties").and_then(Value::as_object);
+            for (key, _) in properties.into_iter().flatten() {
+                if !order.contains(&key.as_str()) {
+                    order.push(key.as_str());
+                }
+            }
+        }
+        order
+    }
+
+    pub fn property(&self, key: &str) -> Option<SchemaCursor<'a>> {
+        self.subschemas()
+            .into_iter()
+            .find_map(|schema| schema.get("properties")?.get(key))
+            .map(|schema| self.at(schema))
+    }
+
+    pub fn item(&self, index: usize) -> Option<SchemaCursor<'a>> {
+        self.subschemas()
+            .into_iter()
+            .find_map(|schema| {
+                let prefix = schema.get("prefixItems").and_then(Value::as_array);
+                match prefix.and_then(|prefix| prefix.get(index)) {
+                    Some(item) => Some(item),
+                    None => schema.get("items"),
+                }
+            })
+            .map(|schema| self.at(schema))
+    }
+
+    fn at(&self, schema: &'a Value) -> SchemaCursor<'a> {
+        SchemaCursor {
+            root: self.root,
+            schema,
+        }
+    }
+
+    /// This schema followed by every schema it pulls in through `$ref` and `allOf`.
+    fn subschemas(&self) -> Vec<&'a Value> {
+        let mut found: Vec<&'a Value> = vec![];
+        let mut pending = vec![self.schema];
+        while let Some(schema) = pending.pop() {
+            if found.iter().any(|seen| std::ptr::eq(*seen, schema)) {
+                continue;
+            }
+            found.push(schema);
+
+            let all_of = schema.get("allOf").and_then(Value::as_array);
+            pending.extend(all_of.into_iter().flatten().rev());
+            let reference = schema.get("$ref").and_then(Value::as_str);
+            pending.extend(reference.and_then(|reference| resolve(self.root, reference)));
+        }
+        found
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn follows_references_and_all_of() {
+        let schema = Schema::parse(
+            r##"{
+                "$defs": {"base": {"properties": {"id": {}, "name": {"properties": {"first": {}}}}}},
+                "allOf": [{"$ref": "#/$defs/base"}, {"properties": {"tags": {"items": {"properties": {"x": {}}}}}}],
+                "properties": {"version": {}}
+            }"##,
+        )
+        .unwrap();
+        let cursor = SchemaCursor::new(&schema);
+
+        assert_eq!(
+            cursor.property_order(),
+            vec!["version", "id", "name", "tags"]
+        );
+        assert_eq!(
+            cursor.property("name").unwrap().property_order(),
+            vec!["first"]
+        );
+        assert_eq!(
+            cursor
+                .property("tags")
+                .unwrap()
+                .item(3)
+                .unwrap()
+                .property_order(),
+            vec!["x"]
+        );
+        assert!(cursor.property("missing").is_none());
+    }
+}
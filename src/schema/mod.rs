@@ -0,0 +1,208 @@
+//! Validation against JSON Schema draft 2020-12.
+use std::fmt;
+
+use crate::cst;
+use crate::formatter::errors::FormatterError;
+use crate::regex::Regex;
+use crate::value::{to_pointer, Value};
+
+mod check;
+mod infer;
+mod keywords;
+mod layout;
+pub use infer::infer_schema;
+pub(crate) use layout::SchemaCursor;
+
+/// A schema that has been checked for keywords this validator cannot apply.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Schema {
+    root: Value,
+}
+
+impl Schema {
+    /// Fails with `InvalidSchema` and the keyword's location when the schema is malformed,
+    /// refers outside itself or uses a pattern outside I-Regexp (RFC 9485).
+    pub fn new(schema: Value) -> Result<Schema, FormatterError> {
+        check::check(&schema)?;
+        Ok(Schema { root: schema })
+    }
+
+    pub fn parse(input: &str) -> Result<Schema, FormatterError> {
+        Schema::new(crate::parse(input)?)
+    }
+
+    pub fn as_value(&self) -> &Value {
+        &self.root
+    }
+
+    /// Every violation in `instance`, in the order the schema finds them.
+    pub fn validate(&self, instance: &Value) -> Vec<Violation> {
+        let mut validator = keywords::Validator::new(&self.root);
+        validator.validate(&self.root, &mut vec![], instance, &mut vec![]);
+        validator.violations
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct SourcePosition {
+    /// 1-based line number.
+    pub line: usize,
+    /// 1-based column, counted in characters.
+    pub column: usize,
+    /// Byte offset of the value in the source.
+    pub offset: usize,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Violation {
+    /// JSON Pointer to the offending value in the document.
+    pub instance_path: String,
+    /// JSON Pointer to the schema keyword that failed.
+    pub keyword_location: String,
+    pub message: String,
+    /// Where the offending value starts, when the document was validated from source text.
+    pub position: Option<SourcePosition>,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(position) = self.position {
+            write!(f, "{}:{}: ", position.line, position.column)?;
+        }
+        match self.instance_path.as_str() {
+            "" => write!(f, "(root): {}", self.message),
+            path => write!(f, "{}: {}", path, self.message),
+        }
+    }
+}
+
+/// Parses `input` and validates it against `schema`, locating each violation in the source.
+pub fn validate(input: &str, schema: &Schema) -> Result<Vec<Violation>, FormatterError> {
+    let instance = crate::parse(input)?;
+    let document = cst::parse(input)?;
+
+    Ok(schema
+        .validate(&instance)
+        .into_iter()
+        .map(|violation| {
+            let position = document
+                .pointer(&violation.instance_path)
+                .map(|element| position_of(input, element.offset()));
+            Violation {
+                position,
+                ..violation
+            }
+        })
+        .collect())
+}
+
+fn position_of(input: &str, offset: usize) -> SourcePosition {
+    let before = &input[..offset];
+    let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+    SourcePosition {
+        line: before.matches('\n').count() + 1,
+        column: before[line_start..].chars().count() + 1,
+        offset,
+    }
+}
+
+/// A `pattern` regular expression, matched as I-Regexp. A leading `^` or trailing `$` anchors
+/// the match the way it would in ECMA-262; without them the pattern may match anywhere.
+struct Pattern {
+    regex: Regex,
+    at_start: bool,
+    at_end: bool,
+}
+
+impl Pattern {
+    fn new(pattern: &str) -> Option<Pattern> {
+        let (at_start, rest) = match pattern.strip_prefix('^') {
+            Some(rest) => (true, rest),
+            None => (false, pattern),
+        };
+        let escapes = rest
+            .strip_suffix('$')
+            .unwrap_or(rest)
+            .chars()
+            .rev()
+            .take_while(|character| *character == '\\')
+            .count();
+        let (at_end, rest) = match rest.strip_suffix('$') {
+            Some(stripped) if escapes % 2 == 0 => (true, stripped),
+            _ => (false, rest),
+        };
+
+        // I-Regexp has no anchors, so a literal `$` is written without the escape.
+        let mut translated = String::with_capacity(rest.len());
+        let mut characters = rest.chars().peekable();
+        while let Some(character) = characters.next() {
+            match (character, characters.peek()) {
+                ('\\', Some('$')) => {}
+                ('\\', Some(&escaped)) => {
+                    translated.push(character);
+                    translated.push(escaped);
+                    characters.next();
+                }
+                _ => translated.push(character),
+            }
+        }
+
+        Some(Pattern {
+            regex: Regex::new(&translated)?,
+            at_start,
+            at_end,
+        })
+    }
+
+    fn is_match(&self, input: &str) -> bool {
+        self.regex
+            .is_anchored_match(input, self.at_start, self.at_end)
+    }
+}
+
+fn pointer(path: &[String]) -> String {
+    to_pointer(path.iter())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn patterns_honour_anchors() {
+        let anchored = Pattern::new("^a+$").unwrap();
+        assert!(anchored.is_match("aaa"));
+        assert!(!anchored.is_match("baaa"));
+
+        let unanchored = Pattern::new("a+").unwrap();
+        assert!(unanchored.is_match("baaab"));
+
+        let literal = Pattern::new("a\\$").unwrap();
+        assert!(literal.is_match("xa$y"));
+
+        let escaped_backslash = Pattern::new("a\\\\$").unwrap();
+        assert!(escaped_backslash.is_match("xa\\"));
+        assert!(!escaped_backslash.is_match("a\\y"));
+    }
+
+    #[test]
+    fn violations_are_located_in_the_source() {
+        let schema = Schema::parse(r#"{"properties": {"port": {"type": "integer"}}}"#).unwrap();
+        let input = "{\n  \"port\": \"80\"\n}";
+        let violations = validate(input, &schema).unwrap();
+
+        assert_eq!(violations.len(), 1);
+        assert_eq!(
+            violations[0].position,
+            Some(SourcePosition {
+                line: 2,
+                column: 11,
+                offset: 12
+            })
+        );
+        assert_eq!(
+            violations[0].to_string(),
+            "2:11: /port: expected integer, found string"
+        );
+    }
+}
@@ -0,0 +1,367 @@
+//! Source maps between a document and its formatted output, in byte offsets.
+use std::ops::Range;
+
+use crate::cst::{self, SyntaxElement, SyntaxKind, SyntaxNode};
+use crate::formatter::errors::FormatterError;
+use crate::formatter::{stringify_with_spans, FormatConfig, Markup};
+use crate::parser::{self, ParseContext};
+use crate::value::to_pointer;
+
+/// Where one value, or one object member (key included), is in the output and in the input.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Mapping {
+    /// The value's JSON Pointer. A member has the pointer of its value.
+    pub pointer: String,
+    /// `Object`, `Array` or `Member`, or the kind of a scalar token, as it was printed.
+    pub kind: SyntaxKind,
+    pub output: Range<usize>,
+    pub input: Range<usize>,
+}
+
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct SourceMap {
+    mappings: Vec<Mapping>,
+}
+
+impl SourceMap {
+    /// Every mapping, in output order. A value printed that wasn't in the input, like a
+    /// `"$schema"` member added by `with_schema_key`, has none.
+    pub fn mappings(&self) -> &[Mapping] {
+        &self.mappings
+    }
+
+    /// The innermost value or member printed at `offset` in the output.
+    pub fn at_output(&self, offset: usize) -> Option<&Mapping> {
+        innermost(&self.mappings, offset, |mapping| &mapping.output)
+    }
+
+    /// The innermost value or member at `offset` in the input.
+    pub fn at_input(&self, offset: usize) -> Option<&Mapping> {
+        innermost(&self.mappings, offset, |mapping| &mapping.input)
+    }
+
+    /// The input position matching `offset` in the output. Inside a token the position is the
+    /// same distance from its start; between values it follows the preceding value, and
+    /// brackets map to brackets.
+    pub fn to_input(&self, offset: usize) -> Option<usize> {
+        translate(
+            &self.mappings,
+            offset,
+            |mapping| &mapping.output,
+            |mapping| &mapping.input,
+        )
+    }
+
+    /// The output position matching `offset` in the input, the reverse of `to_input`.
+    pub fn to_output(&self, offset: usize) -> Option<usize> {
+        translate(
+            &self.mappings,
+            offset,
+            |mapping| &mapping.input,
+            |mapping| &mapping.output,
+        )
+    }
+}
+
+/// Formats `input` like `format` and maps every value and member of the output back to the
+/// input, as the printer recorded it. Colour, HTML and previews are turned off so the output
+/// is plain JSON.
+pub fn format_with_source_map(
+    input: String,
+    config: Option<FormatConfig>,
+) -> Result<(String, SourceMap), FormatterError> {
+    let mut config = config.unwrap_or_default();
+    config.markup = Markup::Plain;
+    config.preview = None;
+    config.detect_line_ending(&input);
+
+    let source = cst::parse(&input)?;
+    let ast = parser::parse(&source, &mut ParseContext::for_config(&config))?;
+    let (formatted, spans) = stringify_with_spans(ast, &config);
+    let output = cst::parse(&formatted)?;
+    let mut mappings = vec![];
+    if let (Some(output), Some(root)) = (output.value(), source.value()) {
+        let walk = Walk {
+            spans: &spans,
+            source: &source,
+        };
+        walk.pair(&mut vec![], output, root, &mut mappings);
+    }
+    Ok((formatted, SourceMap { mappings }))
+}
+
+/// Follows the output tree, finding each member or item in the input where the printer said it
+/// came from. Members are never paired by key, which duplicate keys would get wrong.
+struct Walk<'a> {
+    spans: &'a [(Range<usize>, Range<usize>)],
+    source: &'a SyntaxNode,
+}
+
+impl Walk<'_> {
+    /// The input element the output `element` was printed from, if it was in the input.
+    fn origin(&self, element: &SyntaxElement) -> Option<&SyntaxElement> {
+        let output = element.offset()..element.end();
+        let (_, input) = self.spans.iter().find(|(printed, _)| *printed == output)?;
+        find(self.source, input)
+    }
+
+    /// Maps `output` to `source`, then their members or items.
+    fn pair(
+        &self,
+        path: &mut Vec<String>,
+        output: &SyntaxElement,
+        source: &SyntaxElement,
+        mappings: &mut Vec<Mapping>,
+    ) {
+        mappings.push(Mapping {
+            pointer: to_pointer(path.iter()),
+            kind: output.kind(),
+            output: output.offset()..output.end(),
+            input: source.offset()..source.end(),
+        });
+        let output = match output {
+            SyntaxElement::Node(output) => output,
+            SyntaxElement::Token(_) => return,
+        };
+
+        match output.kind() {
+            SyntaxKind::Object => {
+                let members = output
+                    .children()
+                    .iter()
+                    .filter(|child| child.kind() == SyntaxKind::Member);
+                for member in members {
+                    if let (SyntaxElement::Node(printed), Some(SyntaxElement::Node(found))) =
+                        (member, self.origin(member))
+                    {
+                        path.push(printed.key().unwrap_or_default());
+                        self.pair_member(path, printed, found, mappings);
+                        path.pop();
+                    }
+                }
+            }
+            SyntaxKind::Array => {
+                let items = output.children().iter().filter(|child| child.is_value());
+                for (index, item) in items.enumerate() {
+                    if let Some(found) = self.origin(item) {
+                        path.push(index.to_string());
+                        self.pair(path, item, found, mappings);
+                        path.pop();
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn pair_member(
+        &self,
+        path: &mut Vec<String>,
+        output: &SyntaxNode,
+        source: &SyntaxNode,
+        mappings: &mut Vec<Mapping>,
+    ) {
+        mappings.push(Mapping {
+            pointer: to_pointer(path.iter()),
+            kind: SyntaxKind::Member,
+            output: output.offset()..output.end(),
+            input: source.offset()..source.end(),
+        });
+        if let (Some(output), Some(source)) = (output.value(), source.value()) {
+            self.pair(path, output, source, mappings);
+        }
+    }
+}
+
+/// The member or value of `node` that covers exactly `range`.
+fn find<'a>(node: &'a SyntaxNode, range: &Range<usize>) -> Option<&'a SyntaxElement> {
+    let child = node
+        .children()
+        .iter()
+        .find(|child| child.offset() <= range.start && range.end <= child.end())?;
+    match child {
+        _ if child.offset() == range.start && child.end() == range.end => Some(child),
+        SyntaxElement::Node(child) => find(child, range),
+        SyntaxElement::Token(_) => None,
+    }
+}
+
+/// The mapping with the smallest span containing `offset`.
+fn innermost(
+    mappings: &[Mapping],
+    offset: usize,
+    span: impl Fn(&Mapping) -> &Range<usize>,
+) -> Option<&Mapping> {
+    mappings
+        .iter()
+        .filter(|mapping| span(mapping).contains(&offset))
+        .min_by_key(|mapping| span(mapping).len())
+}
+
+fn translate(
+    mappings: &[Mapping],
+    offset: usize,
+    from: impl Fn(&Mapping) -> &Range<usize>,
+    to: impl Fn(&Mapping) -> &Range<usize>,
+) -> Option<usize> {
+    let container = innermost(mappings, offset, &from)?;
+    let (outer, target) = (from(container), to(container));
+    if matches!(container.kind, SyntaxKind::Object | SyntaxKind::Array) && offset == outer.end - 1 {
+        return Some(target.end - 1);
+    }
+
+    let inside = mappings
+        .iter()
+        .filter(|mapping| *mapping != container)
+        .filter(|mapping| outer.start <= from(mapping).start && from(mapping).end <= outer.end);
+    // The value just before `offset`, and the start of the next one, both in `to`'s terms.
+    let before = inside
+        .clone()
+        .filter(|mapping| from(mapping).end <= offset)
+        .max_by_key(|mapping| from(mapping).end);
+    let after = inside
+        .filter(|mapping| from(mapping).start > offset)
+        .map(|mapping| to(mapping).start)
+        .min()
+        .unwrap_or(target.end);
+
+    let (anchor, distance) = match before {
+        Some(mapping) => (to(mapping).end, offset - from(mapping).end),
+        None => (target.start, offset - outer.start),
+    };
+    // Never run into the next value.
+    let limit = after.max(anchor + 1) - 1;
+    Some((anchor + distance).min(limit))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DuplicateKeyPolicy;
+
+    const INPUT: &str = r#"{"name":"jsonist", "tags":["a","b"],"size":{}}"#;
+
+    fn offset(text: &str, needle: &str) -> usize {
+        text.find(needle).unwrap()
+    }
+
+    #[test]
+    fn maps_every_value_and_member() {
+        let (output, map) = format_with_source_map(INPUT.to_owned(), None).unwrap();
+        let pointers = map
+            .mappings()
+            .iter()
+            .map(|mapping| (mapping.pointer.as_str(), mapping.kind))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            pointers,
+            vec![
+                ("", SyntaxKind::Object),
+                ("/name", SyntaxKind::Member),
+                ("/name", SyntaxKind::StringLiteral),
+                ("/tags", SyntaxKind::Member),
+                ("/tags", SyntaxKind::Array),
+                ("/tags/0", SyntaxKind::StringLiteral),
+                ("/tags/1", SyntaxKind::StringLiteral),
+                ("/size", SyntaxKind::Member),
+                ("/size", SyntaxKind::Object),
+            ]
+        );
+        for mapping in map.mappings() {
+            let printed = &output[mapping.output.clone()];
+            let source = &INPUT[mapping.input.clone()];
+            assert_eq!(
+                printed.split_whitespace().collect::<String>(),
+                source.split_whitespace().collect::<String>()
+            );
+        }
+    }
+
+    #[test]
+    fn translates_positions_both_ways() {
+        let (output, map) = format_with_source_map(INPUT.to_owned(), None).unwrap();
+        for needle in [r#""b""#, r#""tags""#, "json", "ags"] {
+            assert_eq!(
+                map.to_input(offset(&output, needle)),
+                Some(offset(INPUT, needle)),
+                "{}",
+                needle
+            );
+            assert_eq!(
+                map.to_output(offset(INPUT, needle)),
+                Some(offset(&output, needle)),
+                "{}",
+                needle
+            );
+        }
+        // Brackets map to brackets, gaps follow the value before them.
+        assert_eq!(map.to_input(offset(&output, "]")), Some(offset(INPUT, "]")));
+        assert_eq!(map.to_input(output.len() - 2), Some(INPUT.len() - 1));
+        assert_eq!(
+            map.to_input(
+                offset(
+                    &output,
+                    r#"",
+    "tags""#
+                ) + 1
+            ),
+            Some(offset(INPUT, r#"", "tags""#) + 1)
+        );
+        assert_eq!(map.to_input(output.len() + 5), None);
+        assert_eq!(
+            map.at_input(offset(INPUT, "json")).unwrap().pointer,
+            "/name"
+        );
+    }
+
+    #[test]
+    fn follows_members_a_schema_moved() {
+        let schema =
+            crate::schema::Schema::parse(r#"{"properties": {"size": {}, "tags": {}, "name": {}}}"#)
+                .unwrap();
+        let config = FormatConfig::default()
+            .with_schema(schema)
+            .with_schema_key("https://example.com/schema");
+        let (output, map) = format_with_source_map(INPUT.to_owned(), Some(config)).unwrap();
+        assert!(output.find("size") < output.find("name"));
+        assert_eq!(
+            map.to_output(offset(INPUT, "jsonist")),
+            Some(offset(&output, "jsonist"))
+        );
+        assert_eq!(
+            map.at_output(offset(&output, "example")).unwrap().pointer,
+            ""
+        );
+    }
+
+    #[test]
+    fn follows_the_member_a_duplicate_key_policy_kept() {
+        let input = r#"{"a": 1, "b": true, "a": 2}"#;
+        let config = FormatConfig::default().with_duplicate_keys(DuplicateKeyPolicy::KeepLast);
+        let (output, map) = format_with_source_map(input.to_owned(), Some(config)).unwrap();
+        assert_eq!(output, "{\n    \"a\": 2,\n    \"b\": true\n}\n");
+
+        let two = map.at_output(offset(&output, "2")).unwrap();
+        assert_eq!((two.pointer.as_str(), two.input.clone()), ("/a", 25..26));
+        let member = map.at_output(offset(&output, "\"a\"")).unwrap();
+        assert_eq!(&input[member.input.clone()], r#""a": 2"#);
+        // The dropped member has nothing in the output of its own.
+        assert_eq!(map.at_input(offset(input, "1")).unwrap().pointer, "");
+    }
+
+    #[test]
+    fn follows_members_merged_from_duplicate_keys() {
+        let input = r#"{"a": {"x": 1}, "a": {"y": 2}}"#;
+        let config = FormatConfig::default().with_duplicate_keys(DuplicateKeyPolicy::Merge);
+        let (output, map) = format_with_source_map(input.to_owned(), Some(config)).unwrap();
+
+        for needle in ["\"x\"", "\"y\"", "2"] {
+            assert_eq!(
+                map.to_input(offset(&output, needle)),
+                Some(offset(input, needle)),
+                "{}",
+                needle
+            );
+        }
+    }
+}
@@ -0,0 +1,364 @@
+//! Statistics about a document's shape, to find out what makes it large.
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+use crate::cst::{self, SyntaxElement, SyntaxKind};
+use crate::formatter::errors::FormatterError;
+use crate::formatter::thousands;
+use crate::value::{to_pointer, Value};
+
+pub struct StatsConfig {
+    top: usize,
+}
+
+impl StatsConfig {
+    pub fn new() -> StatsConfig {
+        StatsConfig { top: 10 }
+    }
+
+    /// How many entries to keep in each ranking, 10 by default.
+    pub fn with_top(mut self, top: usize) -> StatsConfig {
+        self.top = top;
+        self
+    }
+}
+
+impl Default for StatsConfig {
+    fn default() -> Self {
+        StatsConfig::new()
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Stats {
+    /// The size of the input.
+    pub bytes: usize,
+    /// How deeply containers nest: 0 for a lone scalar, 1 for `[1]`, 2 for `[[1]]`.
+    pub max_depth: usize,
+    pub objects: usize,
+    pub arrays: usize,
+    pub strings: usize,
+    pub numbers: usize,
+    pub booleans: usize,
+    pub nulls: usize,
+    /// The JSON Pointer and byte size of each member (or item) of the top-level value, key
+    /// included, largest first.
+    pub subtrees: Vec<(String, usize)>,
+    /// The JSON Pointer and length of the largest arrays, largest first.
+    pub largest_arrays: Vec<(String, usize)>,
+    /// The JSON Pointer and length in characters of the longest strings, longest first.
+    pub longest_strings: Vec<(String, usize)>,
+    /// How many times each key is used, most used first.
+    pub keys: Vec<(String, usize)>,
+}
+
+/// Analyses the document in `input`. Rankings are cut to `StatsConfig::with_top` entries and
+/// ties keep document order.
+pub fn stats(input: &str, config: Option<StatsConfig>) -> Result<Stats, FormatterError> {
+    let config = config.unwrap_or_default();
+    let document = crate::parse(input)?;
+    let tree = cst::parse(input)?;
+
+    let mut stats = Stats {
+        bytes: input.len(),
+        ..Stats::default()
+    };
+    walk(&mut vec![], &document, 0, &mut stats, &mut HashMap::new());
+    if let Some(SyntaxElement::Node(root)) = tree.value() {
+        let items = root.children().iter().filter_map(|child| match child {
+            SyntaxElement::Node(node) if node.kind() == SyntaxKind::Member => {
+                let pointer = to_pointer(node.key());
+                Some((pointer, node.end() - node.offset()))
+            }
+            _ => None,
+        });
+        stats.subtrees = match root.kind() {
+            SyntaxKind::Object => items.collect(),
+            _ => root
+                .children()
+                .iter()
+                .filter(|child| child.is_value())
+                .enumerate()
+                .map(|(index, item)| (format!("/{}", index), item.end() - item.offset()))
+                .collect(),
+        };
+    }
+
+    for ranking in [
+        &mut stats.subtrees,
+        &mut stats.largest_arrays,
+        &mut stats.longest_strings,
+        &mut stats.keys,
+    ] {
+        ranking.sort_by_key(|(_, size)| Reverse(*size));
+    }
+    for ranking in [
+        &mut stats.largest_arrays,
+        &mut stats.longest_strings,
+        &mut stats.keys,
+    ] {
+        ranking.truncate(config.top);
+    }
+    Ok(stats)
+}
+
+/// `keys` is where each key is in `stats.keys`, so counting stays fast on large documents.
+fn walk(
+    path: &mut Vec<String>,
+    value: &Value,
+    depth: usize,
+    stats: &mut Stats,
+    keys: &mut HashMap<String, usize>,
+) {
+    match value {
+        Value::Object(pairs) => {
+            stats.objects += 1;
+            stats.max_depth = stats.max_depth.max(depth + 1);
+            for (key, member) in pairs {
+                match keys.get(key) {
+                    Some(index) => stats.keys[*index].1 += 1,
+                    None => {
+                        keys.insert(key.clone(), stats.keys.len());
+                        stats.keys.push((key.clone(), 1));
+                    }
+                }
+                path.push(key.clone());
+                walk(path, member, depth + 1, stats, keys);
+                path.pop();
+            }
+        }
+        Value::Array(items) => {
+            stats.arrays += 1;
+            stats.max_depth = stats.max_depth.max(depth + 1);
+            stats
+                .largest_arrays
+                .push((to_pointer(path.iter()), items.len()));
+            for (index, item) in items.iter().enumerate() {
+                path.push(index.to_string());
+                walk(path, item, depth + 1, stats, keys);
+                path.pop();
+            }
+        }
+        Value::String(string) => {
+            stats.strings += 1;
+            stats
+                .longest_strings
+                .push((to_pointer(path.iter()), string.chars().count()));
+        }
+        Value::Number(_) => stats.numbers += 1,
+        Value::Bool(_) => stats.booleans += 1,
+        Value::Null => stats.nulls += 1,
+    }
+}
+
+/// The statistics as an object for a machine readable report.
+pub fn report(stats: &Stats) -> Value {
+    let number = |count: usize| Value::Number(count as f64);
+    let entry = |name: &str, value: Value| (String::from(name), value);
+    let ranking = |ranking: &[(String, usize)], name: &str, count: &str| {
+        Value::Array(
+            ranking
+                .iter()
+                .map(|(key, size)| {
+                    Value::Object(vec![
+                        entry(name, Value::String(key.clone())),
+                        entry(count, number(*size)),
+                    ])
+                })
+                .collect(),
+        )
+    };
+
+    Value::Object(vec![
+        entry("bytes", number(stats.bytes)),
+        entry("maxDepth", number(stats.max_depth)),
+        entry(
+            "counts",
+            Value::Object(vec![
+                entry("objects", number(stats.objects)),
+                entry("arrays", number(stats.arrays)),
+                entry("strings", number(stats.strings)),
+                entry("numbers", number(stats.numbers)),
+                entry("booleans", number(stats.booleans)),
+                entry("nulls", number(stats.nulls)),
+            ]),
+        ),
+        entry("subtrees", ranking(&stats.subtrees, "path", "bytes")),
+        entry(
+            "largestArrays",
+            ranking(&stats.largest_arrays, "path", "items"),
+        ),
+        entry(
+            "longestStrings",
+            ranking(&stats.longest_strings, "path", "characters"),
+        ),
+        entry("keys", ranking(&stats.keys, "key", "count")),
+    ])
+}
+
+/// A plain text summary, one section per statistic.
+pub fn render(stats: &Stats) -> String {
+    let mut text = format!(
+        "Size: {} bytes\nMax depth: {}\nValues: {} objects, {} arrays, {} strings, {} numbers, {} booleans, {} nulls\n",
+        thousands(stats.bytes),
+        stats.max_depth,
+        thousands(stats.objects),
+        thousands(stats.arrays),
+        thousands(stats.strings),
+        thousands(stats.numbers),
+        thousands(stats.booleans),
+        thousands(stats.nulls),
+    );
+    let percent = |bytes: usize| match stats.bytes {
+        0 => 0.0,
+        total => bytes as f64 * 100.0 / total as f64,
+    };
+    write_ranking(
+        &mut text,
+        "Largest top-level values",
+        &stats.subtrees,
+        |bytes| format!("{} bytes ({:.1}%)", thousands(bytes), percent(bytes)),
+    );
+    write_ranking(
+        &mut text,
+        "Largest arrays",
+        &stats.largest_arrays,
+        |items| format!("{} items", thousands(items)),
+    );
+    write_ranking(
+        &mut text,
+        "Longest strings",
+        &stats.longest_strings,
+        |characters| format!("{} characters", thousands(characters)),
+    );
+    write_ranking(&mut text, "Most used keys", &stats.keys, thousands);
+    text
+}
+
+/// A titled section with one aligned line per entry, or nothing for an empty ranking.
+fn write_ranking(
+    text: &mut String,
+    title: &str,
+    ranking: &[(String, usize)],
+    describe: impl Fn(usize) -> String,
+) {
+    if ranking.is_empty() {
+        return;
+    }
+    text.push_str(&format!("\n{}:\n", title));
+    let width = ranking
+        .iter()
+        .map(|(name, _)| display_name(name).chars().count())
+        .max()
+        .unwrap_or(0);
+    for (name, size) in ranking {
+        let name = display_name(name);
+        let padding = width - name.chars().count();
+        text.push_str(&format!(
+            "  {}{}  {}\n",
+            name,
+            " ".repeat(padding),
+            describe(*size)
+        ));
+    }
+}
+
+fn display_name(name: &str) -> &str {
+    match name {
+        "" => "(root)",
+        name => name,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DOCUMENT: &str = r#"{"users": [{"id": 1, "name": "Ada Lovelace", "tags": []}, {"id": 2, "name": "Al", "admin": true, "manager": null}], "count": 2}"#;
+
+    #[test]
+    fn counts_values_and_depth() {
+        let stats = stats(DOCUMENT, None).unwrap();
+        assert_eq!(stats.bytes, DOCUMENT.len());
+        assert_eq!(stats.max_depth, 4);
+        assert_eq!(
+            (
+                stats.objects,
+                stats.arrays,
+                stats.strings,
+                stats.numbers,
+                stats.booleans,
+                stats.nulls
+            ),
+            (3, 2, 2, 3, 1, 1)
+        );
+        assert_eq!(super::stats("1", None).unwrap().max_depth, 0);
+    }
+
+    #[test]
+    fn ranks_arrays_strings_and_keys() {
+        let stats = stats(DOCUMENT, Some(StatsConfig::new().with_top(2))).unwrap();
+        let owned = |entries: &[(&str, usize)]| {
+            entries
+                .iter()
+                .map(|(name, size)| (name.to_string(), *size))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            stats.largest_arrays,
+            owned(&[("/users", 2), ("/users/0/tags", 0)])
+        );
+        assert_eq!(
+            stats.longest_strings,
+            owned(&[("/users/0/name", 12), ("/users/1/name", 2)])
+        );
+        assert_eq!(stats.keys, owned(&[("id", 2), ("name", 2)]));
+    }
+
+    #[test]
+    fn measures_top_level_subtrees() {
+        let stats = stats(DOCUMENT, None).unwrap();
+        assert_eq!(stats.subtrees[0].0, "/users");
+        assert_eq!(
+            stats.subtrees[1],
+            (String::from("/count"), r#""count": 2"#.len())
+        );
+        assert_eq!(
+            super::stats("[[1, 2], 3]", None).unwrap().subtrees,
+            vec![(String::from("/0"), 6), (String::from("/1"), 1)]
+        );
+    }
+
+    #[test]
+    fn renders_a_summary() {
+        let stats = stats(
+            r#"{"a": [1, 2, 3], "bb": "xy"}"#,
+            Some(StatsConfig::new().with_top(1)),
+        )
+        .unwrap();
+        assert_eq!(
+            render(&stats),
+            "Size: 28 bytes
+Max depth: 2
+Values: 1 objects, 1 arrays, 1 strings, 3 numbers, 0 booleans, 0 nulls
+
+Largest top-level values:
+  /a   14 bytes (50.0%)
+  /bb  10 bytes (35.7%)
+
+Largest arrays:
+  /a  3 items
+
+Longest strings:
+  /bb  2 characters
+
+Most used keys:
+  a  1
+"
+        );
+        assert_eq!(
+            report(&stats).get("counts").unwrap().to_string(),
+            r#"{"objects":1,"arrays":1,"strings":1,"numbers":3,"booleans":0,"nulls":0}"#
+        );
+    }
+}
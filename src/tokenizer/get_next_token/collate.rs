@@ -8,13 +8,14 @@ pub fn process_expectation(
     indexed_characters: IndexedCharacters,
 ) -> Result<Token, FormatterError> {
     let mut indexed_characters = indexed_characters;
-    let mut character_position = indexed_characters.get_index();
+    let start = indexed_characters.get_index();
 
-    for expected_character in literal.chars() {
+    for (character_position, expected_character) in (start..).zip(literal.chars()) {
         if let Some(&character) = indexed_characters.current_character() {
             if character != expected_character {
                 // Wrong character error
                 return Err(FormatterError::WrongCharacter {
+                    position: start,
                     attempted_token_literal: literal,
                     expected_character,
                     wrong_character: character,
@@ -25,12 +26,13 @@ pub fn process_expectation(
         };
 
         indexed_characters = indexed_characters.progress();
-        character_position = character_position + 1;
     }
 
     Ok(token)
 }
 
+/// Collects a string literal exactly as written between its quotes, escapes included,
+/// so the literal can be printed back out without changing its meaning.
 pub fn process_string_literal(
     indexed_characters: IndexedCharacters,
 ) -> Result<Token, FormatterError> {
@@ -38,26 +40,26 @@ pub fn process_string_literal(
     let mut indexed_characters = indexed_characters.progress();
     let mut character_position = position + 1;
     let mut literal = "".to_owned();
+    let mut escaped = false;
     loop {
         if let Some(&character) = indexed_characters.current_character() {
             match &character {
-                '\"' => {
-                    let prev_character = indexed_characters.previous_character().unwrap();
-                    if *prev_character != '\\' {
-                        return Ok(Token::StringLiteral(position, literal));
-                    } else {
-                        literal.push(character)
-                    }
+                '\"' if !escaped => return Ok(Token::StringLiteral(position, literal)),
+                '\\' if !escaped => {
+                    escaped = true;
+                    literal.push(character)
+                }
+                value => {
+                    escaped = false;
+                    literal.push(*value)
                 }
-                '\\' => {}
-                value @ _ => literal.push(*value),
             }
         } else {
             return Err(FormatterError::ExpectedMoreCharacters(character_position));
         };
 
         indexed_characters = indexed_characters.progress();
-        character_position = character_position + 1;
+        character_position += 1;
     }
 }
 
@@ -80,7 +82,7 @@ mod tests {
 
     #[test]
     #[should_panic(
-        expected = "Wrong Character: found (l) when expecting (s) while trying to build token false"
+        expected = "Wrong Character: found (l) when expecting (s) while trying to build token false at position (0)."
     )]
     fn expect_failure_when_false_is_spelt_wrong() {
         let json = "fall";
@@ -125,7 +127,7 @@ mod tests {
         let json = r#""tes\"ter""#;
         let chars = json.chars().collect::<Vec<char>>();
         let indexed_characters = IndexedCharacters::new(&chars);
-        let expectation = Token::StringLiteral(0, String::from("tes\"ter"));
+        let expectation = Token::StringLiteral(0, String::from(r#"tes\"ter"#));
         match process_string_literal(indexed_characters) {
             Ok(result) => assert_eq!(result, expectation),
             Err(e) => panic!("{}", e),
@@ -137,7 +139,19 @@ mod tests {
         let json = r#""tes\"te\"   \"r""#;
         let chars = json.chars().collect::<Vec<char>>();
         let indexed_characters = IndexedCharacters::new(&chars);
-        let expectation = Token::StringLiteral(0, String::from("tes\"te\"   \"r"));
+        let expectation = Token::StringLiteral(0, String::from(r#"tes\"te\"   \"r"#));
+        match process_string_literal(indexed_characters) {
+            Ok(result) => assert_eq!(result, expectation),
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    #[test]
+    fn string_literal_ending_in_an_escaped_backslash() {
+        let json = r#""tes\\" "#;
+        let chars = json.chars().collect::<Vec<char>>();
+        let indexed_characters = IndexedCharacters::new(&chars);
+        let expectation = Token::StringLiteral(0, String::from(r#"tes\\"#));
         match process_string_literal(indexed_characters) {
             Ok(result) => assert_eq!(result, expectation),
             Err(e) => panic!("{}", e),
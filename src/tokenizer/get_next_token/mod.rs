@@ -36,8 +36,11 @@ pub fn get_next_token(indexed_characters: IndexedCharacters) -> Result<Token, Fo
         ' ' => WhiteSpace(position, ' '),
         '\n' => WhiteSpace(position, '\n'),
         '\t' => WhiteSpace(position, '\t'),
-        &literal @ _ if literal.is_ascii_digit() || literal == '-' => process_number_literal(indexed_characters)?,
-        &literal @ _ => {
+        '\r' => WhiteSpace(position, '\r'),
+        &literal if literal.is_ascii_digit() || literal == '-' => {
+            process_number_literal(indexed_characters)?
+        }
+        &literal => {
             return Err(FormatterError::InvalidTokenStartCharacter(
                 position, literal,
             ))
@@ -106,6 +109,11 @@ mod tests {
             "\t",
             "Can't create WhiteSpace for a tab"
         );
+        can_create_token!(
+            WhiteSpace(0, '\r'),
+            "\r",
+            "Can't create WhiteSpace for a carriage return"
+        );
     }
 
     #[test]
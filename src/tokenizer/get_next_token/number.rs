@@ -3,8 +3,9 @@ use super::super::Token;
 use crate::formatter::errors::FormatterError;
 
 pub fn check_end_for_e(token_position: usize, literal: String) -> Result<Token, FormatterError> {
-    if literal.len() != 0 && literal.chars().rev().next().unwrap() == 'e' {
-        Err(FormatterError::NumberLiteralEndingInE())
+    if !literal.is_empty() && literal.ends_with('e') {
+        let e_position = token_position + literal.chars().count() - 1;
+        Err(FormatterError::NumberLiteralEndingInE(e_position))
     } else {
         Ok(Token::Number(token_position, literal))
     }
@@ -22,12 +23,19 @@ pub fn process_number_literal(
     loop {
         if let Some(&character) = indexed_characters.current_character() {
             match &character {
-                ',' | ']' | '}' | ' ' | '\n' | '\t' => {
+                ',' | ']' | '}' | ' ' | '\n' | '\r' | '\t' => {
                     return check_end_for_e(token_position, literal)
                 }
-                value @ _ if value.is_ascii_digit() || *value == '.' || *value == 'e' || *value == '-' => {
-                    if *value == '-' && literal.len() != 0 {
-                        return Err(FormatterError::NumberCanNotHaveANegativeSignNotAtHead());
+                value
+                    if value.is_ascii_digit()
+                        || *value == '.'
+                        || *value == 'e'
+                        || *value == '-' =>
+                {
+                    if *value == '-' && !literal.is_empty() {
+                        return Err(FormatterError::NumberCanNotHaveANegativeSignNotAtHead(
+                            character_position,
+                        ));
                     }
 
                     // No second dots
@@ -49,7 +57,7 @@ pub fn process_number_literal(
 
                     literal.push(*value)
                 }
-                value @ _ => {
+                value => {
                     return Err(FormatterError::InvalidNumberCharacter(
                         character_position,
                         *value,
@@ -61,7 +69,7 @@ pub fn process_number_literal(
         };
 
         indexed_characters = indexed_characters.progress();
-        character_position = character_position + 1;
+        character_position += 1;
     }
 }
 
@@ -81,6 +89,18 @@ mod tests {
         }
     }
 
+    #[test]
+    fn number_literal_ends_at_a_carriage_return() {
+        let json = "12\r\n";
+        let chars = json.chars().collect::<Vec<char>>();
+        let indexed_characters = IndexedCharacters::new(&chars);
+        let expectation = Token::Number(0, String::from("12"));
+        match process_number_literal(indexed_characters) {
+            Ok(result) => assert_eq!(result, expectation),
+            Err(e) => panic!("{}", e),
+        }
+    }
+
     #[test]
     #[should_panic(expected = "Character (f) at postition (4) is not valid in a number.")]
     fn number_can_not_contain_letters_other_than_e() {
@@ -123,7 +143,9 @@ mod tests {
     }
 
     #[test]
-    #[should_panic(expected = "A number literal can not end with an 'e' character")]
+    #[should_panic(
+        expected = "A number literal can not end with an 'e' character, found at position (3)."
+    )]
     fn number_literal_can_not_end_with_an_e() {
         let json = r#"2.3e"#;
         let chars = json.chars().collect::<Vec<char>>();
@@ -148,7 +170,9 @@ mod tests {
     }
 
     #[test]
-    #[should_panic(expected = "Number can not have a - at a position other than the start of string")]
+    #[should_panic(
+        expected = "Number can not have a - at a position other than the start of string, found at position (1)"
+    )]
     fn number_literals_no_negative_symbol_at_postitions_other_than_the_first() {
         let json = r#"2-.34"#;
         let chars = json.chars().collect::<Vec<char>>();
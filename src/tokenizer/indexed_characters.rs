@@ -16,10 +16,6 @@ impl<'a> IndexedCharacters<'a> {
         self.jump(1)
     }
 
-    pub fn previous_character(self) -> Option<&'a char> {
-        self.characters.get(self.index - 1)
-    }
-
     pub fn current_character(self) -> Option<&'a char> {
         self.characters.get(self.index)
     }
@@ -30,7 +26,7 @@ impl<'a> IndexedCharacters<'a> {
 
     pub fn jump(&self, jump: usize) -> IndexedCharacters<'a> {
         IndexedCharacters {
-            characters: &self.characters,
+            characters: self.characters,
             index: self.index + jump,
         }
     }
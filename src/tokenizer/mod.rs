@@ -1,7 +1,7 @@
 use crate::formatter::errors::FormatterError;
 
 mod utils;
-use utils::{get_end_index, get_start_index};
+pub(crate) use utils::{get_end_index, get_start_index};
 
 mod get_next_token;
 use get_next_token::get_next_token;
@@ -34,7 +34,7 @@ pub fn tokenize(input: &str) -> Result<Vec<Token>, FormatterError> {
     let mut tokens: Vec<Token> = vec![];
 
     loop {
-        if let Some(_) = indexed_characters.current_character() {
+        if indexed_characters.current_character().is_some() {
             let token = get_next_token(indexed_characters)?;
 
             let start_index = get_start_index(&token);
@@ -25,7 +25,7 @@ pub fn get_end_index(token: &Token) -> usize {
         True(position, literal) => position + literal.len() - 1,
         False(position, literal) => position + literal.len() - 1,
         Number(position, literal) => position + literal.len() - 1,
-        StringLiteral(position, literal) => position + literal.len() + 1, // Add 2 for the quotes
+        StringLiteral(position, literal) => position + literal.chars().count() + 1, // Add 2 for the quotes
         _ => get_start_index(token),
     }
 }
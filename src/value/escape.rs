@@ -0,0 +1,101 @@
+/// Decodes the escapes of a string literal as written in the source (without its quotes).
+pub fn unescape(literal: &str) -> String {
+    let mut unescaped = String::with_capacity(literal.len());
+    let mut characters = literal.chars();
+
+    while let Some(character) = characters.next() {
+        if character != '\\' {
+            unescaped.push(character);
+            continue;
+        }
+
+        match characters.next() {
+            Some('b') => unescaped.push('\u{8}'),
+            Some('f') => unescaped.push('\u{c}'),
+            Some('n') => unescaped.push('\n'),
+            Some('r') => unescaped.push('\r'),
+            Some('t') => unescaped.push('\t'),
+            Some('u') => {
+                let high = read_hex(&mut characters);
+                let code = if (0xD800..0xDC00).contains(&high) {
+                    let mut lookahead = characters.clone();
+                    match (lookahead.next(), lookahead.next()) {
+                        (Some('\\'), Some('u')) => {
+                            let low = read_hex(&mut lookahead);
+                            if (0xDC00..0xE000).contains(&low) {
+                                characters = lookahead;
+                                0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+                            } else {
+                                high
+                            }
+                        }
+                        _ => high,
+                    }
+                } else {
+                    high
+                };
+                unescaped.push(std::char::from_u32(code).unwrap_or('\u{FFFD}'));
+            }
+            Some(other) => unescaped.push(other),
+            None => unescaped.push('\\'),
+        }
+    }
+
+    unescaped
+}
+
+fn read_hex(characters: &mut std::str::Chars) -> u32 {
+    characters
+        .take(4)
+        .collect::<String>()
+        .chars()
+        .try_fold(0, |code, character| {
+            character.to_digit(16).map(|digit| code * 16 + digit)
+        })
+        .unwrap_or(0xFFFD)
+}
+
+/// Escapes a string so it can be written between quotes in JSON.
+pub fn escape(string: &str) -> String {
+    let mut escaped = String::with_capacity(string.len());
+
+    for character in string.chars() {
+        match character {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            '\u{8}' => escaped.push_str("\\b"),
+            '\u{c}' => escaped.push_str("\\f"),
+            control if (control as u32) < 0x20 => {
+                escaped.push_str(&format!("\\u{:04x}", control as u32))
+            }
+            other => escaped.push(other),
+        }
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unescape_simple_escapes() {
+        assert_eq!(unescape(r#"a\"b\\c\/d\ne\tf"#), "a\"b\\c/d\ne\tf");
+    }
+
+    #[test]
+    fn unescape_unicode_escapes() {
+        assert_eq!(unescape(r#"\u00e9\ud83d\ude00"#), "é😀");
+    }
+
+    #[test]
+    fn escape_round_trips() {
+        let string = "say \"hi\"\n\\ \u{1}";
+        assert_eq!(escape(string), r#"say \"hi\"\n\\ \u0001"#);
+        assert_eq!(unescape(&escape(string)), string);
+    }
+}
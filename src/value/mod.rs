@@ -0,0 +1,259 @@
+use std::fmt;
+
+use crate::formatter::{stringify_with_config, FormatConfig};
+use crate::parser::{Item, Node, AST};
+
+mod escape;
+pub(crate) use escape::{escape, unescape};
+
+mod pointer;
+pub(crate) use pointer::{parse_pointer, to_pointer};
+
+/// A parsed JSON document with its strings decoded and its object keys kept in source order.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Value>),
+    Object(Vec<(String, Value)>),
+}
+
+impl Value {
+    /// Looks up `key` when this is an object.
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Object(pairs) => pairs
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(string) => Some(string),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Number(number) => Some(*number),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Bool(boolean) => Some(*boolean),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&Vec<Value>> {
+        match self {
+            Value::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    pub fn as_object(&self) -> Option<&Vec<(String, Value)>> {
+        match self {
+            Value::Object(pairs) => Some(pairs),
+            _ => None,
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, Value::Null)
+    }
+
+    /// Pretty prints the value the same way `format` prints a document.
+    pub fn format(&self, config: Option<FormatConfig>) -> String {
+        let config = config.unwrap_or_default();
+        stringify_with_config(
+            AST {
+                root: Node::from(self.clone()),
+            },
+            &config,
+        )
+    }
+
+    /// `format`, with `LineEnding::Auto` settled from `input`, the text the value came from,
+    /// so a CRLF file comes back with CRLF.
+    pub fn format_like(&self, input: &str, config: Option<FormatConfig>) -> String {
+        let mut config = config.unwrap_or_default();
+        config.detect_line_ending(input);
+        self.format(Some(config))
+    }
+}
+
+/// Deep equality where object member order does not matter.
+pub(crate) fn values_equal(left: &Value, right: &Value) -> bool {
+    match (left, right) {
+        (Value::Array(left), Value::Array(right)) => {
+            left.len() == right.len()
+                && left
+                    .iter()
+                    .zip(right.iter())
+                    .all(|(left, right)| values_equal(left, right))
+        }
+        (Value::Object(left), Value::Object(right)) => {
+            left.len() == right.len()
+                && left.iter().all(|(key, value)| {
+                    right
+                        .iter()
+                        .find(|(other, _)| other == key)
+                        .is_some_and(|(_, other)| values_equal(value, other))
+                })
+        }
+        (left, right) => left == right,
+    }
+}
+
+/// Writes the value as compact JSON. JSON has no infinities or NaN, so those are written as
+/// `null`, like JavaScript's `JSON.stringify` does.
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Null => write!(f, "null"),
+            Value::Bool(boolean) => write!(f, "{}", boolean),
+            Value::Number(number) if !number.is_finite() => write!(f, "null"),
+            Value::Number(number) => write!(f, "{}", number),
+            Value::String(string) => write!(f, "\"{}\"", escape(string)),
+            Value::Array(items) => {
+                write!(f, "[")?;
+                for (index, item) in items.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, "]")
+            }
+            Value::Object(pairs) => {
+                write!(f, "{{")?;
+                for (index, (key, value)) in pairs.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "\"{}\":{}", escape(key), value)?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+impl From<Node> for Value {
+    fn from(node: Node) -> Value {
+        match node {
+            Node::Object { pairs } => Value::Object(
+                pairs
+                    .into_iter()
+                    .filter_map(|pair| match pair.node {
+                        Node::Pair { key, value } => match *key {
+                            Node::Literal { literal } => {
+                                Some((unescape(&literal), Value::from(*value)))
+                            }
+                            _ => None,
+                        },
+                        _ => None,
+                    })
+                    .collect(),
+            ),
+            Node::Array { items } => Value::Array(
+                items
+                    .into_iter()
+                    .map(|item| Value::from(item.node))
+                    .collect(),
+            ),
+            Node::Pair { value, .. } => Value::from(*value),
+            Node::Literal { literal } => Value::String(unescape(&literal)),
+            Node::Number { value } => Value::Number(value),
+            Node::True => Value::Bool(true),
+            Node::False => Value::Bool(false),
+            Node::Null => Value::Null,
+        }
+    }
+}
+
+impl From<Value> for Node {
+    fn from(value: Value) -> Node {
+        match value {
+            Value::Null => Node::Null,
+            Value::Bool(true) => Node::True,
+            Value::Bool(false) => Node::False,
+            Value::Number(value) => Node::Number { value },
+            Value::String(string) => Node::Literal {
+                literal: escape(&string),
+            },
+            Value::Array(items) => Node::Array {
+                items: items
+                    .into_iter()
+                    .map(|item| Item::from(Node::from(item)))
+                    .collect(),
+            },
+            Value::Object(pairs) => Node::Object {
+                pairs: pairs
+                    .into_iter()
+                    .map(|(key, value)| {
+                        Item::from(Node::Pair {
+                            key: Box::new(Node::Literal {
+                                literal: escape(&key),
+                            }),
+                            value: Box::new(Node::from(value)),
+                        })
+                    })
+                    .collect(),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_is_compact() {
+        let value = Value::Object(vec![
+            (
+                String::from("a\"b"),
+                Value::Array(vec![Value::Number(1.5), Value::Null]),
+            ),
+            (String::from("c"), Value::Bool(true)),
+        ]);
+
+        assert_eq!(value.to_string(), r#"{"a\"b":[1.5,null],"c":true}"#);
+    }
+
+    #[test]
+    fn display_never_writes_non_finite_numbers() {
+        let value = Value::Array(vec![
+            Value::Number(f64::INFINITY),
+            Value::Number(f64::NEG_INFINITY),
+            Value::Number(f64::NAN),
+        ]);
+
+        assert_eq!(value.to_string(), "[null,null,null]");
+    }
+
+    #[test]
+    fn node_strings_are_decoded() {
+        let node = Node::Literal {
+            literal: String::from(r#"tab\there"#),
+        };
+
+        assert_eq!(Value::from(node), Value::String(String::from("tab\there")));
+    }
+
+    #[test]
+    fn format_uses_the_formatter() {
+        let value = Value::Array(vec![Value::String(String::from("x"))]);
+        assert_eq!(value.format(None), "[\n    \"x\"\n]");
+    }
+}
@@ -0,0 +1,277 @@
+use crate::formatter::errors::FormatterError;
+
+use super::Value;
+
+/// Splits an RFC 6901 JSON Pointer into its unescaped reference tokens.
+pub fn parse_pointer(pointer: &str) -> Result<Vec<String>, FormatterError> {
+    if pointer.is_empty() {
+        return Ok(vec![]);
+    }
+    if !pointer.starts_with('/') {
+        return Err(FormatterError::InvalidPointer(pointer.to_owned()));
+    }
+
+    pointer[1..]
+        .split('/')
+        .map(|token| {
+            let mut unescaped = String::with_capacity(token.len());
+            let mut characters = token.chars();
+            while let Some(character) = characters.next() {
+                match (character, character == '~') {
+                    (_, false) => unescaped.push(character),
+                    (_, true) => match characters.next() {
+                        Some('0') => unescaped.push('~'),
+                        Some('1') => unescaped.push('/'),
+                        _ => return Err(FormatterError::InvalidPointer(pointer.to_owned())),
+                    },
+                }
+            }
+            Ok(unescaped)
+        })
+        .collect()
+}
+
+/// Escapes a key for use as a JSON Pointer reference token.
+pub fn escape_token(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+/// Builds a JSON Pointer from unescaped reference tokens.
+pub fn to_pointer<I, S>(tokens: I) -> String
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    tokens
+        .into_iter()
+        .map(|token| format!("/{}", escape_token(token.as_ref())))
+        .collect()
+}
+
+/// An array index token: digits without leading zeros.
+fn index_of(token: &str) -> Option<usize> {
+    if token.is_empty() || (token.len() > 1 && token.starts_with('0')) {
+        return None;
+    }
+    if !token.chars().all(|character| character.is_ascii_digit()) {
+        return None;
+    }
+    token.parse::<usize>().ok()
+}
+
+impl Value {
+    /// Looks up a value by JSON Pointer, e.g. `value.pointer("/address/city")`.
+    pub fn pointer(&self, pointer: &str) -> Option<&Value> {
+        let mut current = self;
+        for token in parse_pointer(pointer).ok()? {
+            current = match current {
+                Value::Object(pairs) => pairs
+                    .iter()
+                    .find(|(key, _)| *key == token)
+                    .map(|(_, value)| value)?,
+                Value::Array(items) => items.get(index_of(&token)?)?,
+                _ => return None,
+            };
+        }
+        Some(current)
+    }
+
+    pub fn pointer_mut(&mut self, pointer: &str) -> Option<&mut Value> {
+        let mut current = self;
+        for token in parse_pointer(pointer).ok()? {
+            current = match current {
+                Value::Object(pairs) => pairs
+                    .iter_mut()
+                    .find(|(key, _)| *key == token)
+                    .map(|(_, value)| value)?,
+                Value::Array(items) => items.get_mut(index_of(&token)?)?,
+                _ => return None,
+            };
+        }
+        Some(current)
+    }
+
+    /// Adds `value` at `pointer` the way a JSON Patch `add` does: object members are set
+    /// (returning any value replaced), array items are inserted, and `-` appends to an array.
+    pub fn insert_at_pointer(
+        &mut self,
+        pointer: &str,
+        value: Value,
+    ) -> Result<Option<Value>, FormatterError> {
+        let mut tokens = parse_pointer(pointer)?;
+        let last = match tokens.pop() {
+            Some(last) => last,
+            None => return Ok(Some(std::mem::replace(self, value))),
+        };
+        let not_found = || FormatterError::PointerNotFound(pointer.to_owned());
+        let parent = self
+            .pointer_mut(&to_pointer(&tokens))
+            .ok_or_else(not_found)?;
+
+        match parent {
+            Value::Object(pairs) => match pairs.iter_mut().find(|(key, _)| *key == last) {
+                Some((_, existing)) => Ok(Some(std::mem::replace(existing, value))),
+                None => {
+                    pairs.push((last, value));
+                    Ok(None)
+                }
+            },
+            Value::Array(items) => {
+                let index = match last.as_str() {
+                    "-" => items.len(),
+                    token => index_of(token).ok_or_else(not_found)?,
+                };
+                if index > items.len() {
+                    return Err(not_found());
+                }
+                items.insert(index, value);
+                Ok(None)
+            }
+            _ => Err(not_found()),
+        }
+    }
+
+    /// Removes and returns the value at `pointer`.
+    pub fn remove_at_pointer(&mut self, pointer: &str) -> Result<Value, FormatterError> {
+        let mut tokens = parse_pointer(pointer)?;
+        let not_found = || FormatterError::PointerNotFound(pointer.to_owned());
+        let last = tokens.pop().ok_or_else(not_found)?;
+        let parent = self
+            .pointer_mut(&to_pointer(&tokens))
+            .ok_or_else(not_found)?;
+
+        match parent {
+            Value::Object(pairs) => {
+                let index = pairs
+                    .iter()
+                    .position(|(key, _)| *key == last)
+                    .ok_or_else(not_found)?;
+                Ok(pairs.remove(index).1)
+            }
+            Value::Array(items) => match index_of(&last) {
+                Some(index) if index < items.len() => Ok(items.remove(index)),
+                _ => Err(not_found()),
+            },
+            _ => Err(not_found()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn document() -> Value {
+        Value::Object(vec![
+            (
+                String::from("address"),
+                Value::Object(vec![(
+                    String::from("city"),
+                    Value::String(String::from("a large one")),
+                )]),
+            ),
+            (
+                String::from("a/b~c"),
+                Value::Array(vec![Value::Number(1.0), Value::Number(2.0)]),
+            ),
+        ])
+    }
+
+    #[test]
+    fn parse_pointer_unescapes_tokens() {
+        assert_eq!(parse_pointer("").unwrap(), Vec::<String>::new());
+        assert_eq!(parse_pointer("/a~1b~0c/0").unwrap(), vec!["a/b~c", "0"]);
+        assert_eq!(parse_pointer("/~01").unwrap(), vec!["~1"]);
+        assert_eq!(
+            parse_pointer("a"),
+            Err(FormatterError::InvalidPointer(String::from("a")))
+        );
+        assert_eq!(
+            parse_pointer("/~2"),
+            Err(FormatterError::InvalidPointer(String::from("/~2")))
+        );
+    }
+
+    #[test]
+    fn to_pointer_escapes_tokens() {
+        assert_eq!(to_pointer(vec!["a/b~c", "0"]), "/a~1b~0c/0");
+    }
+
+    #[test]
+    fn pointer_finds_values() {
+        let document = document();
+        assert_eq!(
+            document.pointer("/address/city"),
+            Some(&Value::String(String::from("a large one")))
+        );
+        assert_eq!(document.pointer("/a~1b~0c/1"), Some(&Value::Number(2.0)));
+        assert_eq!(document.pointer("/a~1b~0c/01"), None);
+        assert_eq!(document.pointer("/missing"), None);
+        assert_eq!(document.pointer(""), Some(&document));
+    }
+
+    #[test]
+    fn pointer_mut_changes_values() {
+        let mut document = document();
+        *document.pointer_mut("/address/city").unwrap() = Value::Null;
+        assert_eq!(document.pointer("/address/city"), Some(&Value::Null));
+    }
+
+    #[test]
+    fn insert_at_pointer_adds_members_and_items() {
+        let mut document = document();
+
+        assert_eq!(
+            document.insert_at_pointer("/address/zip", Value::Number(1.0)),
+            Ok(None)
+        );
+        assert_eq!(
+            document.insert_at_pointer("/a~1b~0c/0", Value::Null),
+            Ok(None)
+        );
+        assert_eq!(
+            document.insert_at_pointer("/a~1b~0c/-", Value::Bool(true)),
+            Ok(None)
+        );
+        assert_eq!(
+            document.insert_at_pointer("/address/zip", Value::Number(2.0)),
+            Ok(Some(Value::Number(1.0)))
+        );
+
+        assert_eq!(document.pointer("/address/zip"), Some(&Value::Number(2.0)));
+        assert_eq!(
+            document.pointer("/a~1b~0c"),
+            Some(&Value::Array(vec![
+                Value::Null,
+                Value::Number(1.0),
+                Value::Number(2.0),
+                Value::Bool(true)
+            ]))
+        );
+        assert_eq!(
+            document.insert_at_pointer("/a~1b~0c/9", Value::Null),
+            Err(FormatterError::PointerNotFound(String::from("/a~1b~0c/9")))
+        );
+    }
+
+    #[test]
+    fn remove_at_pointer_takes_values_out() {
+        let mut document = document();
+
+        assert_eq!(
+            document.remove_at_pointer("/a~1b~0c/0"),
+            Ok(Value::Number(1.0))
+        );
+        assert_eq!(
+            document.remove_at_pointer("/address"),
+            Ok(Value::Object(vec![(
+                String::from("city"),
+                Value::String(String::from("a large one"))
+            )]))
+        );
+        assert_eq!(
+            document.remove_at_pointer("/address"),
+            Err(FormatterError::PointerNotFound(String::from("/address")))
+        );
+    }
+}
@@ -2,14 +2,19 @@ extern crate jsonist;
 
 use std::fs;
 
-use jsonist::{format, Delimiter, DelimiterCount, FormatConfig, FormatterError};
+use jsonist::cst;
+
+use jsonist::{
+    format, format_with_warnings, Delimiter, DelimiterCount, DuplicateKeyPolicy, FormatConfig,
+    FormatterError,
+};
 
 #[test]
 fn integration_can_use_errors_in_public_interface() {
     let json = r#"
         {
             "name": "Peter",
-            "name": 2,
+            "name": 2
         }
     "#
     .to_owned();
@@ -25,6 +30,54 @@ fn integration_can_use_errors_in_public_interface() {
     }
 }
 
+#[test]
+fn integration_duplicate_keys_can_be_merged() {
+    let json = r#"
+        {
+            "address": { "city": "a large one" },
+            "name": "Peter",
+            "address": { "street_name": "lets not put this online" }
+        }
+    "#
+    .to_owned();
+
+    let config = FormatConfig::new(Delimiter::Spaces(DelimiterCount::Two))
+        .with_duplicate_keys(DuplicateKeyPolicy::Merge);
+
+    let expected = r#"{
+  "address": {
+    "city": "a large one",
+    "street_name": "lets not put this online"
+  },
+  "name": "Peter"
+}
+"#;
+
+    match format(json, Some(config)) {
+        Ok(value) => assert_eq!(value, expected),
+        Err(e) => panic!("{}", e),
+    }
+}
+
+#[test]
+fn integration_duplicate_keys_can_be_kept_with_warnings() {
+    let json = r#"{ "name": "Peter", "name": 2 }"#.to_owned();
+
+    let config = FormatConfig::new(Delimiter::Spaces(DelimiterCount::Two))
+        .with_duplicate_keys(DuplicateKeyPolicy::KeepAll);
+
+    match format_with_warnings(json, Some(config)) {
+        Ok((value, warnings)) => {
+            assert_eq!(value, "{\n  \"name\": \"Peter\",\n  \"name\": 2\n}\n");
+            assert_eq!(
+                warnings,
+                vec![FormatterError::DuplicateKeyEntry(String::from("name"))]
+            );
+        }
+        Err(e) => panic!("{}", e),
+    }
+}
+
 #[test]
 fn integration_test_max_depth_one() {
     let json = r#"
@@ -97,3 +150,208 @@ fn integration_test_more_depth_tabs() {
         Err(e) => panic!("{}", e),
     }
 }
+
+#[test]
+fn integration_blank_lines_can_be_kept() {
+    let json = complex_json("./tests/input/sample.json");
+
+    let expected_out_file_path = "./tests/output/con_parse_complex_json_four_spaces.json";
+    let expected_contents = fs::read_to_string(expected_out_file_path)
+        .expect("No output file")
+        .replacen(
+            "\"Washington, DC, USA\",\n",
+            "\"Washington, DC, USA\",\n\n",
+            1,
+        );
+
+    let config = FormatConfig::default().with_blank_lines(true);
+
+    match format(json, Some(config)) {
+        Ok(value) => assert_eq!(value, expected_contents),
+        Err(e) => panic!("{}", e),
+    }
+}
+
+#[test]
+fn integration_escaped_strings_are_kept_as_written() {
+    let json = r#"{"quote": "say \"hi\"", "path": "C:\\"}"#.to_owned();
+
+    let expected = "{\n    \"quote\": \"say \\\"hi\\\"\",\n    \"path\": \"C:\\\\\"\n}\n";
+
+    match format(json, None) {
+        Ok(value) => assert_eq!(value, expected),
+        Err(e) => panic!("{}", e),
+    }
+}
+
+#[test]
+fn integration_syntax_tree_round_trips_the_input() {
+    let json = complex_json("./tests/input/sample.json");
+
+    match cst::parse(&json) {
+        Ok(document) => assert_eq!(document.to_string(), json),
+        Err(e) => panic!("{}", e),
+    }
+}
+
+#[test]
+fn integration_pointers_edit_a_parsed_document() {
+    let json = complex_json("./tests/input/sample.json");
+    let mut document = jsonist::parse(&json).expect("sample parses");
+
+    assert_eq!(
+        document.pointer("/origin_addresses/0"),
+        Some(&jsonist::Value::String(String::from("New York, NY, USA")))
+    );
+
+    document
+        .remove_at_pointer("/rows")
+        .expect("rows can be removed");
+    document
+        .insert_at_pointer("/status", jsonist::Value::Null)
+        .expect("status can be replaced");
+
+    assert!(document.format(None).ends_with("\"status\": null\n}\n"));
+}
+
+#[test]
+fn integration_queries_find_values_and_their_spans() {
+    let json = complex_json("./tests/input/sample.json");
+    let matches = jsonist::query::query(
+        &json,
+        "$.rows[0].elements[?@.distance.text == '227 mi'].status",
+    )
+    .expect("query runs");
+
+    assert_eq!(matches.len(), 1);
+    assert_eq!(
+        matches[0].path.to_string(),
+        "$['rows'][0]['elements'][0]['status']"
+    );
+    assert_eq!(matches[0].path.to_pointer(), "/rows/0/elements/0/status");
+    assert_eq!(&json[matches[0].span.clone()], "\"OK\"");
+}
+
+#[test]
+fn integration_patches_round_trip_between_documents() {
+    let json = complex_json("./tests/input/sample.json");
+    let before = jsonist::parse(&json).expect("sample parses");
+    let mut after = before.clone();
+    after
+        .insert_at_pointer("/destination_addresses/1", jsonist::Value::Null)
+        .expect("item can be inserted");
+    after
+        .remove_at_pointer("/rows/0/elements/2")
+        .expect("item can be removed");
+
+    let generated = jsonist::patch::diff_to_patch(&before, &after);
+    assert_eq!(generated.len(), 2);
+
+    let patch = jsonist::patch::patch_to_value(&generated).format(None);
+    let patched = jsonist::patch::patch_document(&json, &patch, None).expect("patch applies");
+    assert_eq!(patched, after.format(None));
+}
+
+#[test]
+fn integration_merge_patches_overlay_settings() {
+    let base = r#"{"name": "service", "env": {"debug": true, "port": 8080}}"#;
+    let overlay = r#"{"env": {"debug": null, "port": 9090}, "region": "eu"}"#;
+
+    let merged = jsonist::patch::merge_documents(base, overlay, None).expect("documents merge");
+    assert_eq!(
+        merged,
+        "{\n    \"name\": \"service\",\n    \"env\": {\n        \"port\": 9090\n    },\n    \"region\": \"eu\"\n}\n"
+    );
+}
+
+#[test]
+fn integration_reformatting_is_not_a_structural_difference() {
+    let json = complex_json("./tests/input/sample.json");
+    let formatted = format(json.clone(), None).expect("sample formats");
+
+    let changes = jsonist::diff::diff_documents(&json, &formatted, None).expect("documents parse");
+    assert_eq!(changes, vec![]);
+}
+
+#[test]
+fn integration_schema_violations_point_at_the_source() {
+    let json = complex_json("./tests/input/sample.json");
+    let schema = jsonist::schema::Schema::parse(
+        r#"{
+            "required": ["status"],
+            "properties": {
+                "origin_addresses": {"items": {"type": "string", "maxLength": 10}}
+            }
+        }"#,
+    )
+    .expect("schema is valid");
+
+    let violations = jsonist::schema::validate(&json, &schema).expect("sample parses");
+    let lines = violations
+        .iter()
+        .map(|violation| violation.to_string())
+        .collect::<Vec<String>>();
+    assert_eq!(
+        lines,
+        vec!["14:24: /origin_addresses/0: string is longer than 10 characters"]
+    );
+}
+
+#[test]
+fn integration_inferred_schemas_accept_their_samples() {
+    let samples = jsonist::parse_ndjson(
+        "{\"id\": 1, \"seen\": \"2024-05-01T10:00:00Z\"}\n\n{\"id\": 2.5, \"note\": null}\n",
+    )
+    .expect("samples parse");
+    let inferred = jsonist::schema::infer_schema(&samples);
+
+    let schema = jsonist::schema::Schema::new(inferred).expect("inferred schema is valid");
+    for sample in &samples {
+        assert_eq!(schema.validate(sample), vec![]);
+    }
+    assert_eq!(
+        schema.as_value().get("required"),
+        Some(&jsonist::Value::Array(vec![jsonist::Value::String(
+            String::from("id")
+        )]))
+    );
+}
+
+#[test]
+fn integration_filters_reshape_documents() {
+    let json = complex_json("./tests/input/sample.json");
+    let config = FormatConfig::new(Delimiter::Spaces(DelimiterCount::Two));
+    let printed = jsonist::filter::filter(
+        &json,
+        r#".rows[0].elements[:2][] | {miles: (.distance.value / 1609.344 | floor), text: "\(.duration.text) away"}"#,
+        Some(config),
+    )
+    .expect("filter runs");
+
+    assert_eq!(
+        printed,
+        "{\n  \"miles\": 227,\n  \"text\": \"3 hours 54 mins away\"\n}\n{\n  \"miles\": 94,\n  \"text\": \"1 hour 44 mins away\"\n}\n"
+    );
+}
+
+#[test]
+fn integration_every_feature_accepts_the_same_documents() {
+    for json in [
+        "[1 2]",
+        "[1,]",
+        "[1,,2]",
+        "[1] 2",
+        "{\"a\": 1,}",
+        "{\"a\" 1}",
+    ] {
+        assert!(format(json.to_owned(), None).is_err(), "{}", json);
+        assert!(cst::parse(json).is_err(), "{}", json);
+        assert!(jsonist::parse(json).is_err(), "{}", json);
+        assert!(jsonist::format_range(json, 0..1, None).is_err(), "{}", json);
+        assert!(
+            jsonist::source_map::format_with_source_map(json.to_owned(), None).is_err(),
+            "{}",
+            json
+        );
+    }
+}
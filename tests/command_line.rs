@@ -0,0 +1,111 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+fn jsonist(args: &[&str], input: &str) -> String {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_jsonist"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("jsonist runs");
+    child
+        .stdin
+        .take()
+        .expect("stdin is piped")
+        .write_all(input.as_bytes())
+        .expect("input is written");
+    let output = child.wait_with_output().expect("jsonist finishes");
+    assert!(output.status.success(), "jsonist {:?} failed", args);
+    String::from_utf8(output.stdout).expect("output is utf8")
+}
+
+#[test]
+fn integration_line_ending_option() {
+    assert_eq!(
+        jsonist(&["--line-ending", "crlf"], "[1, \"a\\nb\"]"),
+        "[\r\n    1,\r\n    \"a\\nb\"\r\n]\r\n"
+    );
+    assert_eq!(
+        jsonist(&["--line-ending=lf"], "[1,\r\n2]"),
+        "[\n    1,\n    2\n]\n"
+    );
+    assert_eq!(jsonist(&[], "[1,\r\n2]"), "[\r\n    1,\r\n    2\r\n]\r\n");
+    assert_eq!(jsonist(&[], "[1,\n2]\r\n"), "[\n    1,\n    2\n]\n");
+}
+
+#[test]
+fn integration_commands_keep_crlf_input_crlf() {
+    let overlay = std::env::temp_dir().join(format!("jsonist-overlay-{}.json", std::process::id()));
+    std::fs::write(&overlay, r#"{"b": 2}"#).expect("overlay is written");
+    let merged = jsonist(
+        &["merge", "-", overlay.to_str().expect("utf8 path")],
+        "{\"a\": 1}\r\n",
+    );
+    std::fs::remove_file(&overlay).expect("overlay is removed");
+    assert_eq!(merged, "{\r\n    \"a\": 1,\r\n    \"b\": 2\r\n}\r\n");
+
+    let document = "{\"a\": [1,\r\n2]}\r\n";
+    assert_eq!(
+        jsonist(&["get", "/a"], document),
+        "[\r\n    1,\r\n    2\r\n]\r\n"
+    );
+    assert_eq!(
+        jsonist(&["flatten"], document),
+        "{\r\n    \"a.0\": 1,\r\n    \"a.1\": 2\r\n}\r\n"
+    );
+    assert_eq!(
+        jsonist(&["convert", "--from", "csv"], "a\r\n1\r\n"),
+        "[\r\n    {\r\n        \"a\": 1\r\n    }\r\n]\r\n"
+    );
+}
+
+const SECRETS: &str = r#"{"user": {"name": "ada", "password": "hunter2"}}"#;
+
+#[test]
+fn integration_redaction_applies_to_every_command() {
+    let rows = r#"[{"name": "ada", "password": "hunter2"}]"#;
+    let commands: [(&[&str], &str); 6] = [
+        (&["--redact", "password"], SECRETS),
+        (&["--redact", "password", "html"], SECRETS),
+        (
+            &["--redact", "password", "convert", "--to", "yaml"],
+            SECRETS,
+        ),
+        (&["--redact", "password", "convert", "--to", "csv"], rows),
+        (&["--redact", "password", "get", "/user"], SECRETS),
+        (
+            &["--redact", "/user/password", "query", "$..password"],
+            SECRETS,
+        ),
+    ];
+    for (args, input) in commands {
+        let printed = jsonist(args, input);
+        assert!(
+            printed.contains("[REDACTED]"),
+            "{:?} printed {}",
+            args,
+            printed
+        );
+        assert!(
+            !printed.contains("hunter2"),
+            "{:?} printed {}",
+            args,
+            printed
+        );
+    }
+}
+
+#[test]
+fn integration_redaction_pointers_are_relative_to_the_document() {
+    assert_eq!(
+        jsonist(&["--redact", "/password", "get", "/user"], SECRETS),
+        "{\n    \"name\": \"ada\",\n    \"password\": \"hunter2\"\n}\n"
+    );
+    assert_eq!(
+        jsonist(
+            &["--mask", "query", "$.card"],
+            r#"{"card": "4111111111111111"}"#
+        ),
+        "[\n    \"************1111\"\n]\n"
+    );
+}
@@ -0,0 +1,127 @@
+extern crate jsonist;
+
+use jsonist::{lsp, parse, Value};
+
+fn frame(body: &str) -> String {
+    format!("Content-Length: {}\r\n\r\n{}", body.len(), body)
+}
+
+fn responses(output: Vec<u8>) -> Vec<Value> {
+    let output = String::from_utf8(output).expect("output is utf8");
+    output
+        .split("Content-Length: ")
+        .filter(|message| !message.is_empty())
+        .map(|message| {
+            let (_, body) = message.split_once("\r\n\r\n").expect("framed message");
+            parse(body).expect("responses are json")
+        })
+        .collect()
+}
+
+#[test]
+fn integration_language_server_session() {
+    let input = [
+        r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}"#,
+        r#"{"jsonrpc":"2.0","method":"initialized","params":{}}"#,
+        r#"{"jsonrpc":"2.0","method":"textDocument/didOpen","params":{"textDocument":{"uri":"file:///a.json","text":"{\"a\": 1, \"a\": 2}"}}}"#,
+        r#"{"jsonrpc":"2.0","method":"textDocument/didChange","params":{"textDocument":{"uri":"file:///a.json"},"contentChanges":[{"text":"{\"a\":[1,2]}"}]}}"#,
+        r#"{"jsonrpc":"2.0","id":2,"method":"textDocument/formatting","params":{"textDocument":{"uri":"file:///a.json"},"options":{"tabSize":2,"insertSpaces":true}}}"#,
+        r#"{"jsonrpc":"2.0","id":3,"method":"textDocument/hover","params":{}}"#,
+        r#"{"jsonrpc":"2.0","id":5,"method":"textDocument/documentSymbol","params":{"textDocument":{"uri":"file:///a.json"}}}"#,
+        r#"{"jsonrpc":"2.0","id":6,"method":"textDocument/selectionRange","params":{"textDocument":{"uri":"file:///a.json"},"positions":[{"line":0,"character":6}]}}"#,
+        r#"{"jsonrpc":"2.0","id":4,"method":"shutdown"}"#,
+        r#"{"jsonrpc":"2.0","method":"exit"}"#,
+    ]
+    .iter()
+    .map(|body| frame(body))
+    .collect::<String>();
+
+    let mut output = vec![];
+    lsp::serve(input.as_bytes(), &mut output).expect("server runs");
+    let messages = responses(output);
+
+    assert_eq!(messages.len(), 8);
+
+    let capabilities = messages[0]
+        .get("result")
+        .and_then(|r| r.get("capabilities"));
+    assert_eq!(
+        capabilities.and_then(|c| c.get("documentFormattingProvider")),
+        Some(&Value::Bool(true))
+    );
+
+    let diagnostics = messages[1]
+        .get("params")
+        .and_then(|p| p.get("diagnostics"))
+        .and_then(Value::as_array)
+        .expect("diagnostics");
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(
+        diagnostics[0].get("message").and_then(Value::as_str),
+        Some("Duplicate key ('a') entry.")
+    );
+
+    let cleared = messages[2].get("params").and_then(|p| p.get("diagnostics"));
+    assert_eq!(cleared, Some(&Value::Array(vec![])));
+
+    let edits = messages[3]
+        .get("result")
+        .and_then(Value::as_array)
+        .expect("edits");
+    assert_eq!(
+        edits[0].get("newText").and_then(Value::as_str),
+        Some("{\n  \"a\": [\n    1,\n    2\n  ]\n}\n")
+    );
+
+    let error = messages[4].get("error").and_then(|e| e.get("code"));
+    assert_eq!(error, Some(&Value::Number(-32601.0)));
+
+    let symbols = messages[5]
+        .get("result")
+        .and_then(Value::as_array)
+        .expect("symbols");
+    assert_eq!(symbols[0].get("name").and_then(Value::as_str), Some("a"));
+    assert_eq!(symbols[0].get("kind"), Some(&Value::Number(18.0)));
+
+    let selection = messages[6]
+        .get("result")
+        .and_then(Value::as_array)
+        .expect("selection ranges");
+    let innermost = selection[0].get("range").and_then(|r| r.get("start"));
+    assert_eq!(
+        innermost.and_then(|s| s.get("character")),
+        Some(&Value::Number(6.0))
+    );
+    assert!(selection[0].get("parent").is_some());
+
+    assert_eq!(messages[7].get("result"), Some(&Value::Null));
+}
+
+#[test]
+fn integration_language_server_reports_invalid_numbers() {
+    let input = [
+        r#"{"jsonrpc":"2.0","method":"textDocument/didOpen","params":{"textDocument":{"uri":"file:///a.json","text":"[1, -]"}}}"#,
+        r#"{"jsonrpc":"2.0","id":1,"method":"textDocument/formatting","params":{"textDocument":{"uri":"file:///a.json"},"options":{"tabSize":2,"insertSpaces":true}}}"#,
+        r#"{"jsonrpc":"2.0","method":"exit"}"#,
+    ]
+    .iter()
+    .map(|body| frame(body))
+    .collect::<String>();
+
+    let mut output = vec![];
+    lsp::serve(input.as_bytes(), &mut output).expect("server runs");
+    let messages = responses(output);
+
+    let diagnostics = messages[0]
+        .get("params")
+        .and_then(|p| p.get("diagnostics"))
+        .and_then(Value::as_array)
+        .expect("diagnostics");
+    let start = diagnostics[0]
+        .get("range")
+        .and_then(|r| r.get("start"))
+        .and_then(|s| s.get("character"));
+    assert_eq!(start, Some(&Value::Number(4.0)));
+
+    assert_eq!(messages[1].get("result"), Some(&Value::Null));
+}